@@ -1,16 +1,18 @@
 #![allow(non_camel_case_types)]
 
 use self::sealed::Sealed;
-use crate::{batch::Batch, InNamespace};
+use crate::{batch::Batch, InNamespace, MathMlAttribute, SvgAttribute};
 
 mod sealed {
-    use crate::{Attribute, InNamespace};
+    use crate::{Attribute, InNamespace, MathMlAttribute, SvgAttribute};
 
     pub trait Sealed {}
 
     impl Sealed for Attribute {}
+    impl Sealed for SvgAttribute {}
+    impl Sealed for MathMlAttribute {}
     impl<'a> Sealed for InNamespace<'a, Attribute> {}
-    impl<'a> Sealed for &'a str {}
+    impl Sealed for &str {}
     impl<'a, 'b> Sealed for InNamespace<'b, &'a str> {}
 }
 
@@ -20,6 +22,8 @@ pub enum AnyAttribute<'a, 'b> {
     InNamespace(InNamespace<'a, Attribute>),
     Str(&'a str),
     InNamespaceStr(InNamespace<'a, &'b str>),
+    Svg(SvgAttribute),
+    MathMl(MathMlAttribute),
 }
 
 impl AnyAttribute<'_, '_> {
@@ -29,6 +33,8 @@ impl AnyAttribute<'_, '_> {
             AnyAttribute::InNamespace(a) => a.encode_u8_discriminant_prealloc(v),
             AnyAttribute::Str(a) => a.encode_u8_discriminant_prealloc(v),
             AnyAttribute::InNamespaceStr(a) => a.encode_u8_discriminant_prealloc(v),
+            AnyAttribute::Svg(a) => a.encode_u8_discriminant_prealloc(v),
+            AnyAttribute::MathMl(a) => a.encode_u8_discriminant_prealloc(v),
         }
     }
 
@@ -38,6 +44,8 @@ impl AnyAttribute<'_, '_> {
             AnyAttribute::InNamespace(_) => 1 + 1 + 2,
             AnyAttribute::Str(_) => 1 + 2,
             AnyAttribute::InNamespaceStr(_) => 1 + 2 + 2,
+            AnyAttribute::Svg(_) => 1,
+            AnyAttribute::MathMl(_) => 1,
         }
     }
 }
@@ -186,7 +194,7 @@ impl<'a, 'b> From<InNamespace<'a, &'b str>> for AnyAttribute<'a, 'b> {
 }
 
 macro_rules! attributes {
-    ($($i: ident),*) => {
+    ($($i: ident: $name: literal),*) => {
         /// All built-in attributes
         /// These are the attributes can be encoded with a single byte so they are more efficient (but less flexable) than a &str attribute
         #[derive(Copy, Clone)]
@@ -196,15 +204,32 @@ macro_rules! attributes {
             ),*
         }
 
+        impl Attribute {
+            /// The number of built-in attributes. Namespaced attributes are encoded after these.
+            pub(crate) const COUNT: usize = [$($name),*].len();
+
+            /// Every attribute in the order they are encoded
+            pub(crate) const ALL: [Self; Self::COUNT] = [$(Self::$i),*];
+
+            /// The name of the attribute in the DOM
+            pub const fn as_str(&self) -> &'static str {
+                match self {
+                    $(
+                        Self::$i => $name,
+                    )*
+                }
+            }
+        }
+
         pub struct NotElementError;
 
         impl std::str::FromStr for Attribute {
             type Err = NotElementError;
 
             fn from_str(s: &str) -> Result<Self, Self::Err> {
-                Ok(match s{
+                Ok(match s {
                     $(
-                        stringify!($i) => Self::$i,
+                        $name => Self::$i,
                     )*
                     _ => return Err(NotElementError)
                 })
@@ -214,157 +239,157 @@ macro_rules! attributes {
 }
 
 attributes! {
-    accept_charset,
-    accept,
-    accesskey,
-    action,
-    align,
-    allow,
-    alt,
-    aria_atomic,
-    aria_busy,
-    aria_controls,
-    aria_current,
-    aria_describedby,
-    aria_description,
-    aria_details,
-    aria_disabled,
-    aria_dropeffect,
-    aria_errormessage,
-    aria_flowto,
-    aria_grabbed,
-    aria_haspopup,
-    aria_hidden,
-    aria_invalid,
-    aria_keyshortcuts,
-    aria_label,
-    aria_labelledby,
-    aria_live,
-    aria_owns,
-    aria_relevant,
-    aria_roledescription,
-    r#async,
-    autocapitalize,
-    autocomplete,
-    autofocus,
-    autoplay,
-    background,
-    bgcolor,
-    border,
-    buffered,
-    capture,
-    challenge,
-    charset,
-    checked,
-    cite,
-    class,
-    code,
-    codebase,
-    color,
-    cols,
-    colspan,
-    content,
-    contenteditable,
-    contextmenu,
-    controls,
-    coords,
-    crossorigin,
-    csp,
-    data,
-    datetime,
-    decoding,
-    default,
-    defer,
-    dir,
-    dirname,
-    disabled,
-    download,
-    draggable,
-    enctype,
-    enterkeyhint,
-    r#for,
-    form,
-    formaction,
-    formenctype,
-    formmethod,
-    formnovalidate,
-    formtarget,
-    headers,
-    height,
-    hidden,
-    high,
-    href,
-    hreflang,
-    http_equiv,
-    icon,
-    id,
-    importance,
-    inputmode,
-    integrity,
-    intrinsicsize,
-    ismap,
-    itemprop,
-    keytype,
-    kind,
-    label,
-    lang,
-    language,
-    list,
-    loading,
-    r#loop,
-    low,
-    manifest,
-    max,
-    maxlength,
-    media,
-    method,
-    min,
-    minlength,
-    multiple,
-    muted,
-    name,
-    novalidate,
-    open,
-    optimum,
-    pattern,
-    ping,
-    placeholder,
-    poster,
-    preload,
-    radiogroup,
-    readonly,
-    referrerpolicy,
-    rel,
-    required,
-    reversed,
-    role,
-    rows,
-    rowspan,
-    sandbox,
-    scope,
-    scoped,
-    selected,
-    shape,
-    size,
-    sizes,
-    slot,
-    span,
-    spellcheck,
-    src,
-    srcdoc,
-    srclang,
-    srcset,
-    start,
-    step,
-    style,
-    summary,
-    tabindex,
-    target,
-    title,
-    translate,
-    r#type,
-    usemap,
-    value,
-    width,
-    wrap
+    accept_charset: "accept-charset",
+    accept: "accept",
+    accesskey: "accesskey",
+    action: "action",
+    align: "align",
+    allow: "allow",
+    alt: "alt",
+    aria_atomic: "aria-atomic",
+    aria_busy: "aria-busy",
+    aria_controls: "aria-controls",
+    aria_current: "aria-current",
+    aria_describedby: "aria-describedby",
+    aria_description: "aria-description",
+    aria_details: "aria-details",
+    aria_disabled: "aria-disabled",
+    aria_dropeffect: "aria-dropeffect",
+    aria_errormessage: "aria-errormessage",
+    aria_flowto: "aria-flowto",
+    aria_grabbed: "aria-grabbed",
+    aria_haspopup: "aria-haspopup",
+    aria_hidden: "aria-hidden",
+    aria_invalid: "aria-invalid",
+    aria_keyshortcuts: "aria-keyshortcuts",
+    aria_label: "aria-label",
+    aria_labelledby: "aria-labelledby",
+    aria_live: "aria-live",
+    aria_owns: "aria-owns",
+    aria_relevant: "aria-relevant",
+    aria_roledescription: "aria-roledescription",
+    r#async: "async",
+    autocapitalize: "autocapitalize",
+    autocomplete: "autocomplete",
+    autofocus: "autofocus",
+    autoplay: "autoplay",
+    background: "background",
+    bgcolor: "bgcolor",
+    border: "border",
+    buffered: "buffered",
+    capture: "capture",
+    challenge: "challenge",
+    charset: "charset",
+    checked: "checked",
+    cite: "cite",
+    class: "class",
+    code: "code",
+    codebase: "codebase",
+    color: "color",
+    cols: "cols",
+    colspan: "colspan",
+    content: "content",
+    contenteditable: "contenteditable",
+    contextmenu: "contextmenu",
+    controls: "controls",
+    coords: "coords",
+    crossorigin: "crossorigin",
+    csp: "csp",
+    data: "data",
+    datetime: "datetime",
+    decoding: "decoding",
+    default: "default",
+    defer: "defer",
+    dir: "dir",
+    dirname: "dirname",
+    disabled: "disabled",
+    download: "download",
+    draggable: "draggable",
+    enctype: "enctype",
+    enterkeyhint: "enterkeyhint",
+    r#for: "for",
+    form: "form",
+    formaction: "formaction",
+    formenctype: "formenctype",
+    formmethod: "formmethod",
+    formnovalidate: "formnovalidate",
+    formtarget: "formtarget",
+    headers: "headers",
+    height: "height",
+    hidden: "hidden",
+    high: "high",
+    href: "href",
+    hreflang: "hreflang",
+    http_equiv: "http-equiv",
+    icon: "icon",
+    id: "id",
+    importance: "importance",
+    inputmode: "inputmode",
+    integrity: "integrity",
+    intrinsicsize: "intrinsicsize",
+    ismap: "ismap",
+    itemprop: "itemprop",
+    keytype: "keytype",
+    kind: "kind",
+    label: "label",
+    lang: "lang",
+    language: "language",
+    list: "list",
+    loading: "loading",
+    r#loop: "loop",
+    low: "low",
+    manifest: "manifest",
+    max: "max",
+    maxlength: "maxlength",
+    media: "media",
+    method: "method",
+    min: "min",
+    minlength: "minlength",
+    multiple: "multiple",
+    muted: "muted",
+    name: "name",
+    novalidate: "novalidate",
+    open: "open",
+    optimum: "optimum",
+    pattern: "pattern",
+    ping: "ping",
+    placeholder: "placeholder",
+    poster: "poster",
+    preload: "preload",
+    radiogroup: "radiogroup",
+    readonly: "readonly",
+    referrerpolicy: "referrerpolicy",
+    rel: "rel",
+    required: "required",
+    reversed: "reversed",
+    role: "role",
+    rows: "rows",
+    rowspan: "rowspan",
+    sandbox: "sandbox",
+    scope: "scope",
+    scoped: "scoped",
+    selected: "selected",
+    shape: "shape",
+    size: "size",
+    sizes: "sizes",
+    slot: "slot",
+    span: "span",
+    spellcheck: "spellcheck",
+    src: "src",
+    srcdoc: "srcdoc",
+    srclang: "srclang",
+    srcset: "srcset",
+    start: "start",
+    step: "step",
+    style: "style",
+    summary: "summary",
+    tabindex: "tabindex",
+    target: "target",
+    title: "title",
+    translate: "translate",
+    r#type: "type",
+    usemap: "usemap",
+    value: "value",
+    width: "width",
+    wrap: "wrap"
 }
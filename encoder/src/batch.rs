@@ -1,77 +1,487 @@
+use std::ops::Range;
+
 use crate::{
-    ElementBuilder, IntoAttribue, IntoElement, MaybeId, NodeId, TextBuilder, WritableText,
+    CommentBuilder, ElementBuilder, InsertPosition, IntoAttribue, IntoElement, IntoStyle, MaybeId,
+    NodeBuilder, NodeId, ShadowRootMode, StyleSheetId, TemplateId, TextBuilder, WritableText,
 };
 
-// operations that have no booleans can be encoded as a half byte, these are placed first
-pub enum Op {
-    /// Navigates to the last node to the first child of the current node.
-    FirstChild = 0,
+/// The version of the binary format batches are encoded in. It changes whenever a batch encoded by one version would be read differently by an interpreter of another version.
+///
+/// Static batches, streams, recordings and the javascript interpreter carry the version they were built with, and it is checked before a batch is run.
+pub const FORMAT_VERSION: u8 = 3;
+
+/// A batch was encoded in a different version of the format than the one this version of sledgehammer reads
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VersionMismatch {
+    /// The version the batch was encoded in
+    pub found: u8,
+    /// The version this version of sledgehammer reads, see [`FORMAT_VERSION`]
+    pub expected: u8,
+}
+
+impl std::fmt::Display for VersionMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "the batch was encoded in version {} of the sledgehammer format, but version {} is supported. Build it with the same release of sledgehammer-prebuild and sledgehammer-encoder",
+            self.found, self.expected
+        )
+    }
+}
+
+impl std::error::Error for VersionMismatch {}
 
-    /// Navigates to the last node to the last child of the current node.
-    NextSibling = 1,
+/// Operations with a value of [`EXTENDED_OP`] or larger are encoded as [`EXTENDED_OP`] followed by a byte with the value of the operation.
+pub(crate) const EXTENDED_OP: u8 = 0x1F;
 
-    /// Navigates to the last node to the parent of the current node.
-    ParentNode = 2,
+/// The kinds of operands an operation can encode. Every operation declares its operands, see [`Op::operands`], and the encoder, the [`NativeInterpreter`](crate::NativeInterpreter) and the javascript interpreters decode them in that order.
+///
+/// Some operands are encoded as bools in the operation byte. The bools are assigned to the operands in order, starting with bit `0x20`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operand {
+    /// A [`NodeId`] as a u32
+    Id,
+    /// An optional id to store a new node with: a bool and a u32 if it is set
+    Store,
+    /// The node an operation works on: a bool and the u32 id of the node if it is set, or the last node
+    Node,
+    /// One or more nodes: a bool that is set for many nodes followed by the u8 count and the nodes with a u8 discriminant, or a single [`Operand::Node`]. This must be the last operand that uses bools
+    Nodes,
+    /// A bool
+    Flag,
+    /// A [`ShadowRootMode`] as a bool that is set for closed shadow roots
+    Mode,
+    /// An [`InsertPosition`] as a u8
+    Position,
+    /// A u16
+    U16,
+    /// A u32
+    U32,
+    /// A [`TemplateId`] as a u32
+    TemplateId,
+    /// A [`StyleSheetId`] as a u32
+    StyleSheetId,
+    /// The u16 length of a string in the strings of the batch
+    Str,
+    /// The u32 length of a string in the strings of the batch, for markup like html and css that can be longer than a u16 length allows
+    LongStr,
+    /// An element name, see [`IntoElement`]
+    Element,
+    /// A node built with an [`ElementBuilder`]
+    FullElement,
+    /// A u8 count followed by nodes built with [`NodeBuilder`]s
+    Roots,
+    /// An attribute name and namespace, see [`IntoAttribue`]. The first bool is set if the name is a string, and the second if the attribute has a namespace
+    Attribute,
+    /// A style property name with a u8 discriminant, see [`IntoStyle`]
+    Style,
+    /// A u16 count followed by style property names and values
+    Styles,
+    /// A range of ids: a bool that is set for a range, the u32 start and the u32 end if the bool is set
+    IdRange,
+}
 
-    /// Stores the last node with a new id.
-    StoreWithId = 3,
+impl Operand {
+    /// The number of bools the operand uses in the operation byte
+    pub const fn bools(self) -> u8 {
+        match self {
+            Operand::Store | Operand::Node | Operand::Flag | Operand::Mode | Operand::IdRange => 1,
+            Operand::Nodes | Operand::Attribute => 2,
+            _ => 0,
+        }
+    }
+}
 
-    /// Manually set the last node.
-    SetLastNode = 4,
+/// The operations of the format. Every operation has a code, the operands it encodes in order, an optional [`Batch`] method that encodes it and the javascript that runs it with the decoded operands bound to their names.
+///
+/// The list is passed to a macro that generates code from it: [`ops!`] generates [`Op`] and the [`Batch`] methods, and the native interpreter generates its decoder. The javascript of the interpreters is generated with [`spec`](crate::spec).
+macro_rules! op_list {
+    ($callback:ident) => {
+        $callback! {
+            /// Navigates to the last node to the first child of the current node.
+            FirstChild = 0 () => pub fn first_child {
+                "inptr.lastNode = inptr.lastNode.firstChild;"
+            }
 
-    /// Stop
-    Stop = 5,
+            /// Navigates to the last node to the next sibling of the current node.
+            NextSibling = 1 () => pub fn next_sibling {
+                "inptr.lastNode = inptr.lastNode.nextSibling;"
+            }
 
-    /// Build Full Element
-    BuildFullElement = 6,
+            /// Navigates to the last node to the parent of the current node.
+            ParentNode = 2 () => pub fn parent_node {
+                "inptr.lastNode = inptr.lastNode.parentNode;"
+            }
 
-    /// Pop the topmost node from our stack and append them to the node
-    AppendChildren = 7,
+            /// Store the last node with the given id. This is useful when traversing the document tree.
+            StoreWithId = 3 (id: Id) => pub fn store_with_id {
+                "inptr.nodes[id] = inptr.lastNode;"
+            }
 
-    /// Replace a given (single) node with a handful of nodes currently on the stack.
-    ReplaceWith = 8,
+            /// Set the last node to the given id. The last node can be used to traverse the document tree without passing objects between wasm and js every time.
+            SetLastNode = 4 (id: Id) => pub fn set_last_node {
+                "inptr.lastNode = inptr.nodes[id];"
+            }
 
-    /// Insert a number of nodes after a given node.
-    InsertAfter = 9,
+            /// Stop
+            Stop = 5 () {
+                "return true;"
+            }
 
-    /// Insert a number of nodes before a given node.
-    InsertBefore = 10,
+            /// Build a full element, slightly more efficent than creating the element creating the element with `create_element` and then setting the attributes.
+            BuildFullElement = 6 (el: FullElement) => pub fn build_full_element {
+                "inptr.lastNode = el;"
+            }
 
-    /// Remove a particular node from the DOM
-    Remove = 11,
+            /// Append a node to the children of a node.
+            AppendChildren = 7 (root: Node, child: Node) => pub fn append_child {
+                "root.appendChild(child);"
+            }
 
-    /// Create a new text node
-    CreateTextNode = 12,
+            /// Replace a given (single) node with one or more nodes.
+            ReplaceWith = 8 (root: Node, nodes: Nodes) => pub fn replace_with_nodes {
+                "root.replaceWith(...nodes);"
+            }
 
-    /// Create a new element node
-    CreateElement = 13,
+            /// Insert a number of nodes after a given node.
+            InsertAfter = 9 (root: Node, nodes: Nodes) => pub fn insert_nodes_after {
+                "root.after(...nodes);"
+            }
 
-    /// Set the textcontent of a node.
-    SetText = 14,
+            /// Insert a number of nodes before a given node.
+            InsertBefore = 10 (root: Node, nodes: Nodes) => pub fn insert_nodes_before {
+                "root.before(...nodes);"
+            }
 
-    /// Set the value of a node's attribute.
-    SetAttribute = 15,
+            /// Remove a particular node from the DOM, and release the ids of the node and of any stored descendants of the node if the flag is set. The descendants include the nodes in shadow roots attached to the node or its descendants.
+            Remove = 11 (node: Node, free: Flag) => fn encode_remove {
+                "node.remove();"
+                "if (free) inptr.freeContained(node);"
+            }
 
-    /// Remove an attribute from a node.
-    RemoveAttribute = 16,
+            /// Create a new text node
+            CreateTextNode = 12 (text: Str, id: Store) => pub fn create_text_node {
+                "inptr.lastNode = doc.createTextNode(text);"
+                "if (id >= 0) inptr.nodes[id] = inptr.lastNode;"
+            }
 
-    /// Set a style property on a node.
-    SetStyle = 17,
+            /// Create a new element node
+            CreateElement = 13 (tag: Element, id: Store) => pub fn create_element {
+                "inptr.lastNode = tag;"
+                "if (id >= 0) inptr.nodes[id] = tag;"
+            }
+
+            /// Set the textcontent of a node.
+            SetText = 14 (text: Str, root: Node) => pub fn set_text {
+                "root.textContent = text;"
+            }
+
+            /// Set the value of a node's attribute.
+            SetAttribute = 15 (attr: Attribute, value: Str, root: Node) => pub fn set_attribute {
+                "if (attrNs === null) root.setAttribute(attr, value);"
+                "else root.setAttributeNS(attrNs, attr, value);"
+            }
+
+            /// Remove an attribute from a node.
+            RemoveAttribute = 16 (attr: Attribute, root: Node) => pub fn remove_attribute {
+                "if (attrNs === null) root.removeAttribute(attr);"
+                "else root.removeAttributeNS(attrNs, attr);"
+            }
+
+            /// Set a style property on a node, with the `important` priority if the flag is set.
+            SetStyle = 17 (style: Style, value: Str, important: Flag, root: Node) => fn encode_set_style {
+                "root.style.setProperty(style, value, important ? \"important\" : \"\");"
+            }
+
+            /// Remove a style property from a node.
+            RemoveStyle = 18 (style: Style, root: Node) => pub fn remove_style {
+                "root.style.removeProperty(style);"
+            }
+
+            /// Clones a node and store the clone with an id.
+            CloneNode = 19 (node: Node, id: Store) => fn encode_clone_node {
+                "inptr.lastNode = node.cloneNode(true);"
+                "if (id >= 0) inptr.nodes[id] = inptr.lastNode;"
+            }
+
+            /// Does nothing, but allows us to skip a byte.
+            NoOp = 20 () {}
+
+            /// Set the inner html of a node. If an id is given, the first child of the node after parsing the html is stored with that id.
+            ///
+            /// The html is inserted without sanitization, so it should only be used with trusted markup.
+            SetInnerHtml = 21 (html: LongStr, root: Node, id: Store) => pub fn set_inner_html {
+                "root.innerHTML = html;"
+                "if (id >= 0) inptr.nodes[id] = root.firstChild;"
+            }
+
+            /// Parse html and insert it at the given position relative to a node. If an id is given, the first node that was inserted is stored with that id. If the html is empty, no node is stored with the id.
+            ///
+            /// The html is inserted without sanitization, so it should only be used with trusted markup.
+            InsertAdjacentHtml = 22 (position: Position, html: LongStr, root: Node, id: Store) => pub fn insert_adjacent_html {
+                "node = inptr.insertAdjacentHtml(root, position, html);"
+                "if (id >= 0) inptr.nodes[id] = node;"
+            }
+
+            /// Create a new comment node
+            CreateComment = 23 (text: Str, id: Store) => pub fn create_comment {
+                "inptr.lastNode = doc.createComment(text);"
+                "if (id >= 0) inptr.nodes[id] = inptr.lastNode;"
+            }
+
+            /// Create a new document fragment. Nodes can be appended to the fragment with [`Batch::append_child`], and all of the nodes in the fragment are moved at once when the fragment is appended or inserted.
+            CreateFragment = 24 (id: Store) => pub fn create_fragment {
+                "inptr.lastNode = doc.createDocumentFragment();"
+                "if (id >= 0) inptr.nodes[id] = inptr.lastNode;"
+            }
+
+            /// Build a number of nodes into a template element that is held by the interpreter. The template can be instantiated any number of times with [`Batch::clone_template`].
+            ///
+            /// Ids assigned to nodes in the template refer to the nodes inside of the template, not to the clones.
+            CreateTemplate = 25 (id: TemplateId, roots: Roots) => pub fn create_template {
+                "inptr.templates[id] = doc.createElement(\"template\");"
+                "inptr.templates[id].content.append(...roots);"
+            }
+
+            /// Clone the content of a template into a new document fragment. The fragment becomes the last node and contains a copy of every root of the template.
+            CloneTemplate = 26 (template: TemplateId, id: Store) => pub fn clone_template {
+                "inptr.lastNode = doc.importNode(inptr.templates[template].content, true);"
+                "if (id >= 0) inptr.nodes[id] = inptr.lastNode;"
+            }
+
+            /// Attach a shadow root to a node and store the shadow root with the given id. The shadow root becomes the last node.
+            ///
+            /// Nodes can be added to the shadow root like any other node, for example with [`Batch::append_child`].
+            AttachShadow = 27 (root: Node, mode: Mode, id: Id) => pub fn attach_shadow {
+                "inptr.lastNode = inptr.nodes[id] = root.attachShadow({ mode });"
+                "inptr.shadowRoots.set(root, inptr.lastNode);"
+            }
 
-    /// Remove a style property from a node.
-    RemoveStyle = 18,
+            /// Create a new constructable style sheet from css and store it with the given id. The style sheet can be shared between many shadow roots with [`Batch::adopt_style_sheet`].
+            CreateStyleSheet = 28 (css: LongStr, id: StyleSheetId) => pub fn create_style_sheet {
+                "inptr.styleSheets[id] = new (doc.defaultView || window).CSSStyleSheet();"
+                "inptr.styleSheets[id].replaceSync(css);"
+            }
+
+            /// Add a style sheet to the adopted style sheets of a shadow root or document.
+            AdoptStyleSheet = 29 (root: Node, sheet: StyleSheetId) => pub fn adopt_style_sheet {
+                "root.adoptedStyleSheets = [...root.adoptedStyleSheets, inptr.styleSheets[sheet]];"
+            }
+
+            /// Release the nodes stored with any id in a range. The ids can be reused for other nodes afterwards.
+            FreeIds = 30 (ids: IdRange) => pub fn free_ids {
+                "inptr.nodes.fill(undefined, ids, idsEnd);"
+            }
+
+            /// Push a node onto the stack. Nodes on the stack can be consumed by [`Batch::append_children`], [`Batch::replace_with_stack`], [`Batch::insert_stack_after`] and [`Batch::insert_stack_before`].
+            PushRoot = 32 (root: Node) => pub fn push_root {
+                "inptr.stack.push(root);"
+            }
+
+            /// Pop the topmost n nodes from the stack and append them as children of the given node in the order they were pushed.
+            AppendChildrenFromStack = 33 (root: Node, n: U16) => pub fn append_children {
+                "root.append(...inptr.popStack(n));"
+            }
+
+            /// Pop the topmost n nodes from the stack and replace the given node with them in the order they were pushed.
+            ReplaceWithFromStack = 34 (root: Node, n: U16) => pub fn replace_with_stack {
+                "root.replaceWith(...inptr.popStack(n));"
+            }
+
+            /// Pop the topmost n nodes from the stack and insert them after the given node in the order they were pushed.
+            InsertAfterFromStack = 35 (root: Node, n: U16) => pub fn insert_stack_after {
+                "root.after(...inptr.popStack(n));"
+            }
+
+            /// Pop the topmost n nodes from the stack and insert them before the given node in the order they were pushed.
+            InsertBeforeFromStack = 36 (root: Node, n: U16) => pub fn insert_stack_before {
+                "root.before(...inptr.popStack(n));"
+            }
+
+            /// Append text to the data of a text or comment node.
+            AppendData = 37 (text: Str, root: Node) => pub fn append_data {
+                "root.appendData(text);"
+            }
+
+            /// Insert text into the data of a text or comment node. The offset is in UTF-16 code units, see [`utf16_offset`](crate::utf16_offset).
+            InsertData = 38 (offset: U32, text: Str, root: Node) => pub fn insert_data {
+                "root.insertData(offset, text);"
+            }
+
+            /// Delete a range of the data of a text or comment node. The offset and count are in UTF-16 code units, see [`utf16_offset`](crate::utf16_offset).
+            DeleteData = 39 (offset: U32, count: U32, root: Node) => pub fn delete_data {
+                "root.deleteData(offset, count);"
+            }
 
-    /// Clones a node.
-    CloneNode = 19,
+            /// Replace a range of the data of a text or comment node with new text. The offset and count are in UTF-16 code units, see [`utf16_offset`](crate::utf16_offset).
+            ReplaceData = 40 (offset: U32, count: U32, text: Str, root: Node) => pub fn replace_data {
+                "root.replaceData(offset, count, text);"
+            }
+
+            /// Set a number of style properties on a node. The node is only looked up once.
+            SetStyles = 41 (styles: Styles, root: Node) => pub fn set_styles {
+                "for (i = 0; i < styles.length; i += 2) root.style.setProperty(styles[i], styles[i + 1]);"
+            }
 
-    /// Does nothing, but allows us to skip a byte.
-    NoOp = 20,
+            /// Release the template stored with an id. The id can be reused for another template afterwards.
+            FreeTemplate = 42 (template: TemplateId) => pub fn free_template {
+                "inptr.templates[template] = undefined;"
+            }
+
+            /// Release the style sheet stored with an id. Documents and shadow roots that adopted the style sheet keep it, and the id can be reused for another style sheet afterwards.
+            FreeStyleSheet = 43 (sheet: StyleSheetId) => pub fn free_style_sheet {
+                "inptr.styleSheets[sheet] = undefined;"
+            }
+        }
+    };
+}
+pub(crate) use op_list;
+
+/// Generates [`Op`] and the [`Batch`] methods from [`op_list`]
+macro_rules! ops {
+    ($(
+        $(#[$meta:meta])*
+        $name:ident = $code:literal ($($arg:ident: $kind:ident),*) $(=> $vis:vis fn $method:ident)? { $($js:literal)* }
+    )*) => {
+        // operations that have no booleans can be encoded as a half byte, these are placed first
+        #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+        pub enum Op {
+            $(
+                $(#[$meta])*
+                $name = $code,
+            )*
+        }
+
+        impl Op {
+            /// Every operation in the order of their codes
+            pub const ALL: &'static [Op] = &[$(Op::$name),*];
+
+            /// The operation with a code, or `None` if no operation has the code
+            pub const fn from_code(code: u8) -> Option<Self> {
+                match code {
+                    $($code => Some(Op::$name),)*
+                    _ => None,
+                }
+            }
+
+            /// The name of the operation, like `CreateElement`
+            pub const fn name(self) -> &'static str {
+                match self {
+                    $(Op::$name => stringify!($name),)*
+                }
+            }
+
+            /// The names and kinds of the operands of the operation in the order they are encoded
+            pub const fn operands(self) -> &'static [(&'static str, Operand)] {
+                match self {
+                    $(Op::$name => &[$((stringify!($arg), Operand::$kind)),*],)*
+                }
+            }
+
+            /// The javascript that runs the operation, one statement per line. The operands are decoded into constants with their names first
+            pub const fn js(self) -> &'static [&'static str] {
+                match self {
+                    $(Op::$name => &[$($js),*],)*
+                }
+            }
+        }
+
+        impl Batch {
+            $(
+                ops!(@method [$(#[$meta])*] $name ($($arg: $kind),*) $($vis fn $method)?);
+            )*
+        }
+    };
+    (@method [$(#[$meta:meta])*] $name:ident ($($arg:ident: $kind:ident),*)) => {};
+    (@method [$(#[$meta:meta])*] $name:ident ($($arg:ident: $kind:ident),*) $vis:vis fn $method:ident) => {
+        $(#[$meta])*
+        #[allow(clippy::extra_unused_lifetimes)]
+        $vis fn $method<'a, 'b>(&mut self, $($arg: ops!(@param $kind)),*) {
+            self.encode_op(Op::$name);
+            $(ops!(@encode self, $kind, $arg);)*
+        }
+    };
+    (@param Id) => { NodeId };
+    (@param Store) => { Option<NodeId> };
+    (@param Node) => { MaybeId };
+    (@param Nodes) => { &[MaybeId] };
+    (@param Flag) => { bool };
+    (@param Mode) => { ShadowRootMode };
+    (@param Position) => { InsertPosition };
+    (@param U16) => { u16 };
+    (@param U32) => { u32 };
+    (@param TemplateId) => { TemplateId };
+    (@param StyleSheetId) => { StyleSheetId };
+    (@param Str) => { impl WritableText };
+    (@param LongStr) => { impl WritableText };
+    (@param Element) => { impl IntoElement<'a, 'b> };
+    (@param FullElement) => { ElementBuilder };
+    (@param Roots) => { &[NodeBuilder] };
+    (@param Attribute) => { impl IntoAttribue<'a, 'b> };
+    (@param Style) => { impl IntoStyle<'a> };
+    (@param Styles) => { &[(impl IntoStyle<'a> + Copy, &str)] };
+    (@param IdRange) => { Range<NodeId> };
+    (@encode $batch:ident, Id, $v:ident) => { $batch.encode_id($v) };
+    (@encode $batch:ident, Store, $v:ident) => { $batch.encode_optional_id($v) };
+    (@encode $batch:ident, Node, $v:ident) => { $batch.encode_maybe_id($v) };
+    (@encode $batch:ident, Nodes, $v:ident) => { $batch.encode_nodes($v) };
+    (@encode $batch:ident, Flag, $v:ident) => { $batch.encode_bool($v) };
+    (@encode $batch:ident, Mode, $v:ident) => { $batch.encode_bool($v == ShadowRootMode::Closed) };
+    (@encode $batch:ident, Position, $v:ident) => { $batch.msg.push($v as u8) };
+    (@encode $batch:ident, U16, $v:ident) => { $batch.encode_u16($v) };
+    (@encode $batch:ident, U32, $v:ident) => { $batch.encode_u32($v) };
+    (@encode $batch:ident, TemplateId, $v:ident) => { $batch.encode_u32($v.0) };
+    (@encode $batch:ident, StyleSheetId, $v:ident) => { $batch.encode_u32($v.0) };
+    (@encode $batch:ident, Str, $v:ident) => { $batch.encode_str($v) };
+    (@encode $batch:ident, LongStr, $v:ident) => { $batch.encode_long_str($v) };
+    (@encode $batch:ident, Element, $v:ident) => { $v.encode($batch) };
+    (@encode $batch:ident, FullElement, $v:ident) => { $v.encode($batch) };
+    (@encode $batch:ident, Roots, $v:ident) => { $batch.encode_roots($v) };
+    (@encode $batch:ident, Attribute, $v:ident) => { $v.encode($batch) };
+    (@encode $batch:ident, Style, $v:ident) => { $batch.encode_style($v) };
+    (@encode $batch:ident, Styles, $v:ident) => { $batch.encode_styles($v) };
+    (@encode $batch:ident, IdRange, $v:ident) => { $batch.encode_id_range($v) };
+}
+
+op_list!(ops);
+
+impl Op {
+    /// The code of an operation from its byte in an operation group. Extended operations store their code in the first byte of their data, which starts at `data_start` in the message.
+    pub fn decode_code(op: u8, msg: &[u8], data_start: usize) -> u8 {
+        match op & 0x1F {
+            EXTENDED_OP => msg.get(data_start).copied().unwrap_or(EXTENDED_OP),
+            code => code,
+        }
+    }
+
+    /// The code of the first operation in an encoded message, or the code of [`Op::Stop`] if the message is empty
+    pub fn first_code(msg: &[u8]) -> u8 {
+        // the data of the first operation starts after its group of four operation bytes
+        msg.first()
+            .map_or(Op::Stop as u8, |&op| Op::decode_code(op, msg, 4))
+    }
 }
 
 /// A batch of operations ready to perform on the DOM.
 pub trait PreparedBatch {
     fn msg(&self) -> &[u8];
     fn str(&self) -> &[u8];
+
+    /// The version of the format the batch was encoded in. Batches encoded at runtime always have the current [`FORMAT_VERSION`].
+    fn version(&self) -> u8 {
+        FORMAT_VERSION
+    }
+
+    /// Check that the batch was encoded in the version of the format this version of sledgehammer reads
+    fn check_version(&self) -> Result<(), VersionMismatch> {
+        match self.version() {
+            FORMAT_VERSION => Ok(()),
+            found => Err(VersionMismatch {
+                found,
+                expected: FORMAT_VERSION,
+            }),
+        }
+    }
 }
 
 /// A batch of operations ready to perform on the DOM.
@@ -89,7 +499,7 @@ impl PreparedBatch for FinalizedBatch {
     }
 }
 
-impl<'a> PreparedBatch for &'a FinalizedBatch {
+impl PreparedBatch for &FinalizedBatch {
     fn msg(&self) -> &[u8] {
         &self.msg
     }
@@ -100,9 +510,31 @@ impl<'a> PreparedBatch for &'a FinalizedBatch {
 
 /// A batch of static operations ready to perform on the DOM.
 /// This is meant to be generated from FinalizedBatch from a macro.
+///
+/// The batch may be compiled long before it is run, so it records the [`FORMAT_VERSION`] it was encoded in.
 pub struct StaticBatch {
     pub msg: &'static [u8],
     pub str: &'static [u8],
+    pub version: u8,
+}
+
+impl StaticBatch {
+    /// Start a [`Batch`] with a template the `html!` macro encoded without finalizing it, so the values of its holes can be encoded after it. The template must fill its last group of operations.
+    ///
+    /// # Panics
+    ///
+    /// If the template was encoded in another version of the format
+    #[doc(hidden)]
+    pub fn into_template_batch(self) -> Batch {
+        if let Err(err) = self.check_version() {
+            panic!("{err}");
+        }
+        Batch {
+            msg: self.msg.to_vec(),
+            str_buf: self.str.to_vec(),
+            ..Batch::default()
+        }
+    }
 }
 
 impl PreparedBatch for StaticBatch {
@@ -112,15 +544,21 @@ impl PreparedBatch for StaticBatch {
     fn str(&self) -> &[u8] {
         self.str
     }
+    fn version(&self) -> u8 {
+        self.version
+    }
 }
 
-impl<'a> PreparedBatch for &'a StaticBatch {
+impl PreparedBatch for &StaticBatch {
     fn msg(&self) -> &[u8] {
         self.msg
     }
     fn str(&self) -> &[u8] {
         self.str
     }
+    fn version(&self) -> u8 {
+        self.version
+    }
 }
 
 /// A batch of operations to perform on the DOM.
@@ -129,6 +567,8 @@ impl<'a> PreparedBatch for &'a StaticBatch {
 /// This is useful for building up a batch of operations to perform on the DOM many times. If the operation is only performed once, it is better to use the `MsgChannel` directly because it reuses the same allocation from the last batch of operations.
 /// See [`MsgChannel::append`] and [`MsgChannel::run_batch`] for examples.
 /// The methods on this struct are a subset of the methods on [`MsgChannel`] and work the same with the exception of [`Batch::finalize`].
+///
+/// Strings are encoded with a u16 length, so the methods panic with a string longer than 65535 bytes. The html of [`Batch::set_inner_html`] and [`Batch::insert_adjacent_html`] and the css of [`Batch::create_style_sheet`] have a u32 length instead.
 pub struct Batch {
     #[doc(hidden)]
     pub msg: Vec<u8>,
@@ -164,196 +604,45 @@ impl Batch {
         }
     }
 
-    /// Appends a number of nodes as children of the given node.
-    pub fn append_child(&mut self, root: MaybeId, child: MaybeId) {
-        self.encode_op(Op::AppendChildren);
-        let size = root.encoded_size() + child.encoded_size();
-        self.msg.reserve(size as usize);
-        unsafe {
-            self.encode_maybe_id_prealloc(root);
-            self.encode_maybe_id_prealloc(child);
-        }
-    }
-
     /// Replace a node with another node
     pub fn replace_with(&mut self, root: MaybeId, node: MaybeId) {
-        self.encode_op(Op::ReplaceWith);
-        let size = root.encoded_size() + node.encoded_size();
-        self.msg.reserve(size as usize);
-        unsafe {
-            self.encode_maybe_id_prealloc(root);
-            self.encode_maybe_id_prealloc(node);
-        }
-    }
-
-    /// Replace a node with many nodes
-    pub fn replace_with_nodes(&mut self, root: MaybeId, nodes: &[MaybeId]) {
-        self.encode_op(Op::ReplaceWith);
-        self.encode_bool(true);
-        self.encode_maybe_id(root);
-        self.msg.push(nodes.len() as u8);
-        for n in nodes {
-            self.encode_maybe_id_u8_discriminant(*n);
-        }
+        self.replace_with_nodes(root, &[node])
     }
 
     /// Insert a single node after a given node.
     pub fn insert_after(&mut self, root: MaybeId, node: MaybeId) {
-        self.encode_op(Op::InsertAfter);
-        let size = root.encoded_size() + node.encoded_size();
-        self.msg.reserve(size as usize);
-        unsafe {
-            self.encode_bool(false);
-            self.encode_maybe_id_prealloc(root);
-            self.encode_maybe_id_prealloc(node);
-        }
-    }
-
-    /// Insert many nodes after a given node.
-    pub fn insert_nodes_after(&mut self, root: MaybeId, nodes: &[MaybeId]) {
-        self.encode_op(Op::InsertAfter);
-        self.encode_bool(true);
-        self.encode_maybe_id(root);
-        self.msg.push(nodes.len() as u8);
-        for n in nodes {
-            self.encode_maybe_id_u8_discriminant(*n);
-        }
+        self.insert_nodes_after(root, &[node])
     }
 
     /// Insert a single node before a given node.
     pub fn insert_before(&mut self, root: MaybeId, node: MaybeId) {
-        self.encode_op(Op::InsertBefore);
-        let size = root.encoded_size() + node.encoded_size();
-        self.msg.reserve(size as usize);
-        unsafe {
-            self.encode_bool(false);
-            self.encode_maybe_id_prealloc(root);
-            self.encode_maybe_id_prealloc(node);
-        }
-    }
-
-    /// Insert many nodes before a given node.
-    pub fn insert_nodes_before(&mut self, root: MaybeId, nodes: &[MaybeId]) {
-        self.encode_op(Op::InsertBefore);
-        self.encode_bool(true);
-        self.encode_maybe_id(root);
-        self.msg.push(nodes.len() as u8);
-        for n in nodes {
-            self.encode_maybe_id_u8_discriminant(*n);
-        }
+        self.insert_nodes_before(root, &[node])
     }
 
     /// Remove a node from the DOM.
     pub fn remove(&mut self, id: MaybeId) {
-        self.encode_op(Op::Remove);
-        self.encode_maybe_id(id);
+        self.encode_remove(id, false)
     }
 
-    /// Create a new text node
-    pub fn create_text_node(&mut self, text: impl WritableText, id: Option<NodeId>) {
-        self.encode_op(Op::CreateTextNode);
-        let size = (id.is_some() as u8) * 4 + 2;
-        self.msg.reserve(size as usize);
-        unsafe {
-            self.encode_str_prealloc(text);
-            self.encode_optional_id_prealloc(id);
-        }
+    /// Remove a node from the DOM and release the ids of the node and of any stored descendants of the node, including the nodes in shadow roots.
+    ///
+    /// This checks every stored node, so if the ids in the subtree are known it is cheaper to call [`Batch::remove`] followed by [`Batch::free_id`] or [`Batch::free_ids`].
+    pub fn remove_and_free(&mut self, id: MaybeId) {
+        self.encode_remove(id, true)
     }
 
-    /// Create a new element node
-    pub fn create_element<'a, 'b, E>(&mut self, tag: E, id: Option<NodeId>)
-    where
-        E: IntoElement<'a, 'b>,
-    {
-        self.encode_op(Op::CreateElement);
-        self.msg
-            .reserve((E::SINGLE_BYTE as u8 + (id.is_some() as u8) * 4) as usize);
-        unsafe {
-            tag.encode_prealloc(self);
-            self.encode_optional_id_prealloc(id);
-        }
-    }
-
-    /// Set the textcontent of a node.
-    pub fn set_text(&mut self, text: impl WritableText, root: MaybeId) {
-        self.encode_op(Op::SetText);
-        let size = root.encoded_size() + 2;
-        self.msg.reserve(size as usize);
-        unsafe {
-            self.encode_maybe_id_prealloc(root);
-            self.encode_str_prealloc(text);
-        }
-    }
-
-    /// Set the value of a node's attribute.
-    pub fn set_attribute<'a, 'b, A>(&mut self, attr: A, value: impl WritableText, root: MaybeId)
-    where
-        A: IntoAttribue<'a, 'b>,
-    {
-        self.encode_op(Op::SetAttribute);
-        self.msg
-            .reserve((A::SINGLE_BYTE as u8 + root.encoded_size() + 2) as usize);
-        unsafe {
-            self.encode_maybe_id_prealloc(root);
-            attr.encode_prealloc(self);
-            self.encode_str_prealloc(value);
-        }
-    }
-
-    /// Remove an attribute from a node.
-    pub fn remove_attribute<'a, 'b, A>(&mut self, attr: A, root: MaybeId)
-    where
-        A: IntoAttribue<'a, 'b>,
-    {
-        self.encode_op(Op::RemoveAttribute);
-        let size = A::SINGLE_BYTE as u8 + root.encoded_size();
-        self.msg.reserve(size as usize);
-        unsafe {
-            self.encode_maybe_id_prealloc(root);
-            attr.encode_prealloc(self);
-        }
+    /// Release the node stored with an id. The id can be reused for another node afterwards.
+    pub fn free_id(&mut self, id: NodeId) {
+        self.free_ids(id..NodeId(id.0.wrapping_add(1)))
     }
 
     /// Clone a node and store it with a new id.
     pub fn clone_node(&mut self, id: MaybeId, new_id: MaybeId) {
-        self.encode_op(Op::CloneNode);
-        let size = id.encoded_size() + new_id.encoded_size();
-        self.msg.reserve(size as usize);
-        self.encode_maybe_id(id);
-        self.encode_maybe_id(new_id);
-    }
-
-    /// Move the last node to the first child
-    pub fn first_child(&mut self) {
-        self.encode_op(Op::FirstChild);
-    }
-
-    /// Move the last node to the next sibling
-    pub fn next_sibling(&mut self) {
-        self.encode_op(Op::NextSibling);
-    }
-
-    /// Move the last node to the parent node
-    pub fn parent_node(&mut self) {
-        self.encode_op(Op::ParentNode);
-    }
-
-    /// Store the last node with the given id. This is useful when traversing the document tree.
-    pub fn store_with_id(&mut self, id: NodeId) {
-        self.encode_op(Op::StoreWithId);
-        self.encode_id(id);
-    }
-
-    /// Set the last node to the given id. The last node can be used to traverse the document tree without passing objects between wasm and js every time.
-    pub fn set_last_node(&mut self, id: NodeId) {
-        self.encode_op(Op::SetLastNode);
-        self.encode_id(id);
-    }
-
-    /// Build a full element, slightly more efficent than creating the element creating the element with `create_element` and then setting the attributes.
-    pub fn build_full_element(&mut self, el: ElementBuilder) {
-        self.encode_op(Op::BuildFullElement);
-        el.encode(self);
+        let new_id = match new_id {
+            MaybeId::Node(id) => Some(id),
+            MaybeId::LastNode => None,
+        };
+        self.encode_clone_node(id, new_id)
     }
 
     /// Build a text node
@@ -361,35 +650,37 @@ impl Batch {
         self.create_text_node(text.text, text.id)
     }
 
+    /// Build a comment node
+    pub fn build_comment(&mut self, comment: CommentBuilder) {
+        self.create_comment(comment.text, comment.id)
+    }
+
     /// Set a style property on a node.
-    pub fn set_style(&mut self, style: &str, value: &str, id: MaybeId) {
-        self.encode_op(Op::SetStyle);
-        let size = id.encoded_size() + 2 + 2;
-        self.msg.reserve(size as usize);
-        self.encode_maybe_id(id);
-        unsafe {
-            self.encode_str_prealloc(style);
-            self.encode_str_prealloc(value);
-        }
+    pub fn set_style<'a>(
+        &mut self,
+        style: impl IntoStyle<'a>,
+        value: impl WritableText,
+        id: MaybeId,
+    ) {
+        self.encode_set_style(style, value, false, id)
     }
 
-    /// Remove a style property from a node.
-    pub fn remove_style(&mut self, style: &str, id: MaybeId) {
-        self.encode_op(Op::RemoveStyle);
-        let size = id.encoded_size() + 2;
-        self.msg.reserve(size as usize);
-        unsafe {
-            self.encode_maybe_id_prealloc(id);
-            self.encode_str_prealloc(style);
-        }
+    /// Set a style property on a node with the `important` priority.
+    pub fn set_important_style<'a>(
+        &mut self,
+        style: impl IntoStyle<'a>,
+        value: impl WritableText,
+        id: MaybeId,
+    ) {
+        self.encode_set_style(style, value, true, id)
     }
 
     #[inline]
-    pub(crate) unsafe fn encode_optional_id_prealloc(&mut self, id: Option<NodeId>) {
+    pub(crate) fn encode_optional_id(&mut self, id: Option<NodeId>) {
         match id {
             Some(id) => {
                 self.encode_bool(true);
-                self.encode_id_prealloc(id);
+                self.encode_id(id);
             }
             None => {
                 self.encode_bool(false);
@@ -397,19 +688,60 @@ impl Batch {
         }
     }
 
+    /// Encode a single node as a [`MaybeId`], or many nodes with a u8 count
     #[inline]
-    pub(crate) unsafe fn encode_maybe_id_prealloc(&mut self, id: MaybeId) {
-        match id {
-            MaybeId::Node(id) => {
-                self.encode_bool(true);
-                self.encode_id_prealloc(id);
-            }
-            MaybeId::LastNode => {
+    pub(crate) fn encode_nodes(&mut self, nodes: &[MaybeId]) {
+        match nodes {
+            [node] => {
                 self.encode_bool(false);
+                self.encode_maybe_id(*node);
+            }
+            _ => {
+                self.encode_bool(true);
+                self.msg.push(nodes.len() as u8);
+                for node in nodes {
+                    self.encode_maybe_id_u8_discriminant(*node);
+                }
             }
         }
     }
 
+    pub(crate) fn encode_roots(&mut self, roots: &[NodeBuilder]) {
+        self.msg.push(roots.len() as u8);
+        for root in roots {
+            root.encode(self);
+        }
+    }
+
+    #[inline]
+    pub(crate) fn encode_style<'a>(&mut self, style: impl IntoStyle<'a>) {
+        self.msg.reserve(style.size_with_u8_discriminant());
+        unsafe {
+            style.encode_u8_discriminant_prealloc(self);
+        }
+    }
+
+    pub(crate) fn encode_styles<'a>(&mut self, styles: &[(impl IntoStyle<'a> + Copy, &str)]) {
+        self.encode_u16(styles.len() as u16);
+        for (style, value) in styles {
+            self.encode_style(*style);
+            self.encode_str(*value);
+        }
+    }
+
+    /// Encode a single id, or a range of ids with a bool
+    #[inline]
+    pub(crate) fn encode_id_range(&mut self, ids: Range<NodeId>) {
+        if ids.end.0 == ids.start.0.wrapping_add(1) {
+            self.encode_bool(false);
+            self.encode_id(ids.start);
+        } else {
+            self.encode_bool(true);
+            self.encode_id(ids.start);
+            self.encode_id(ids.end);
+        }
+    }
+
     #[inline]
     pub(crate) fn encode_maybe_id_u8_discriminant(&mut self, id: MaybeId) {
         match id {
@@ -494,34 +826,57 @@ impl Batch {
         }
     }
 
+    /// Write a string to the strings of the batch and return its length. Strings are encoded with a u16 length, so a longer string panics instead of being truncated, which would shift every string read after it
     #[inline]
-    pub(crate) fn encode_str(&mut self, string: impl WritableText) {
+    fn write_str(&mut self, string: impl WritableText) -> u16 {
         let prev_len = self.str_buf.len();
         string.write_as_text(&mut self.str_buf);
         let len = self.str_buf.len() - prev_len;
-        self.encode_u16(len as u16);
+        assert!(
+            len <= u16::MAX as usize,
+            "a string of {len} bytes is longer than the {} bytes an operation can encode",
+            u16::MAX
+        );
+        len as u16
+    }
+
+    #[inline]
+    pub(crate) fn encode_str(&mut self, string: impl WritableText) {
+        let len = self.write_str(string);
+        self.encode_u16(len);
     }
 
     #[inline]
     pub(crate) unsafe fn encode_str_prealloc(&mut self, string: impl WritableText) {
-        let prev_len = self.str_buf.len();
-        string.write_as_text(&mut self.str_buf);
-        let len = self.str_buf.len() - prev_len;
-        self.encode_u16_prealloc(len as u16);
+        let len = self.write_str(string);
+        self.encode_u16_prealloc(len);
     }
 
     #[inline]
     pub(crate) fn encode_cachable_str(&mut self, string: impl WritableText) {
+        let len = self.write_str(string);
+        self.encode_u16(len);
+    }
+
+    /// Write a string that can be longer than a u16 length allows, like html
+    #[inline]
+    pub(crate) fn encode_long_str(&mut self, string: impl WritableText) {
         let prev_len = self.str_buf.len();
         string.write_as_text(&mut self.str_buf);
         let len = self.str_buf.len() - prev_len;
-        self.encode_u16(len as u16);
+        assert!(
+            len <= u32::MAX as usize,
+            "a string of {len} bytes is longer than the {} bytes an operation can encode",
+            u32::MAX
+        );
+        self.encode_u32(len as u32);
     }
 
     #[inline]
     #[doc(hidden)]
     pub fn encode_op(&mut self, op: Op) {
-        let u8_op = op as u8;
+        let full_op = op as u8;
+        let u8_op = full_op.min(EXTENDED_OP);
 
         self.current_op_byte_idx += 1;
         if self.current_op_byte_idx - self.current_op_batch_idx < 4 {
@@ -540,6 +895,9 @@ impl Batch {
                 *self.msg.get_unchecked_mut(self.current_op_batch_idx) = u8_op;
             }
         }
+        if full_op >= EXTENDED_OP {
+            self.msg.push(full_op);
+        }
         self.current_op_bit_pack_index = 0;
     }
 
@@ -0,0 +1,800 @@
+//! A small in-memory DOM that the [`NativeInterpreter`](crate::interpreter::NativeInterpreter) applies batches to.
+//!
+//! It models the parts of the DOM sledgehammer operations can change: elements with their attributes, styles and shadow roots, text, comments and fragments.
+//! Html inserted with [`Batch::set_inner_html`](crate::batch::Batch::set_inner_html) or [`Batch::insert_adjacent_html`](crate::batch::Batch::insert_adjacent_html) is parsed with a small parser that understands tags, attributes, text, comments and common character references, but not the implied end tags of the full html parsing algorithm.
+
+use std::fmt::Write;
+
+use crate::{ShadowRootMode, StyleSheetId};
+
+/// The namespace of svg elements
+pub const SVG_NAMESPACE: &str = "http://www.w3.org/2000/svg";
+/// The namespace of MathML elements
+pub const MATHML_NAMESPACE: &str = "http://www.w3.org/1998/Math/MathML";
+
+/// Elements that never have children or a closing tag in html
+const VOID_ELEMENTS: [&str; 13] = [
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+/// A node in a [`Dom`]. A [`DomId`] stays valid until its node is freed with [`Dom::retain_trees`], after which the id may be reused for a new node.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DomId(usize);
+
+/// An in-memory tree of nodes
+#[derive(Clone, Debug, Default)]
+pub struct Dom {
+    nodes: Vec<DomNode>,
+    /// The freed nodes, which are reused for new nodes
+    free: Vec<DomId>,
+}
+
+/// A node with its position in the tree
+#[derive(Clone, Debug, PartialEq)]
+pub struct DomNode {
+    /// What kind of node this is
+    pub kind: NodeKind,
+    /// The node this node is a child of. Shadow roots have no parent, see [`NodeKind::ShadowRoot`] for the host.
+    pub parent: Option<DomId>,
+    /// The children of the node in order
+    pub children: Vec<DomId>,
+}
+
+/// The kind of a node and the data that is specific to it
+#[derive(Clone, Debug, PartialEq)]
+pub enum NodeKind {
+    /// A document, which can adopt style sheets
+    Document {
+        /// The style sheets adopted by the document in order
+        adopted_style_sheets: Vec<StyleSheetId>,
+    },
+    /// An element
+    Element(ElementData),
+    /// A text node
+    Text(String),
+    /// A comment node
+    Comment(String),
+    /// A document fragment. Inserting a fragment moves its children instead.
+    Fragment,
+    /// The shadow root attached to an element
+    ShadowRoot {
+        /// The element the shadow root is attached to
+        host: DomId,
+        /// The encapsulation mode of the shadow root
+        mode: ShadowRootMode,
+        /// The style sheets adopted by the shadow root in order
+        adopted_style_sheets: Vec<StyleSheetId>,
+    },
+}
+
+impl NodeKind {
+    pub(crate) fn can_have_children(&self) -> bool {
+        matches!(
+            self,
+            NodeKind::Document { .. }
+                | NodeKind::Element(_)
+                | NodeKind::Fragment
+                | NodeKind::ShadowRoot { .. }
+        )
+    }
+}
+
+/// The data of an element node
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ElementData {
+    /// The name of the element
+    pub name: String,
+    /// The namespace of the element, or `None` for html elements
+    pub namespace: Option<String>,
+    /// The attributes of the element in the order they were first set. The style attribute is kept in `styles` instead.
+    pub attributes: Vec<DomAttribute>,
+    /// The inline style properties of the element in the order they were first set
+    pub styles: Vec<StyleProperty>,
+    /// The shadow root attached to the element
+    pub shadow_root: Option<DomId>,
+}
+
+/// An attribute of an element
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DomAttribute {
+    /// The name of the attribute
+    pub name: String,
+    /// The namespace of the attribute
+    pub namespace: Option<String>,
+    /// The value of the attribute
+    pub value: String,
+}
+
+/// An inline style property of an element
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StyleProperty {
+    /// The name of the property
+    pub name: String,
+    /// The value of the property
+    pub value: String,
+    /// If the property has the important priority
+    pub important: bool,
+}
+
+impl ElementData {
+    /// Get the value of an attribute without a namespace
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|a| a.namespace.is_none() && a.name == name)
+            .map(|a| a.value.as_str())
+    }
+
+    /// Get the value of an inline style property
+    pub fn style(&self, name: &str) -> Option<&str> {
+        self.styles
+            .iter()
+            .find(|s| s.name == name)
+            .map(|s| s.value.as_str())
+    }
+
+    pub(crate) fn set_attribute(&mut self, namespace: Option<&str>, name: &str, value: &str) {
+        if namespace.is_none() && name == "style" {
+            self.styles.clear();
+            for declaration in value.split(';') {
+                if let Some((name, value)) = declaration.split_once(':') {
+                    let value = value.trim();
+                    match value.strip_suffix("!important") {
+                        Some(value) => self.set_style(name.trim(), value.trim(), true),
+                        None => self.set_style(name.trim(), value, false),
+                    }
+                }
+            }
+            return;
+        }
+        match self
+            .attributes
+            .iter_mut()
+            .find(|a| a.namespace.as_deref() == namespace && a.name == name)
+        {
+            Some(attr) => attr.value = value.to_string(),
+            None => self.attributes.push(DomAttribute {
+                name: name.to_string(),
+                namespace: namespace.map(str::to_string),
+                value: value.to_string(),
+            }),
+        }
+    }
+
+    pub(crate) fn remove_attribute(&mut self, namespace: Option<&str>, name: &str) {
+        if namespace.is_none() && name == "style" {
+            self.styles.clear();
+        }
+        self.attributes
+            .retain(|a| !(a.namespace.as_deref() == namespace && a.name == name));
+    }
+
+    pub(crate) fn set_style(&mut self, name: &str, value: &str, important: bool) {
+        // like CSSStyleDeclaration.setProperty, an empty value removes the property
+        if value.is_empty() {
+            self.remove_style(name);
+            return;
+        }
+        match self.styles.iter_mut().find(|s| s.name == name) {
+            Some(style) => {
+                style.value = value.to_string();
+                style.important = important;
+            }
+            None => self.styles.push(StyleProperty {
+                name: name.to_string(),
+                value: value.to_string(),
+                important,
+            }),
+        }
+    }
+
+    pub(crate) fn remove_style(&mut self, name: &str) {
+        self.styles.retain(|s| s.name != name);
+    }
+}
+
+impl Dom {
+    /// Create an empty dom
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn push(&mut self, kind: NodeKind) -> DomId {
+        let node = DomNode {
+            kind,
+            parent: None,
+            children: Vec::new(),
+        };
+        match self.free.pop() {
+            Some(id) => {
+                self.nodes[id.0] = node;
+                id
+            }
+            None => {
+                self.nodes.push(node);
+                DomId(self.nodes.len() - 1)
+            }
+        }
+    }
+
+    /// The number of nodes that have not been freed
+    pub fn len(&self) -> usize {
+        self.nodes.len() - self.free.len()
+    }
+
+    /// If every node has been freed
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Free every node that is not in the same tree as one of the roots. A tree includes the shadow roots attached to its elements and the hosts of its shadow roots.
+    ///
+    /// The ids of the freed nodes are reused for new nodes, so they must not be used afterwards.
+    pub fn retain_trees(&mut self, roots: impl IntoIterator<Item = DomId>) {
+        let mut live = vec![false; self.nodes.len()];
+        for &id in &self.free {
+            live[id.0] = true;
+        }
+        let mut stack: Vec<DomId> = roots.into_iter().map(|id| self.tree_root(id)).collect();
+        while let Some(id) = stack.pop() {
+            if std::mem::replace(&mut live[id.0], true) {
+                continue;
+            }
+            let node = &self.nodes[id.0];
+            stack.extend_from_slice(&node.children);
+            if let NodeKind::Element(ElementData {
+                shadow_root: Some(shadow_root),
+                ..
+            }) = node.kind
+            {
+                stack.push(shadow_root);
+            }
+        }
+        for (i, live) in live.into_iter().enumerate() {
+            if !live {
+                self.nodes[i] = DomNode {
+                    kind: NodeKind::Fragment,
+                    parent: None,
+                    children: Vec::new(),
+                };
+                self.free.push(DomId(i));
+            }
+        }
+    }
+
+    /// The topmost ancestor of a node, going from shadow roots to their hosts
+    fn tree_root(&self, mut id: DomId) -> DomId {
+        loop {
+            let node = &self.nodes[id.0];
+            id = match (node.parent, &node.kind) {
+                (Some(parent), _) => parent,
+                (None, NodeKind::ShadowRoot { host, .. }) => *host,
+                (None, _) => return id,
+            };
+        }
+    }
+
+    /// Create a detached element. Html elements have no namespace.
+    pub fn create_element(&mut self, name: &str, namespace: Option<&str>) -> DomId {
+        self.push(NodeKind::Element(ElementData {
+            name: name.to_string(),
+            namespace: namespace.map(str::to_string),
+            ..Default::default()
+        }))
+    }
+
+    /// Create a detached text node
+    pub fn create_text(&mut self, text: &str) -> DomId {
+        self.push(NodeKind::Text(text.to_string()))
+    }
+
+    /// Create a detached comment node
+    pub fn create_comment(&mut self, text: &str) -> DomId {
+        self.push(NodeKind::Comment(text.to_string()))
+    }
+
+    /// Create an empty document fragment
+    pub fn create_fragment(&mut self) -> DomId {
+        self.push(NodeKind::Fragment)
+    }
+
+    /// Create an empty document
+    pub fn create_document(&mut self) -> DomId {
+        self.push(NodeKind::Document {
+            adopted_style_sheets: Vec::new(),
+        })
+    }
+
+    /// Get a node
+    pub fn node(&self, id: DomId) -> &DomNode {
+        &self.nodes[id.0]
+    }
+
+    /// Get the element data of a node if it is an element
+    pub fn element(&self, id: DomId) -> Option<&ElementData> {
+        match &self.nodes[id.0].kind {
+            NodeKind::Element(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// Get the parent of a node
+    pub fn parent(&self, id: DomId) -> Option<DomId> {
+        self.nodes[id.0].parent
+    }
+
+    /// Get the children of a node
+    pub fn children(&self, id: DomId) -> &[DomId] {
+        &self.nodes[id.0].children
+    }
+
+    /// Get the text content of a node like `Node.textContent`
+    pub fn text_content(&self, id: DomId) -> String {
+        match &self.nodes[id.0].kind {
+            NodeKind::Text(text) | NodeKind::Comment(text) => text.clone(),
+            _ => {
+                let mut out = String::new();
+                self.collect_text(id, &mut out);
+                out
+            }
+        }
+    }
+
+    fn collect_text(&self, id: DomId, out: &mut String) {
+        for &child in &self.nodes[id.0].children {
+            match &self.nodes[child.0].kind {
+                NodeKind::Text(text) => out.push_str(text),
+                NodeKind::Comment(_) => {}
+                _ => self.collect_text(child, out),
+            }
+        }
+    }
+
+    /// Serialize a node and its descendants as html. Shadow roots are serialized as declarative shadow roots.
+    pub fn to_html(&self, id: DomId) -> String {
+        let mut out = String::new();
+        self.write_html(id, &mut out);
+        out
+    }
+
+    fn write_html(&self, id: DomId, out: &mut String) {
+        let node = &self.nodes[id.0];
+        match &node.kind {
+            NodeKind::Element(data) => {
+                let _ = write!(out, "<{}", data.name);
+                for attr in &data.attributes {
+                    let _ = write!(out, " {}=\"{}\"", attr.name, escape(&attr.value, true));
+                }
+                if !data.styles.is_empty() {
+                    out.push_str(" style=\"");
+                    for (i, style) in data.styles.iter().enumerate() {
+                        if i > 0 {
+                            out.push(' ');
+                        }
+                        let important = if style.important { " !important" } else { "" };
+                        let _ = write!(
+                            out,
+                            "{}: {}{};",
+                            style.name,
+                            escape(&style.value, true),
+                            important
+                        );
+                    }
+                    out.push('"');
+                }
+                out.push('>');
+                if let Some(root) = data.shadow_root {
+                    if let NodeKind::ShadowRoot { mode, .. } = &self.nodes[root.0].kind {
+                        let mode = match mode {
+                            ShadowRootMode::Open => "open",
+                            ShadowRootMode::Closed => "closed",
+                        };
+                        let _ = write!(out, "<template shadowrootmode=\"{mode}\">");
+                        self.write_children(root, out);
+                        out.push_str("</template>");
+                    }
+                }
+                self.write_children(id, out);
+                if data.namespace.is_some() || !VOID_ELEMENTS.contains(&data.name.as_str()) {
+                    let _ = write!(out, "</{}>", data.name);
+                }
+            }
+            NodeKind::Text(text) => out.push_str(&escape(text, false)),
+            NodeKind::Comment(text) => {
+                let _ = write!(out, "<!--{text}-->");
+            }
+            NodeKind::Document { .. } | NodeKind::Fragment | NodeKind::ShadowRoot { .. } => {
+                self.write_children(id, out)
+            }
+        }
+    }
+
+    fn write_children(&self, id: DomId, out: &mut String) {
+        for &child in &self.nodes[id.0].children {
+            self.write_html(child, out);
+        }
+    }
+
+    /// The node and its descendants, including the nodes in the shadow roots attached to them
+    pub fn subtree(&self, root: DomId) -> Vec<DomId> {
+        let mut nodes = Vec::new();
+        let mut stack = vec![root];
+        while let Some(id) = stack.pop() {
+            nodes.push(id);
+            let node = &self.nodes[id.0];
+            stack.extend_from_slice(&node.children);
+            if let NodeKind::Element(ElementData {
+                shadow_root: Some(shadow_root),
+                ..
+            }) = node.kind
+            {
+                stack.push(shadow_root);
+            }
+        }
+        nodes
+    }
+
+    /// If the node is the root or one of its descendants, like `Node.contains`
+    pub fn contains(&self, root: DomId, node: DomId) -> bool {
+        let mut current = Some(node);
+        while let Some(id) = current {
+            if id == root {
+                return true;
+            }
+            current = self.nodes[id.0].parent;
+        }
+        false
+    }
+
+    pub(crate) fn kind_mut(&mut self, id: DomId) -> &mut NodeKind {
+        &mut self.nodes[id.0].kind
+    }
+
+    pub(crate) fn element_mut(&mut self, id: DomId) -> Result<&mut ElementData, String> {
+        match &mut self.nodes[id.0].kind {
+            NodeKind::Element(data) => Ok(data),
+            _ => Err("the node is not an element".to_string()),
+        }
+    }
+
+    /// The data of a text or comment node
+    pub(crate) fn character_data_mut(&mut self, id: DomId) -> Result<&mut String, String> {
+        match &mut self.nodes[id.0].kind {
+            NodeKind::Text(text) | NodeKind::Comment(text) => Ok(text),
+            _ => Err("the node is not a text or comment node".to_string()),
+        }
+    }
+
+    /// Remove a node from its parent
+    pub(crate) fn detach(&mut self, id: DomId) {
+        if let Some(parent) = self.nodes[id.0].parent.take() {
+            self.nodes[parent.0].children.retain(|&c| c != id);
+        }
+    }
+
+    fn index_in_parent(&self, id: DomId) -> Option<(DomId, usize)> {
+        let parent = self.nodes[id.0].parent?;
+        let index = self.nodes[parent.0]
+            .children
+            .iter()
+            .position(|&c| c == id)?;
+        Some((parent, index))
+    }
+
+    /// Insert nodes into the children of parent at index, moving them from their old parents. Fragments insert their children instead.
+    pub(crate) fn insert(
+        &mut self,
+        parent: DomId,
+        mut index: usize,
+        nodes: &[DomId],
+    ) -> Result<(), String> {
+        if !self.nodes[parent.0].kind.can_have_children() {
+            return Err("HierarchyRequestError: the parent cannot have children".to_string());
+        }
+        for &node in nodes {
+            if self.contains(node, parent) {
+                return Err("HierarchyRequestError: the new child contains the parent".to_string());
+            }
+            if matches!(
+                self.nodes[node.0].kind,
+                NodeKind::Document { .. } | NodeKind::ShadowRoot { .. }
+            ) {
+                return Err("HierarchyRequestError: the node cannot be inserted".to_string());
+            }
+        }
+        let mut flat = Vec::with_capacity(nodes.len());
+        for &node in nodes {
+            if let NodeKind::Fragment = self.nodes[node.0].kind {
+                let children = std::mem::take(&mut self.nodes[node.0].children);
+                for &child in &children {
+                    self.nodes[child.0].parent = None;
+                }
+                flat.extend(children);
+            } else {
+                flat.push(node);
+            }
+        }
+        for &node in &flat {
+            if let Some((old_parent, old_index)) = self.index_in_parent(node) {
+                if old_parent == parent && old_index < index {
+                    index -= 1;
+                }
+                self.detach(node);
+            }
+            self.nodes[node.0].parent = Some(parent);
+        }
+        let children = &mut self.nodes[parent.0].children;
+        let index = index.min(children.len());
+        children.splice(index..index, flat);
+        Ok(())
+    }
+
+    /// Append nodes to the children of parent
+    pub(crate) fn append(&mut self, parent: DomId, nodes: &[DomId]) -> Result<(), String> {
+        let len = self.nodes[parent.0].children.len();
+        self.insert(parent, len, nodes)
+    }
+
+    /// Insert nodes after a node. Does nothing if the node has no parent.
+    pub(crate) fn after(&mut self, node: DomId, nodes: &[DomId]) -> Result<(), String> {
+        match self.index_in_parent(node) {
+            Some((parent, index)) => self.insert(parent, index + 1, nodes),
+            None => Ok(()),
+        }
+    }
+
+    /// Insert nodes before a node. Does nothing if the node has no parent.
+    pub(crate) fn before(&mut self, node: DomId, nodes: &[DomId]) -> Result<(), String> {
+        match self.index_in_parent(node) {
+            Some((parent, index)) => self.insert(parent, index, nodes),
+            None => Ok(()),
+        }
+    }
+
+    /// Replace a node with nodes. Does nothing if the node has no parent.
+    pub(crate) fn replace_with(&mut self, node: DomId, nodes: &[DomId]) -> Result<(), String> {
+        match self.index_in_parent(node) {
+            Some((parent, index)) => {
+                self.detach(node);
+                self.insert(parent, index, nodes)
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// The sibling after a node
+    pub fn next_sibling(&self, id: DomId) -> Option<DomId> {
+        let (parent, index) = self.index_in_parent(id)?;
+        self.nodes[parent.0].children.get(index + 1).copied()
+    }
+
+    /// The sibling before a node
+    pub fn previous_sibling(&self, id: DomId) -> Option<DomId> {
+        let (parent, index) = self.index_in_parent(id)?;
+        index
+            .checked_sub(1)
+            .map(|index| self.nodes[parent.0].children[index])
+    }
+
+    /// Set the text content of a node like `Node.textContent`
+    pub(crate) fn set_text_content(&mut self, id: DomId, text: &str) {
+        match &mut self.nodes[id.0].kind {
+            NodeKind::Text(data) | NodeKind::Comment(data) => {
+                *data = text.to_string();
+            }
+            _ => {
+                for child in std::mem::take(&mut self.nodes[id.0].children) {
+                    self.nodes[child.0].parent = None;
+                }
+                if !text.is_empty() {
+                    let text = self.create_text(text);
+                    self.nodes[text.0].parent = Some(id);
+                    self.nodes[id.0].children.push(text);
+                }
+            }
+        }
+    }
+
+    /// Copy a node and its descendants like `Node.cloneNode(true)`. Shadow roots are not copied.
+    pub(crate) fn deep_clone(&mut self, id: DomId) -> Result<DomId, String> {
+        let kind = match &self.nodes[id.0].kind {
+            NodeKind::ShadowRoot { .. } => {
+                return Err("NotSupportedError: shadow roots cannot be cloned".to_string())
+            }
+            NodeKind::Element(data) => NodeKind::Element(ElementData {
+                shadow_root: None,
+                ..data.clone()
+            }),
+            kind => kind.clone(),
+        };
+        let clone = self.push(kind);
+        for child in self.nodes[id.0].children.clone() {
+            let child = self.deep_clone(child)?;
+            self.nodes[child.0].parent = Some(clone);
+            self.nodes[clone.0].children.push(child);
+        }
+        Ok(clone)
+    }
+}
+
+/// Elements whose content is text until the matching end tag
+const RAW_TEXT_ELEMENTS: [&str; 4] = ["script", "style", "textarea", "title"];
+
+impl Dom {
+    /// Parse a fragment of html into detached nodes
+    pub(crate) fn parse_html(&mut self, html: &str) -> Vec<DomId> {
+        let root = self.create_fragment();
+        let mut open = vec![root];
+        let mut rest = html;
+        while !rest.is_empty() {
+            let current = *open.last().unwrap();
+            if let Some(comment) = rest.strip_prefix("<!--") {
+                let end = comment.find("-->").unwrap_or(comment.len());
+                let node = self.create_comment(&comment[..end]);
+                self.append_parsed(current, node);
+                rest = comment.get(end + 3..).unwrap_or_default();
+            } else if let Some(tag) = rest.strip_prefix("</") {
+                let end = tag.find('>').unwrap_or(tag.len());
+                let name = tag[..end].trim().to_ascii_lowercase();
+                // close the innermost open element with the name, ignoring end tags that match nothing
+                if let Some(i) = open.iter().rposition(
+                    |&n| matches!(&self.nodes[n.0].kind, NodeKind::Element(e) if e.name == name),
+                ) {
+                    open.truncate(i);
+                }
+                rest = tag.get(end + 1..).unwrap_or_default();
+            } else if rest.starts_with('<')
+                && rest[1..].starts_with(|c: char| c.is_ascii_alphabetic())
+            {
+                let (element, self_closing, after) = self.parse_start_tag(&rest[1..]);
+                self.append_parsed(current, element);
+                rest = after;
+                let name = self.element(element).unwrap().name.clone();
+                if RAW_TEXT_ELEMENTS.contains(&name.as_str()) {
+                    let close = format!("</{name}");
+                    let end = rest.to_ascii_lowercase().find(&close).unwrap_or(rest.len());
+                    if end > 0 {
+                        let text = self.create_text(&unescape(&rest[..end]));
+                        self.append_parsed(element, text);
+                    }
+                    rest = &rest[end..];
+                    rest = rest.find('>').map(|i| &rest[i + 1..]).unwrap_or_default();
+                } else if !self_closing && !VOID_ELEMENTS.contains(&name.as_str()) {
+                    open.push(element);
+                }
+            } else {
+                // the text starts with at least one character that is not the start of a tag
+                let first = rest.chars().next().map_or(0, char::len_utf8);
+                let end = rest[first..]
+                    .find('<')
+                    .map(|i| i + first)
+                    .unwrap_or(rest.len());
+                let text = self.create_text(&unescape(&rest[..end]));
+                self.append_parsed(current, text);
+                rest = &rest[end..];
+            }
+        }
+        let nodes = std::mem::take(&mut self.nodes[root.0].children);
+        for &node in &nodes {
+            self.nodes[node.0].parent = None;
+        }
+        nodes
+    }
+
+    fn append_parsed(&mut self, parent: DomId, node: DomId) {
+        self.nodes[node.0].parent = Some(parent);
+        self.nodes[parent.0].children.push(node);
+    }
+
+    /// Parse a start tag after the `<` and return the element, if it was self closing, and the rest of the html
+    fn parse_start_tag<'a>(&mut self, tag: &'a str) -> (DomId, bool, &'a str) {
+        let name_end = tag
+            .find(|c: char| c.is_ascii_whitespace() || c == '>' || c == '/')
+            .unwrap_or(tag.len());
+        let element = self.create_element(&tag[..name_end].to_ascii_lowercase(), None);
+        let mut rest = &tag[name_end..];
+        loop {
+            rest = rest.trim_start();
+            if let Some(after) = rest.strip_prefix("/>") {
+                return (element, true, after);
+            }
+            if let Some(after) = rest.strip_prefix('>') {
+                return (element, false, after);
+            }
+            if rest.is_empty() {
+                return (element, false, rest);
+            }
+            let name_end = rest
+                .find(|c: char| c.is_ascii_whitespace() || c == '>' || c == '=' || c == '/')
+                .unwrap_or(rest.len())
+                .max(1);
+            let name = rest[..name_end].to_ascii_lowercase();
+            rest = rest[name_end..].trim_start();
+            let mut value = String::new();
+            if let Some(after) = rest.strip_prefix('=') {
+                let after = after.trim_start();
+                let (raw, after) = match after.chars().next() {
+                    Some(quote @ ('"' | '\'')) => {
+                        let end = after[1..].find(quote).map(|i| i + 1).unwrap_or(after.len());
+                        (&after[1..end], after.get(end + 1..).unwrap_or_default())
+                    }
+                    _ => {
+                        let end = after
+                            .find(|c: char| c.is_ascii_whitespace() || c == '>')
+                            .unwrap_or(after.len());
+                        after.split_at(end)
+                    }
+                };
+                value = unescape(raw);
+                rest = after;
+            }
+            if let NodeKind::Element(data) = &mut self.nodes[element.0].kind {
+                // like the html parser, only the first attribute with a name is kept
+                let duplicate = match name.as_str() {
+                    "style" => !data.styles.is_empty(),
+                    name => data.attribute(name).is_some(),
+                };
+                if !duplicate {
+                    data.set_attribute(None, &name, &value);
+                }
+            }
+        }
+    }
+}
+
+/// Replace the common character references in html text
+fn unescape(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(i) = rest.find('&') {
+        out.push_str(&rest[..i]);
+        rest = &rest[i..];
+        let reference = rest[1..].find(';').map(|end| &rest[1..end + 1]);
+        let decoded = reference.and_then(|reference| match reference {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some('\u{a0}'),
+            _ => {
+                let number = reference.strip_prefix('#')?;
+                let code = match number.strip_prefix(['x', 'X']) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                    None => number.parse().ok()?,
+                };
+                char::from_u32(code)
+            }
+        });
+        match (reference, decoded) {
+            (Some(reference), Some(c)) => {
+                out.push(c);
+                rest = &rest[reference.len() + 2..];
+            }
+            _ => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn escape(text: &str, attribute: bool) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '"' if attribute => out.push_str("&quot;"),
+            '<' if !attribute => out.push_str("&lt;"),
+            '>' if !attribute => out.push_str("&gt;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
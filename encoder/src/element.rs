@@ -1,16 +1,20 @@
 #![allow(non_camel_case_types)]
 
-use crate::{attribute::AnyAttribute, batch::Batch, InNamespace, NodeId};
+use crate::{
+    attribute::AnyAttribute, batch::Batch, InNamespace, MathMlElement, NodeId, SvgElement,
+};
 
 use self::sealed::Sealed;
 
 mod sealed {
-    use crate::{Element, InNamespace};
+    use crate::{Element, InNamespace, MathMlElement, SvgElement};
 
     pub trait Sealed {}
 
     impl Sealed for Element {}
-    impl<'a> Sealed for &'a str {}
+    impl Sealed for SvgElement {}
+    impl Sealed for MathMlElement {}
+    impl Sealed for &str {}
     impl<'a> Sealed for InNamespace<'a, Element> {}
     impl<'a, 'b> Sealed for InNamespace<'a, &'b str> {}
 }
@@ -20,6 +24,8 @@ pub enum AnyElement<'a, 'b> {
     InNamespace(InNamespace<'a, Element>),
     Str(&'a str),
     InNamespaceStr(InNamespace<'a, &'b str>),
+    Svg(SvgElement),
+    MathMl(MathMlElement),
 }
 
 impl AnyElement<'_, '_> {
@@ -29,6 +35,8 @@ impl AnyElement<'_, '_> {
             AnyElement::InNamespace(a) => a.encode(v),
             AnyElement::Str(a) => a.encode(v),
             AnyElement::InNamespaceStr(a) => a.encode(v),
+            AnyElement::Svg(a) => a.encode(v),
+            AnyElement::MathMl(a) => a.encode(v),
         }
     }
 
@@ -38,15 +46,19 @@ impl AnyElement<'_, '_> {
             AnyElement::InNamespace(a) => a.encode_prealloc(v),
             AnyElement::Str(a) => a.encode_prealloc(v),
             AnyElement::InNamespaceStr(a) => a.encode_prealloc(v),
+            AnyElement::Svg(a) => a.encode_prealloc(v),
+            AnyElement::MathMl(a) => a.encode_prealloc(v),
         }
     }
 
     pub(crate) fn size(&self) -> usize {
         match self {
             AnyElement::Element(_) => 1,
-            AnyElement::InNamespace(_) => 1 + 2,
-            AnyElement::Str(_) => 2,
-            AnyElement::InNamespaceStr(_) => 2 + 2,
+            AnyElement::InNamespace(_) => 1 + 1 + 2,
+            AnyElement::Str(_) => 1 + 2,
+            AnyElement::InNamespaceStr(_) => 1 + 2 + 2,
+            AnyElement::Svg(_) => 1 + 1,
+            AnyElement::MathMl(_) => 1 + 1,
         }
     }
 }
@@ -163,6 +175,7 @@ impl<'a, 'b> InNamespace<'a, &'b str> {
 pub enum NodeBuilder<'a> {
     Text(TextBuilder<'a>),
     Element(ElementBuilder<'a>),
+    Comment(CommentBuilder<'a>),
 }
 
 impl NodeBuilder<'_> {
@@ -171,6 +184,7 @@ impl NodeBuilder<'_> {
         match self {
             NodeBuilder::Text(t) => t.encode(v),
             NodeBuilder::Element(e) => e.encode(v),
+            NodeBuilder::Comment(c) => c.encode(v),
         }
     }
 }
@@ -187,6 +201,12 @@ impl<'a> From<ElementBuilder<'a>> for NodeBuilder<'a> {
     }
 }
 
+impl<'a> From<CommentBuilder<'a>> for NodeBuilder<'a> {
+    fn from(c: CommentBuilder<'a>) -> Self {
+        NodeBuilder::Comment(c)
+    }
+}
+
 /// A builder for an text node with a id, and text
 pub struct TextBuilder<'a> {
     pub(crate) id: Option<NodeId>,
@@ -220,14 +240,48 @@ impl<'a> TextBuilder<'a> {
     }
 }
 
+/// A builder for a comment node with a id, and text
+/// Comments can be used as placeholders for conditionally rendered nodes and fragments.
+pub struct CommentBuilder<'a> {
+    pub(crate) id: Option<NodeId>,
+    pub(crate) text: &'a str,
+}
+
+impl<'a> CommentBuilder<'a> {
+    /// Create a new comment builder
+    pub const fn new(text: &'a str) -> Self {
+        Self { id: None, text }
+    }
+
+    /// Set the id of the comment node
+    pub const fn id(mut self, id: NodeId) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    /// Encode the comment node into a batch
+    pub(crate) fn encode(&self, v: &mut Batch) {
+        match self.id {
+            Some(id) => {
+                v.msg.push(5);
+                v.encode_id(id);
+            }
+            None => {
+                v.msg.push(4);
+            }
+        }
+        v.encode_str(self.text);
+    }
+}
+
 /// A builder for a element with an id, kind, attributes, and children
 ///
-/// /// Example:
+/// Example:
 /// ```rust
-/// let mut channel = MsgChannel::default();
-
+/// # use sledgehammer_encoder::{batch::Batch, Attribute, Element, ElementBuilder, NodeId, TextBuilder};
+/// let mut batch = Batch::default();
 /// // create an element using sledgehammer
-/// channel.build_full_element(
+/// batch.build_full_element(
 ///     ElementBuilder::new("div".into())
 ///         .id(NodeId(1))
 ///         .attrs(&[(Attribute::style.into(), "color: blue")])
@@ -237,7 +291,6 @@ impl<'a> TextBuilder<'a> {
 ///             TextBuilder::new("Hello from sledgehammer!").into(),
 ///         ]),
 /// );
-/// channel.flush();
 /// ```
 pub struct ElementBuilder<'a> {
     id: Option<NodeId>,
@@ -325,6 +378,23 @@ macro_rules! elements {
             ),*
         }
 
+        impl Element {
+            #[allow(unused)]
+            pub(crate) const COUNT: usize = [$(stringify!($i)),*].len();
+
+            /// Every element in the order they are encoded
+            pub(crate) const ALL: [Self; Self::COUNT] = [$(Self::$i),*];
+
+            /// The name of the element
+            pub const fn as_str(&self) -> &'static str {
+                match self {
+                    $(
+                        Self::$i => stringify!($i),
+                    )*
+                }
+            }
+        }
+
         pub struct NotElementError;
 
         impl std::str::FromStr for Element {
@@ -0,0 +1,1052 @@
+//! A native interpreter that applies batches to an in-memory [`Dom`] instead of the browser.
+//!
+//! It decodes the same operations as `interpreter.js`, so it can be used to inspect what a batch does without a browser, for example to replay a [`Recording`](crate::record::Recording) on a server.
+
+use std::{collections::HashSet, fmt::Display};
+
+use crate::{
+    batch::{op_list, Op, PreparedBatch, EXTENDED_OP},
+    dom::{Dom, DomId, NodeKind, MATHML_NAMESPACE, SVG_NAMESPACE},
+    Attribute, Element, MathMlAttribute, MathMlElement, NodeId, ShadowRootMode, Style,
+    StyleSheetId, SvgAttribute, SvgElement,
+};
+
+/// The positions of [`InsertPosition`](crate::InsertPosition) in the order they are encoded
+pub(crate) const POSITIONS: [&str; 4] = ["beforebegin", "afterbegin", "beforeend", "afterend"];
+
+/// An operation that failed while a batch was applied by the [`NativeInterpreter`].
+///
+/// The operations before the failed operation have been applied, and the operations after it in the same batch are skipped.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InterpretError {
+    /// The index of the operation in the batch. The [`Op::NoOp`](crate::Op::NoOp) padding between appended batches is not counted
+    pub op_index: u32,
+    /// The opcode of the operation. See [`Op`](crate::Op)
+    pub op: u8,
+    /// The node id the operation looked up, if no node is stored with that id
+    pub node: Option<NodeId>,
+    /// A description of what went wrong
+    pub message: String,
+}
+
+impl Display for InterpretError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "operation {} (opcode {}) failed: {}",
+            self.op_index, self.op, self.message
+        )
+    }
+}
+
+impl std::error::Error for InterpretError {}
+
+/// Reads the operands of operations from the message and strings of a batch
+struct Reader<'a> {
+    msg: &'a [u8],
+    pos: usize,
+    str: &'a [u8],
+    str_pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn bytes<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        let bytes = self.msg.get(self.pos..self.pos + N).ok_or_else(|| {
+            format!(
+                "read of {N} bytes at {} is past the end of the batch",
+                self.pos
+            )
+        })?;
+        self.pos += N;
+        Ok(bytes.try_into().unwrap())
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.bytes::<1>()?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        Ok(u16::from_le_bytes(self.bytes()?))
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        Ok(u32::from_le_bytes(self.bytes()?))
+    }
+
+    /// Read a string with the given length in bytes
+    fn str(&mut self, len: usize) -> Result<&'a str, String> {
+        let bytes = self
+            .str
+            .get(self.str_pos..self.str_pos.saturating_add(len))
+            .ok_or_else(|| {
+                format!(
+                    "read strings up to {}, but the batch only has {} bytes of strings",
+                    self.str_pos.saturating_add(len),
+                    self.str.len()
+                )
+            })?;
+        self.str_pos += len;
+        std::str::from_utf8(bytes).map_err(|e| e.to_string())
+    }
+
+    /// Read a string with a u16 length
+    fn str_u16(&mut self) -> Result<&'a str, String> {
+        let len = self.u16()?;
+        self.str(len as usize)
+    }
+
+    /// Read a string with a u32 length
+    fn str_u32(&mut self) -> Result<&'a str, String> {
+        let len = self.u32()?;
+        self.str(len as usize)
+    }
+}
+
+/// The bools of an operation, in the order its operands use them
+struct Bools(u8);
+
+impl Bools {
+    fn next(&mut self) -> bool {
+        let set = self.0 & 1 != 0;
+        self.0 >>= 1;
+        set
+    }
+}
+
+/// Generates the decoded [`Operands`] of every operation and the decoder of the [`NativeInterpreter`] from the list of operations
+macro_rules! native_operands {
+    ($(
+        $(#[$meta:meta])*
+        $name:ident = $code:literal ($($arg:ident: $kind:ident),*) $(=> $vis:vis fn $method:ident)? { $($js:literal)* }
+    )*) => {
+        /// The operands of an operation
+        enum Operands<'a> {
+            $($name { $($arg: native_operands!(@type $kind)),* },)*
+        }
+
+        impl NativeInterpreter {
+            /// Decode the operands of an operation in the order they are encoded
+            fn decode<'a>(&mut self, r: &mut Reader<'a>, op: Op, bools: &mut Bools) -> Result<Operands<'a>, String> {
+                Ok(match op {
+                    $(Op::$name => Operands::$name { $($arg: native_operands!(@decode self, r, bools, $kind)),* },)*
+                })
+            }
+        }
+    };
+    (@type Id) => { u32 };
+    (@type Store) => { Option<u32> };
+    (@type Node) => { DomId };
+    (@type Nodes) => { Vec<DomId> };
+    (@type Flag) => { bool };
+    (@type Mode) => { ShadowRootMode };
+    (@type Position) => { u8 };
+    (@type U16) => { u16 };
+    (@type U32) => { u32 };
+    (@type TemplateId) => { u32 };
+    (@type StyleSheetId) => { u32 };
+    (@type Str) => { &'a str };
+    (@type LongStr) => { &'a str };
+    (@type Element) => { DomId };
+    (@type FullElement) => { DomId };
+    (@type Roots) => { Vec<DomId> };
+    (@type Attribute) => { (&'a str, Option<&'a str>) };
+    (@type Style) => { String };
+    (@type Styles) => { Vec<(String, &'a str)> };
+    (@type IdRange) => { std::ops::Range<u32> };
+    (@decode $this:ident, $r:ident, $bools:ident, Id) => { $r.u32()? };
+    (@decode $this:ident, $r:ident, $bools:ident, Store) => {
+        if $bools.next() { Some($r.u32()?) } else { None }
+    };
+    (@decode $this:ident, $r:ident, $bools:ident, Node) => {{
+        let set = $bools.next();
+        $this.maybe_id($r, set)?
+    }};
+    (@decode $this:ident, $r:ident, $bools:ident, Nodes) => { $this.nodes($r, $bools)? };
+    (@decode $this:ident, $r:ident, $bools:ident, Flag) => { $bools.next() };
+    (@decode $this:ident, $r:ident, $bools:ident, Mode) => {
+        if $bools.next() { ShadowRootMode::Closed } else { ShadowRootMode::Open }
+    };
+    (@decode $this:ident, $r:ident, $bools:ident, Position) => { $r.u8()? };
+    (@decode $this:ident, $r:ident, $bools:ident, U16) => { $r.u16()? };
+    (@decode $this:ident, $r:ident, $bools:ident, U32) => { $r.u32()? };
+    (@decode $this:ident, $r:ident, $bools:ident, TemplateId) => { $r.u32()? };
+    (@decode $this:ident, $r:ident, $bools:ident, StyleSheetId) => { $r.u32()? };
+    (@decode $this:ident, $r:ident, $bools:ident, Str) => { $r.str_u16()? };
+    (@decode $this:ident, $r:ident, $bools:ident, LongStr) => { $r.str_u32()? };
+    (@decode $this:ident, $r:ident, $bools:ident, Element) => { $this.element($r)? };
+    (@decode $this:ident, $r:ident, $bools:ident, FullElement) => { $this.full_element($r)? };
+    (@decode $this:ident, $r:ident, $bools:ident, Roots) => { $this.roots($r)? };
+    (@decode $this:ident, $r:ident, $bools:ident, Attribute) => { attribute($r, $bools)? };
+    (@decode $this:ident, $r:ident, $bools:ident, Style) => { style_name($r)? };
+    (@decode $this:ident, $r:ident, $bools:ident, Styles) => { styles($r)? };
+    (@decode $this:ident, $r:ident, $bools:ident, IdRange) => {{
+        let start = $r.u32()?;
+        let end = if $bools.next() { $r.u32()? } else { start.saturating_add(1) };
+        start..end
+    }};
+}
+
+op_list!(native_operands);
+
+/// Applies batches to an in-memory [`Dom`]. Node ids, templates and style sheets work like they do in the browser.
+///
+/// Unlike the browser, the interpreter has no garbage collector to free removed nodes, so it frees them itself after a batch once the dom has grown to twice its size after the last collection. Every node in the same tree as a node stored with an id, a template, a node on the stack or the last node is kept, see [`NativeInterpreter::collect_garbage`]. Store the nodes you create with [`NativeInterpreter::dom_mut`] with an id to keep them.
+///
+/// Example:
+/// ```rust
+/// use sledgehammer_encoder::{batch::Batch, interpreter::NativeInterpreter, Element, MaybeId, NodeId};
+///
+/// let (mut interpreter, body) = NativeInterpreter::with_body();
+///
+/// let mut batch = Batch::default();
+/// batch.create_element(Element::div, None);
+/// batch.append_child(MaybeId::Node(NodeId(0)), MaybeId::LastNode);
+/// interpreter.run_batch(batch.finalize()).unwrap();
+///
+/// assert_eq!(interpreter.dom().to_html(body), "<body><div></div></body>");
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct NativeInterpreter {
+    dom: Dom,
+    nodes: Vec<Option<DomId>>,
+    templates: Vec<Option<DomId>>,
+    style_sheets: Vec<Option<String>>,
+    stack: Vec<DomId>,
+    last_node: Option<DomId>,
+    /// The id the current operation failed to find a node for
+    missing: Option<NodeId>,
+    /// The number of nodes the dom can grow to before the next collection
+    collect_at: usize,
+}
+
+/// The smallest number of nodes the dom can grow to before removed nodes are freed
+const MIN_COLLECT_AT: usize = 1024;
+
+impl NativeInterpreter {
+    /// Create an interpreter with a `<body>` element stored as the node with the id 0, like the body of a page a [`Batch`](crate::batch::Batch) is usually applied to
+    pub fn with_body() -> (Self, DomId) {
+        let mut interpreter = Self::default();
+        let body = interpreter.dom.create_element("body", None);
+        interpreter.set_node(NodeId(0), Some(body));
+        (interpreter, body)
+    }
+
+    /// The dom the interpreter applies batches to
+    pub fn dom(&self) -> &Dom {
+        &self.dom
+    }
+
+    /// The dom the interpreter applies batches to. Use this to create root nodes for [`NativeInterpreter::set_node`].
+    pub fn dom_mut(&mut self) -> &mut Dom {
+        &mut self.dom
+    }
+
+    /// Store a node with an id, or release the id with `None`
+    pub fn set_node(&mut self, id: NodeId, node: Option<DomId>) {
+        self.store(id.0, node);
+    }
+
+    /// Get the node stored with an id
+    pub fn get_node(&self, id: NodeId) -> Option<DomId> {
+        self.nodes.get(id.0 as usize).copied().flatten()
+    }
+
+    /// The last node that was created or navigated to
+    pub fn last_node(&self) -> Option<DomId> {
+        self.last_node
+    }
+
+    /// Get the css text of a style sheet
+    pub fn style_sheet(&self, id: StyleSheetId) -> Option<&str> {
+        self.style_sheets.get(id.0 as usize)?.as_deref()
+    }
+
+    /// Apply a finalized batch. Batches encoded in another version of the format are rejected before they are run.
+    pub fn run_batch(&mut self, batch: impl PreparedBatch) -> Result<(), InterpretError> {
+        batch.check_version().map_err(|err| InterpretError {
+            op_index: 0,
+            op: Op::first_code(batch.msg()),
+            node: None,
+            message: err.to_string(),
+        })?;
+        self.run(batch.msg(), batch.str())
+    }
+
+    /// Free every node that is not in the same tree as a node stored with an id, a template, a node on the stack or the last node. This runs automatically after a batch once the dom has grown enough, see [`NativeInterpreter`].
+    pub fn collect_garbage(&mut self) {
+        let roots = self
+            .nodes
+            .iter()
+            .chain(&self.templates)
+            .flatten()
+            .chain(&self.stack)
+            .chain(&self.last_node)
+            .copied();
+        self.dom.retain_trees(roots.collect::<Vec<_>>());
+        self.collect_at = (self.dom.len() * 2).max(MIN_COLLECT_AT);
+    }
+
+    /// Apply the message and strings of a batch
+    pub fn run(&mut self, msg: &[u8], str: &[u8]) -> Result<(), InterpretError> {
+        let result = self.run_ops(msg, str);
+        if self.dom.len() >= self.collect_at.max(MIN_COLLECT_AT) {
+            self.collect_garbage();
+        }
+        result
+    }
+
+    fn run_ops(&mut self, msg: &[u8], str: &[u8]) -> Result<(), InterpretError> {
+        let mut reader = Reader {
+            msg,
+            pos: 0,
+            str,
+            str_pos: 0,
+        };
+        let mut op_index = 0;
+        loop {
+            // operations are packed four to a u32
+            let ops = reader.u32().map_err(|_| InterpretError {
+                op_index,
+                op: 0,
+                node: None,
+                message: "the batch ended without a stop operation".to_string(),
+            })?;
+            for op in ops.to_le_bytes() {
+                let op_start = reader.pos;
+                match self.op(&mut reader, op) {
+                    Ok(true) => return Ok(()),
+                    Ok(false) => {}
+                    Err(message) => {
+                        return Err(InterpretError {
+                            op_index,
+                            op: Op::decode_code(op, msg, op_start),
+                            node: self.missing.take(),
+                            message,
+                        });
+                    }
+                }
+                // padding is not counted, so the index matches the operations that were queued
+                if op & 0x1F != Op::NoOp as u8 {
+                    op_index += 1;
+                }
+            }
+        }
+    }
+
+    fn store(&mut self, id: u32, node: Option<DomId>) {
+        let id = id as usize;
+        if self.nodes.len() <= id {
+            self.nodes.resize(id + 1, None);
+        }
+        self.nodes[id] = node;
+    }
+
+    fn node(&mut self, id: u32) -> Result<DomId, String> {
+        self.get_node(NodeId(id)).ok_or_else(|| {
+            self.missing = Some(NodeId(id));
+            format!("no node is stored with the id {id}")
+        })
+    }
+
+    fn last(&self) -> Result<DomId, String> {
+        self.last_node
+            .ok_or_else(|| "the last node is not set".to_string())
+    }
+
+    /// Read a node id if the flag is set, or use the last node
+    fn maybe_id(&mut self, r: &mut Reader, flag: bool) -> Result<DomId, String> {
+        if flag {
+            let id = r.u32()?;
+            self.node(id)
+        } else {
+            self.last()
+        }
+    }
+
+    /// Decode the nodes of an [`Operand::Nodes`](crate::batch::Operand::Nodes): many nodes with a u8 discriminant if the first bool is set, or a single node
+    fn nodes(&mut self, r: &mut Reader, bools: &mut Bools) -> Result<Vec<DomId>, String> {
+        let many = bools.next();
+        let single = bools.next();
+        if !many {
+            return Ok(vec![self.maybe_id(r, single)?]);
+        }
+        let count = r.u8()?;
+        (0..count)
+            .map(|_| {
+                let flag = r.u8()? != 0;
+                self.maybe_id(r, flag)
+            })
+            .collect()
+    }
+
+    /// Decode the nodes built with [`NodeBuilder`](crate::NodeBuilder)s after a u8 count
+    fn roots(&mut self, r: &mut Reader) -> Result<Vec<DomId>, String> {
+        let count = r.u8()?;
+        (0..count).map(|_| self.full_element(r)).collect()
+    }
+
+    /// Pop the topmost `count` nodes from the stack in the order they were pushed
+    fn pop_stack(&mut self, count: u16) -> Result<Vec<DomId>, String> {
+        let count = count as usize;
+        let start = self.stack.len().checked_sub(count).ok_or_else(|| {
+            format!(
+                "popped {count} nodes, but the stack only has {} nodes",
+                self.stack.len()
+            )
+        })?;
+        Ok(self.stack.drain(start..).collect())
+    }
+
+    /// Run one operation and return if it was the stop operation
+    fn op(&mut self, r: &mut Reader, op: u8) -> Result<bool, String> {
+        let code = match op & 0x1F {
+            EXTENDED_OP => {
+                let code = r.u8()?;
+                // operations below 32 are never encoded as extended operations
+                if code <= EXTENDED_OP {
+                    return Err(format!("{code} is not an extended operation"));
+                }
+                code
+            }
+            code => code,
+        };
+        let kind = Op::from_code(code).ok_or_else(|| format!("{code} is not an operation"))?;
+        match self.decode(r, kind, &mut Bools(op >> 5))? {
+            Operands::FirstChild {} => {
+                let node = self.last()?;
+                self.last_node = self.dom.children(node).first().copied();
+            }
+            Operands::NextSibling {} => {
+                let node = self.last()?;
+                self.last_node = self.dom.next_sibling(node);
+            }
+            Operands::ParentNode {} => {
+                let node = self.last()?;
+                self.last_node = self.dom.parent(node);
+            }
+            Operands::StoreWithId { id } => {
+                let node = self.last()?;
+                self.store(id, Some(node));
+            }
+            Operands::SetLastNode { id } => self.last_node = self.get_node(NodeId(id)),
+            Operands::Stop {} => return Ok(true),
+            Operands::BuildFullElement { el } => self.last_node = Some(el),
+            Operands::AppendChildren { root, child } => self.dom.append(root, &[child])?,
+            Operands::ReplaceWith { root, nodes } => self.dom.replace_with(root, &nodes)?,
+            Operands::InsertAfter { root, nodes } => self.dom.after(root, &nodes)?,
+            Operands::InsertBefore { root, nodes } => self.dom.before(root, &nodes)?,
+            Operands::Remove { node, free } => {
+                self.dom.detach(node);
+                if free {
+                    let removed: HashSet<_> = self.dom.subtree(node).into_iter().collect();
+                    for stored in self.nodes.iter_mut() {
+                        if matches!(stored, Some(n) if removed.contains(n)) {
+                            *stored = None;
+                        }
+                    }
+                }
+            }
+            Operands::CreateTextNode { text, id } => {
+                let node = self.dom.create_text(text);
+                self.create(node, id);
+            }
+            Operands::CreateElement { tag, id } => self.create(tag, id),
+            Operands::SetText { text, root } => self.dom.set_text_content(root, text),
+            Operands::SetAttribute {
+                attr: (name, namespace),
+                value,
+                root,
+            } => {
+                check_name(name)?;
+                self.dom
+                    .element_mut(root)?
+                    .set_attribute(namespace, name, value);
+            }
+            Operands::RemoveAttribute {
+                attr: (name, namespace),
+                root,
+            } => {
+                self.dom
+                    .element_mut(root)?
+                    .remove_attribute(namespace, name);
+            }
+            Operands::SetStyle {
+                style,
+                value,
+                important,
+                root,
+            } => self
+                .dom
+                .element_mut(root)?
+                .set_style(&style, value, important),
+            Operands::RemoveStyle { style, root } => {
+                self.dom.element_mut(root)?.remove_style(&style);
+            }
+            Operands::CloneNode { node, id } => {
+                let clone = self.dom.deep_clone(node)?;
+                self.create(clone, id);
+            }
+            Operands::NoOp {} => {}
+            Operands::SetInnerHtml { html, root, id } => {
+                if !self.dom.node(root).kind.can_have_children() {
+                    return Err("the node cannot have children".to_string());
+                }
+                self.dom.set_text_content(root, "");
+                let nodes = self.dom.parse_html(html);
+                self.dom.append(root, &nodes)?;
+                if let Some(id) = id {
+                    let first_child = self.dom.children(root).first().copied();
+                    self.store(id, first_child);
+                }
+            }
+            Operands::InsertAdjacentHtml {
+                position,
+                html,
+                root,
+                id,
+            } => {
+                if matches!(position, 0 | 3) && self.dom.parent(root).is_none() {
+                    return Err(format!(
+                        "NoModificationAllowedError: cannot insert html {} a node without a parent",
+                        POSITIONS[position as usize]
+                    ));
+                }
+                let nodes = self.dom.parse_html(html);
+                match position {
+                    0 => self.dom.before(root, &nodes)?,
+                    1 => self.dom.insert(root, 0, &nodes)?,
+                    2 => self.dom.append(root, &nodes)?,
+                    3 => self.dom.after(root, &nodes)?,
+                    _ => return Err(format!("{position} is not an insert position")),
+                }
+                if let Some(id) = id {
+                    // if the html was empty, no node is stored with the id
+                    self.store(id, nodes.first().copied());
+                }
+            }
+            Operands::CreateComment { text, id } => {
+                let node = self.dom.create_comment(text);
+                self.create(node, id);
+            }
+            Operands::CreateFragment { id } => {
+                let node = self.dom.create_fragment();
+                self.create(node, id);
+            }
+            Operands::CreateTemplate { id, roots } => {
+                let template = self.dom.create_fragment();
+                self.dom.append(template, &roots)?;
+                let id = id as usize;
+                if self.templates.len() <= id {
+                    self.templates.resize(id + 1, None);
+                }
+                self.templates[id] = Some(template);
+            }
+            Operands::CloneTemplate { template, id } => {
+                let template_node = self
+                    .templates
+                    .get(template as usize)
+                    .copied()
+                    .flatten()
+                    .ok_or_else(|| format!("no template is stored with the id {template}"))?;
+                let node = self.dom.deep_clone(template_node)?;
+                self.create(node, id);
+            }
+            Operands::AttachShadow { root, mode, id } => {
+                if self.dom.element_mut(root)?.shadow_root.is_some() {
+                    return Err(
+                        "NotSupportedError: the element already has a shadow root".to_string()
+                    );
+                }
+                let shadow = self.dom.push(NodeKind::ShadowRoot {
+                    host: root,
+                    mode,
+                    adopted_style_sheets: Vec::new(),
+                });
+                self.dom.element_mut(root)?.shadow_root = Some(shadow);
+                self.create(shadow, Some(id));
+            }
+            Operands::CreateStyleSheet { css, id } => {
+                let id = id as usize;
+                if self.style_sheets.len() <= id {
+                    self.style_sheets.resize(id + 1, None);
+                }
+                self.style_sheets[id] = Some(css.to_string());
+            }
+            Operands::AdoptStyleSheet { root, sheet } => {
+                let sheet = StyleSheetId(sheet);
+                if self.style_sheet(sheet).is_none() {
+                    return Err(format!("no style sheet is stored with the id {}", sheet.0));
+                }
+                match self.dom.kind_mut(root) {
+                    NodeKind::Document {
+                        adopted_style_sheets,
+                    }
+                    | NodeKind::ShadowRoot {
+                        adopted_style_sheets,
+                        ..
+                    } => adopted_style_sheets.push(sheet),
+                    _ => {
+                        return Err(
+                            "only documents and shadow roots can adopt style sheets".to_string()
+                        )
+                    }
+                }
+            }
+            Operands::FreeIds { ids } => {
+                let end = (ids.end as usize).min(self.nodes.len());
+                let start = ids.start as usize;
+                if start < end {
+                    self.nodes[start..end].fill(None);
+                }
+            }
+            Operands::PushRoot { root } => self.stack.push(root),
+            Operands::AppendChildrenFromStack { root, n } => {
+                let nodes = self.pop_stack(n)?;
+                self.dom.append(root, &nodes)?;
+            }
+            Operands::ReplaceWithFromStack { root, n } => {
+                let nodes = self.pop_stack(n)?;
+                self.dom.replace_with(root, &nodes)?;
+            }
+            Operands::InsertAfterFromStack { root, n } => {
+                let nodes = self.pop_stack(n)?;
+                self.dom.after(root, &nodes)?;
+            }
+            Operands::InsertBeforeFromStack { root, n } => {
+                let nodes = self.pop_stack(n)?;
+                self.dom.before(root, &nodes)?;
+            }
+            Operands::AppendData { text, root } => {
+                self.dom.character_data_mut(root)?.push_str(text);
+            }
+            Operands::InsertData { offset, text, root } => {
+                let data = self.dom.character_data_mut(root)?;
+                let offset = utf16_to_byte_offset(data, offset)?;
+                data.insert_str(offset, text);
+            }
+            Operands::DeleteData {
+                offset,
+                count,
+                root,
+            } => {
+                let data = self.dom.character_data_mut(root)?;
+                let range = utf16_range(data, offset, count)?;
+                data.replace_range(range, "");
+            }
+            Operands::ReplaceData {
+                offset,
+                count,
+                text,
+                root,
+            } => {
+                let data = self.dom.character_data_mut(root)?;
+                let range = utf16_range(data, offset, count)?;
+                data.replace_range(range, text);
+            }
+            Operands::SetStyles { styles, root } => {
+                for (name, value) in styles {
+                    self.dom.element_mut(root)?.set_style(&name, value, false);
+                }
+            }
+            Operands::FreeTemplate { template } => {
+                if let Some(stored) = self.templates.get_mut(template as usize) {
+                    *stored = None;
+                }
+            }
+            Operands::FreeStyleSheet { sheet } => {
+                if let Some(stored) = self.style_sheets.get_mut(sheet as usize) {
+                    *stored = None;
+                }
+            }
+        }
+        Ok(false)
+    }
+
+    /// Set the node an operation created as the last node, and store it with an id if one was given
+    fn create(&mut self, node: DomId, id: Option<u32>) {
+        self.last_node = Some(node);
+        if let Some(id) = id {
+            self.store(id, Some(node));
+        }
+    }
+
+    /// Decode an element name and create the element
+    fn element(&mut self, r: &mut Reader) -> Result<DomId, String> {
+        let (name, namespace) = match r.u8()? {
+            // the element is encoded as an enum and the namespace is encoded as a string
+            255 => {
+                let name = element_name(r.u8()?)?;
+                (name, Some(r.str_u16()?))
+            }
+            // the element is encoded as a string
+            254 => (r.str_u16()?, None),
+            // the element and namespace are encoded as strings
+            253 => {
+                let name = r.str_u16()?;
+                (name, Some(r.str_u16()?))
+            }
+            252 => {
+                let i = r.u8()? as usize;
+                let element = SvgElement::ALL
+                    .get(i)
+                    .ok_or_else(|| format!("{i} is not a svg element"))?;
+                (element.as_str(), Some(SVG_NAMESPACE))
+            }
+            251 => {
+                let i = r.u8()? as usize;
+                let element = MathMlElement::ALL
+                    .get(i)
+                    .ok_or_else(|| format!("{i} is not a MathML element"))?;
+                (element.as_str(), Some(MATHML_NAMESPACE))
+            }
+            i => (element_name(i)?, None),
+        };
+        check_name(name)?;
+        Ok(self.dom.create_element(name, namespace))
+    }
+
+    /// Decode a node built with a [`NodeBuilder`](crate::NodeBuilder)
+    fn full_element(&mut self, r: &mut Reader) -> Result<DomId, String> {
+        let kind = r.u8()?;
+        let id = if kind & 0x1 != 0 {
+            Some(r.u32()?)
+        } else {
+            None
+        };
+        let node = if kind & 0x2 != 0 {
+            let text = r.str_u16()?;
+            self.dom.create_text(text)
+        } else if kind & 0x4 != 0 {
+            let text = r.str_u16()?;
+            self.dom.create_comment(text)
+        } else {
+            let element = self.element(r)?;
+            let num_attributes = r.u8()?;
+            let num_children = r.u8()?;
+            for _ in 0..num_attributes {
+                let (name, namespace) = match r.u8()? {
+                    // the attribute is encoded as an enum and the namespace is encoded as a string
+                    255 => {
+                        let name = attribute_name(r.u8()?)?;
+                        (name, Some(r.str_u16()?))
+                    }
+                    // the attribute is encoded as a string
+                    254 => (r.str_u16()?, None),
+                    // the attribute and namespace are encoded as strings
+                    253 => {
+                        let name = r.str_u16()?;
+                        (name, Some(r.str_u16()?))
+                    }
+                    i => (attribute_name(i)?, None),
+                };
+                let value = r.str_u16()?;
+                check_name(name)?;
+                self.dom
+                    .element_mut(element)?
+                    .set_attribute(namespace, name, value);
+            }
+            for _ in 0..num_children {
+                let child = self.full_element(r)?;
+                self.dom.append(element, &[child])?;
+            }
+            element
+        };
+        if let Some(id) = id {
+            self.store(id, Some(node));
+        }
+        Ok(node)
+    }
+}
+
+/// Check that an element or attribute name could be created in the browser
+fn check_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || name.contains(|c: char| c.is_ascii_whitespace() || "<>/=\"'".contains(c))
+    {
+        return Err(format!(
+            "InvalidCharacterError: {name:?} is not a valid name"
+        ));
+    }
+    Ok(())
+}
+
+fn element_name(i: u8) -> Result<&'static str, String> {
+    Element::ALL
+        .get(i as usize)
+        .map(Element::as_str)
+        .ok_or_else(|| format!("{i} is not an element"))
+}
+
+/// Namespaced attributes are encoded after the html attributes in the same byte
+fn attribute_name(i: u8) -> Result<&'static str, String> {
+    let i = i as usize;
+    if let Some(attr) = Attribute::ALL.get(i) {
+        return Ok(attr.as_str());
+    }
+    let i = i - Attribute::COUNT;
+    if let Some(attr) = SvgAttribute::ALL.get(i) {
+        return Ok(attr.as_str());
+    }
+    MathMlAttribute::ALL
+        .get(i - SvgAttribute::COUNT)
+        .map(MathMlAttribute::as_str)
+        .ok_or_else(|| format!("{} is not an attribute", i + Attribute::COUNT))
+}
+
+/// Decode a style property name encoded with a u8 discriminant
+fn style_name(r: &mut Reader) -> Result<String, String> {
+    Ok(match r.u8()? {
+        // the property is a string
+        255 => r.str_u16()?.to_string(),
+        // the property is a custom property without the leading --
+        254 => format!("--{}", r.str_u16()?),
+        i => Style::ALL
+            .get(i as usize)
+            .ok_or_else(|| format!("{i} is not a style property"))?
+            .as_str()
+            .to_string(),
+    })
+}
+
+/// Decode an attribute name and namespace. The first bool is set if the name is a string, and the second if the attribute has a namespace
+fn attribute<'a>(
+    r: &mut Reader<'a>,
+    bools: &mut Bools,
+) -> Result<(&'a str, Option<&'a str>), String> {
+    let name = if bools.next() {
+        r.str_u16()?
+    } else {
+        attribute_name(r.u8()?)?
+    };
+    let namespace = if bools.next() {
+        Some(r.str_u16()?)
+    } else {
+        None
+    };
+    Ok((name, namespace))
+}
+
+/// Decode a u16 count of style properties and their values
+fn styles<'a>(r: &mut Reader<'a>) -> Result<Vec<(String, &'a str)>, String> {
+    let count = r.u16()?;
+    (0..count)
+        .map(|_| Ok((style_name(r)?, r.str_u16()?)))
+        .collect()
+}
+
+/// Convert an offset in UTF-16 code units into a byte offset into the text
+fn utf16_to_byte_offset(text: &str, offset: u32) -> Result<usize, String> {
+    let mut units = 0;
+    for (i, c) in text.char_indices() {
+        if units >= offset {
+            return if units == offset {
+                Ok(i)
+            } else {
+                Err(format!("{offset} is in the middle of a character"))
+            };
+        }
+        units += c.len_utf16() as u32;
+    }
+    if units == offset {
+        Ok(text.len())
+    } else {
+        Err(format!(
+            "IndexSizeError: {offset} is past the end of the data ({units} code units)"
+        ))
+    }
+}
+
+/// Convert a range in UTF-16 code units into a byte range, clamping the end like `CharacterData.deleteData`
+fn utf16_range(text: &str, offset: u32, count: u32) -> Result<std::ops::Range<usize>, String> {
+    let start = utf16_to_byte_offset(text, offset)?;
+    let total = text.chars().map(|c| c.len_utf16() as u32).sum::<u32>();
+    let end = utf16_to_byte_offset(text, offset.saturating_add(count).min(total))?;
+    Ok(start..end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        batch::Batch, Element, ElementBuilder, InsertPosition, MaybeId, ShadowRootMode,
+        StyleSheetId, TemplateId,
+    };
+
+    #[test]
+    fn inner_html_is_parsed_and_stored() {
+        let (mut interpreter, body) = NativeInterpreter::with_body();
+        let mut batch = Batch::default();
+        batch.set_inner_html(
+            "<p>one</p><p>two</p>",
+            MaybeId::Node(NodeId(0)),
+            Some(NodeId(1)),
+        );
+        batch.set_text("first", MaybeId::Node(NodeId(1)));
+        interpreter.run_batch(batch.finalize()).unwrap();
+        assert_eq!(
+            interpreter.dom().to_html(body),
+            "<body><p>first</p><p>two</p></body>"
+        );
+
+        let mut batch = Batch::default();
+        batch.set_inner_html("", MaybeId::Node(NodeId(0)), Some(NodeId(1)));
+        interpreter.run_batch(batch.finalize()).unwrap();
+        assert_eq!(interpreter.dom().to_html(body), "<body></body>");
+        assert_eq!(interpreter.get_node(NodeId(1)), None);
+    }
+
+    #[test]
+    fn inner_html_with_non_ascii_text() {
+        let (mut interpreter, body) = NativeInterpreter::with_body();
+        for html in ["é", "<b>é</b>日本<i>🦀</i>"] {
+            let mut batch = Batch::default();
+            batch.set_inner_html(html, MaybeId::Node(NodeId(0)), None);
+            interpreter.run_batch(batch.finalize()).unwrap();
+            assert_eq!(
+                interpreter.dom().to_html(body),
+                format!("<body>{html}</body>")
+            );
+        }
+    }
+
+    #[test]
+    fn html_longer_than_a_u16_length() {
+        let (mut interpreter, body) = NativeInterpreter::with_body();
+        let text = "x".repeat(70_000);
+        let mut batch = Batch::default();
+        batch.set_inner_html(
+            format!("<p>{text}</p>").as_str(),
+            MaybeId::Node(NodeId(0)),
+            Some(NodeId(1)),
+        );
+        batch.insert_adjacent_html(
+            InsertPosition::BeforeEnd,
+            format!("<b>{text}</b>").as_str(),
+            MaybeId::Node(NodeId(0)),
+            None,
+        );
+        // the strings after the html are read from the right offset
+        batch.set_attribute(Attribute::class, "after", MaybeId::Node(NodeId(1)));
+        interpreter.run_batch(batch.finalize()).unwrap();
+        assert_eq!(
+            interpreter.dom().to_html(body),
+            format!(r#"<body><p class="after">{text}</p><b>{text}</b></body>"#)
+        );
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "a string of 70000 bytes is longer than the 65535 bytes an operation can encode"
+    )]
+    fn text_longer_than_a_u16_length_is_rejected() {
+        let mut batch = Batch::default();
+        batch.set_text("x".repeat(70_000).as_str(), MaybeId::LastNode);
+    }
+
+    #[test]
+    fn removed_nodes_are_freed() {
+        let (mut interpreter, body) = NativeInterpreter::with_body();
+        let mut batch = Batch::default();
+        batch.set_inner_html("<main>kept</main>", MaybeId::Node(NodeId(0)), None);
+        for i in 0..10_000 {
+            batch.set_inner_html(
+                "<p><b>removed</b></p>",
+                MaybeId::Node(NodeId(0)),
+                Some(NodeId(1)),
+            );
+            batch.remove(MaybeId::Node(NodeId(1)));
+            batch.insert_adjacent_html(
+                InsertPosition::BeforeEnd,
+                "<main>kept</main>",
+                MaybeId::Node(NodeId(0)),
+                None,
+            );
+            if i % 100 == 0 {
+                interpreter
+                    .run_batch(std::mem::take(&mut batch).finalize())
+                    .unwrap();
+            }
+        }
+        interpreter.run_batch(batch.finalize()).unwrap();
+        let dom = interpreter.dom();
+        assert_eq!(dom.to_html(body), "<body><main>kept</main></body>");
+        assert!(dom.len() < 4096, "{} nodes were not freed", dom.len());
+    }
+
+    #[test]
+    fn nodes_in_removed_shadow_roots_are_freed() {
+        let (mut interpreter, _) = NativeInterpreter::with_body();
+        let mut batch = Batch::default();
+        batch.create_element(Element::div, Some(NodeId(1)));
+        batch.append_child(MaybeId::Node(NodeId(0)), MaybeId::LastNode);
+        batch.attach_shadow(MaybeId::Node(NodeId(1)), ShadowRootMode::Closed, NodeId(2));
+        batch.create_element(Element::p, Some(NodeId(3)));
+        batch.append_child(MaybeId::Node(NodeId(2)), MaybeId::LastNode);
+        batch.remove_and_free(MaybeId::Node(NodeId(1)));
+        interpreter.run_batch(batch.finalize()).unwrap();
+        assert!((1..=3).all(|id| interpreter.get_node(NodeId(id)).is_none()));
+        assert!(interpreter.get_node(NodeId(0)).is_some());
+    }
+
+    #[test]
+    fn templates_and_style_sheets_are_freed() {
+        let (mut interpreter, _) = NativeInterpreter::with_body();
+        let mut batch = Batch::default();
+        batch.create_template(
+            TemplateId(0),
+            &[ElementBuilder::new(Element::p.into()).into()],
+        );
+        batch.create_style_sheet("p { color: red; }", StyleSheetId(0));
+        batch.free_template(TemplateId(0));
+        batch.free_style_sheet(StyleSheetId(0));
+        interpreter.run_batch(batch.finalize()).unwrap();
+        assert_eq!(interpreter.style_sheet(StyleSheetId(0)), None);
+
+        let mut batch = Batch::default();
+        batch.clone_template(TemplateId(0), None);
+        let error = interpreter.run_batch(batch.finalize()).unwrap_err();
+        assert_eq!(error.message, "no template is stored with the id 0");
+    }
+
+    #[test]
+    fn adjacent_html_stores_the_first_inserted_node() {
+        let (mut interpreter, body) = NativeInterpreter::with_body();
+        let mut batch = Batch::default();
+        batch.set_inner_html(
+            "<main>main</main>",
+            MaybeId::Node(NodeId(0)),
+            Some(NodeId(1)),
+        );
+        let inserts = [
+            (InsertPosition::BeforeBegin, "before begin"),
+            (InsertPosition::AfterBegin, "after begin"),
+            (InsertPosition::BeforeEnd, "before end"),
+            (InsertPosition::AfterEnd, "after end"),
+        ];
+        for (id, (position, text)) in (2..).zip(inserts) {
+            batch.insert_adjacent_html(
+                position,
+                "<i>x</i><b>y</b>",
+                MaybeId::Node(NodeId(1)),
+                Some(NodeId(id)),
+            );
+            batch.set_text(text, MaybeId::Node(NodeId(id)));
+        }
+        interpreter.run_batch(batch.finalize()).unwrap();
+        assert_eq!(
+            interpreter.dom().to_html(body),
+            concat!(
+                "<body><i>before begin</i><b>y</b>",
+                "<main><i>after begin</i><b>y</b>main<i>before end</i><b>y</b></main>",
+                "<i>after end</i><b>y</b></body>"
+            )
+        );
+
+        // nothing is inserted, so the id must not point at a neighbouring node
+        for position in [InsertPosition::AfterBegin, InsertPosition::AfterEnd] {
+            let mut batch = Batch::default();
+            batch.insert_adjacent_html(position, "", MaybeId::Node(NodeId(1)), Some(NodeId(6)));
+            interpreter.run_batch(batch.finalize()).unwrap();
+            assert_eq!(interpreter.get_node(NodeId(6)), None);
+        }
+    }
+}
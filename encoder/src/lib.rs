@@ -1,12 +1,24 @@
 pub mod attribute;
 pub mod batch;
+pub mod dom;
 pub mod element;
+pub mod interpreter;
+pub mod namespaced;
+pub mod record;
+pub mod spec;
+pub mod stream;
+pub mod style;
 
 use std::{fmt::Arguments, io::Write};
 
 pub use attribute::{Attribute, IntoAttribue};
-pub use batch::{Op, StaticBatch};
-pub use element::{Element, ElementBuilder, IntoElement, NodeBuilder, TextBuilder};
+pub use batch::{Op, StaticBatch, VersionMismatch, FORMAT_VERSION};
+pub use element::{CommentBuilder, Element, ElementBuilder, IntoElement, NodeBuilder, TextBuilder};
+pub use interpreter::{InterpretError, NativeInterpreter};
+pub use namespaced::{MathMlAttribute, MathMlElement, SvgAttribute, SvgElement};
+pub use record::{Recording, Replay};
+pub use stream::{Frame, FrameReader, FrameWriter};
+pub use style::{CustomProperty, IntoStyle, Style};
 
 /// Something that lives in a namespace like a tag or attribute
 #[derive(Clone, Copy)]
@@ -15,7 +27,7 @@ pub struct InNamespace<'a, T>(pub T, pub &'a str);
 /// Something that can live in a namespace
 pub trait WithNsExt {
     /// Moves the item into a namespace
-    fn in_namespace(self, namespace: &str) -> InNamespace<Self>
+    fn in_namespace(self, namespace: &str) -> InNamespace<'_, Self>
     where
         Self: Sized,
     {
@@ -25,10 +37,10 @@ pub trait WithNsExt {
 
 impl WithNsExt for Element {}
 impl WithNsExt for Attribute {}
-impl<'a> WithNsExt for &'a str {}
+impl WithNsExt for &str {}
 
 /// An id that may be either the last node or a node with an assigned id.
-#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum MaybeId {
     /// The last node that was created or navigated to.
     LastNode,
@@ -36,21 +48,57 @@ pub enum MaybeId {
     Node(NodeId),
 }
 
-impl MaybeId {
-    #[inline(always)]
-    pub(crate) const fn encoded_size(&self) -> u8 {
-        match self {
-            MaybeId::LastNode => 0,
-            MaybeId::Node(_) => 4,
-        }
-    }
+/// The position to insert html at relative to a node. See [`Batch::insert_adjacent_html`](batch::Batch::insert_adjacent_html).
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum InsertPosition {
+    /// Before the node itself.
+    BeforeBegin = 0,
+    /// Inside the node, before its first child.
+    AfterBegin = 1,
+    /// Inside the node, after its last child.
+    BeforeEnd = 2,
+    /// After the node itself.
+    AfterEnd = 3,
+}
+
+/// The encapsulation mode of a shadow root. See [`Batch::attach_shadow`](batch::Batch::attach_shadow).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShadowRootMode {
+    /// The shadow root can be accessed from javascript outside of the root with `element.shadowRoot`.
+    Open,
+    /// The shadow root cannot be accessed from javascript outside of the root.
+    Closed,
 }
 
 /// A node that was created and stored with an id
 /// It is recommended to create and store ids with a slab allocator with an exposed slab index for example the excellent [slab](https://docs.rs/slab) crate.
-#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct NodeId(pub u32);
 
+/// A style sheet that was created and stored with an id
+/// Style sheets are stored separately from nodes, so a style sheet and a node can share the same id.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StyleSheetId(pub u32);
+
+/// A template that was created and stored with an id
+/// Templates are stored separately from nodes, so a template and a node can share the same id.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TemplateId(pub u32);
+
+/// Converts a byte offset into a rust string into an offset in UTF-16 code units, which is the unit the DOM uses for text offsets.
+///
+/// The length of a range in UTF-16 code units is the difference between the offsets of the start and end of the range.
+///
+/// # Panics
+///
+/// Panics if the byte offset is not on a char boundary or is out of bounds.
+pub fn utf16_offset(text: &str, byte_offset: usize) -> u32 {
+    text[..byte_offset]
+        .chars()
+        .map(char::len_utf16)
+        .sum::<usize>() as u32
+}
+
 /// Something that can be written as a utf-8 string to a buffer
 pub trait WritableText {
     fn write_as_text(self, to: &mut Vec<u8>);
@@ -62,7 +110,7 @@ impl WritableText for char {
     }
 }
 
-impl<'a> WritableText for &'a str {
+impl WritableText for &str {
     #[inline(always)]
     fn write_as_text(self, to: &mut Vec<u8>) {
         let len = self.len();
@@ -101,6 +149,11 @@ macro_rules! write_unsized {
     ($t: ty) => {
         impl WritableText for $t {
             fn write_as_text(self, to: &mut Vec<u8>) {
+                // zero has no digits in the loop below
+                if self == 0 {
+                    to.push(b'0');
+                    return;
+                }
                 let mut n = self;
                 let mut n2 = n;
                 let mut num_digits = 0;
@@ -137,6 +190,11 @@ macro_rules! write_sized {
     ($t: ty) => {
         impl WritableText for $t {
             fn write_as_text(self, to: &mut Vec<u8>) {
+                // zero has no digits in the loop below
+                if self == 0 {
+                    to.push(b'0');
+                    return;
+                }
                 let neg = self < 0;
                 let mut n = if neg {
                     match self.checked_abs() {
@@ -195,3 +253,161 @@ write_sized!(i32);
 write_sized!(i64);
 write_sized!(i128);
 write_sized!(isize);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    const INTERPRETERS: [&str; 3] = [
+        include_str!("../../web/interpreter.js"),
+        include_str!("../../web/interpreter_opt.js"),
+        include_str!("../../web/interpreter_debug.js"),
+    ];
+
+    /// Read the strings in a `const name = [...]` table from the interpreter
+    fn js_table<'a>(interpreter: &'a str, name: &str) -> Vec<&'a str> {
+        let start = interpreter
+            .find(&format!("const {name} = ["))
+            .unwrap_or_else(|| panic!("missing table {name}"));
+        let table = &interpreter[start..];
+        let table = &table[table.find('[').unwrap() + 1..table.find(']').unwrap()];
+        table
+            .lines()
+            .flat_map(|line| line.split("//").next().unwrap().split(','))
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+            .map(|s| s.trim_matches('"'))
+            .collect()
+    }
+
+    /// Check that every name in the table parses into the value with the same index, and back into the same name
+    fn check_table<T: FromStr>(
+        names: &[&str],
+        offset: usize,
+        count: usize,
+        index: impl Fn(&T) -> usize,
+        as_str: impl Fn(&T) -> &'static str,
+    ) {
+        assert_eq!(names.len(), count);
+        for (i, name) in names.iter().enumerate() {
+            let value = T::from_str(name)
+                .unwrap_or_else(|_| panic!("{name} is in the js table but not in rust"));
+            assert_eq!(index(&value), offset + i, "{name} is at the wrong index");
+            assert_eq!(as_str(&value), *name);
+        }
+    }
+
+    fn text(value: impl WritableText) -> String {
+        let mut out = Vec::new();
+        value.write_as_text(&mut out);
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn numbers_are_written_as_text() {
+        assert_eq!(text(0u8), "0");
+        assert_eq!(text(0u32), "0");
+        assert_eq!(text(0i64), "0");
+        assert_eq!(text(7u16), "7");
+        assert_eq!(text(1203u64), "1203");
+        assert_eq!(text(-45i32), "-45");
+        assert_eq!(text(u128::MAX), u128::MAX.to_string());
+    }
+
+    #[test]
+    fn js_tables_match() {
+        for interpreter in INTERPRETERS {
+            check_table::<Element>(
+                &js_table(interpreter, "els"),
+                0,
+                Element::COUNT,
+                |e| *e as usize,
+                Element::as_str,
+            );
+            check_table::<SvgElement>(
+                &js_table(interpreter, "svgEls"),
+                0,
+                SvgElement::COUNT,
+                |e| *e as usize,
+                SvgElement::as_str,
+            );
+            check_table::<MathMlElement>(
+                &js_table(interpreter, "mathMlEls"),
+                0,
+                MathMlElement::COUNT,
+                |e| *e as usize,
+                MathMlElement::as_str,
+            );
+            check_table::<Style>(
+                &js_table(interpreter, "styles"),
+                0,
+                Style::COUNT,
+                |s| *s as usize,
+                Style::as_str,
+            );
+
+            // namespaced attributes share the attribute table
+            let attrs = js_table(interpreter, "attrs");
+            let (html, namespaced) = attrs.split_at(Attribute::COUNT);
+            let (svg, mathml) = namespaced.split_at(SvgAttribute::COUNT);
+            check_table::<Attribute>(
+                html,
+                0,
+                Attribute::COUNT,
+                |a| *a as usize,
+                Attribute::as_str,
+            );
+            check_table::<SvgAttribute>(
+                svg,
+                Attribute::COUNT,
+                SvgAttribute::COUNT,
+                |a| *a as usize,
+                SvgAttribute::as_str,
+            );
+            check_table::<MathMlAttribute>(
+                mathml,
+                Attribute::COUNT + SvgAttribute::COUNT,
+                MathMlAttribute::COUNT,
+                |a| *a as usize,
+                MathMlAttribute::as_str,
+            );
+        }
+    }
+
+    #[test]
+    fn utf16_offsets() {
+        assert_eq!(utf16_offset("hello", 0), 0);
+        assert_eq!(utf16_offset("hello", 3), 3);
+        assert_eq!(utf16_offset("hello", 5), 5);
+        // é is two bytes in utf-8 and one code unit in utf-16
+        assert_eq!(utf16_offset("héllo", 3), 2);
+        assert_eq!(utf16_offset("日本語", 6), 2);
+        // characters outside the basic multilingual plane are a surrogate pair in utf-16
+        assert_eq!(utf16_offset("a🦀b", 5), 3);
+        assert_eq!(utf16_offset("a🦀b", 6), 4);
+    }
+
+    #[test]
+    #[should_panic]
+    fn utf16_offset_past_the_end() {
+        utf16_offset("abc", 4);
+    }
+
+    #[test]
+    #[should_panic]
+    fn utf16_offset_inside_a_char() {
+        utf16_offset("a🦀b", 2);
+    }
+
+    #[test]
+    fn first_code_reads_extended_ops() {
+        let mut batch = batch::Batch::default();
+        batch.push_root(MaybeId::LastNode);
+        assert_eq!(Op::first_code(&batch.finalize().msg), Op::PushRoot as u8);
+        let mut batch = batch::Batch::default();
+        batch.remove(MaybeId::LastNode);
+        assert_eq!(Op::first_code(&batch.finalize().msg), Op::Remove as u8);
+        assert_eq!(Op::first_code(&[]), Op::Stop as u8);
+    }
+}
@@ -0,0 +1,380 @@
+//! Elements and attributes from the SVG and MathML namespaces.
+//!
+//! Elements in these enums are created in their namespace automatically, so the namespace does not need to be sent with every element.
+#![allow(non_camel_case_types)]
+
+use crate::{
+    attribute::AnyAttribute, batch::Batch, element::AnyElement, Attribute, IntoAttribue,
+    IntoElement,
+};
+
+macro_rules! namespaced_elements {
+    ($(#[$attr: meta])* $name: ident, $any: ident, $discriminant: literal, $($i: ident: $el: literal),*) => {
+        $(#[$attr])*
+        #[derive(Copy, Clone)]
+        pub enum $name {
+            $(
+                $i
+            ),*
+        }
+
+        impl $name {
+            #[allow(unused)]
+            pub(crate) const COUNT: usize = [$($el),*].len();
+
+            /// Every element in the order they are encoded
+            pub(crate) const ALL: [Self; Self::COUNT] = [$(Self::$i),*];
+
+            /// The case-sensitive name of the element
+            pub const fn as_str(&self) -> &'static str {
+                match self {
+                    $(
+                        Self::$i => $el,
+                    )*
+                }
+            }
+
+            /// Turn into an [`AnyElement`] in a const context
+            pub const fn any_element_const<'a, 'b>(self) -> AnyElement<'a, 'b> {
+                AnyElement::$any(self)
+            }
+        }
+
+        impl std::str::FromStr for $name {
+            type Err = crate::element::NotElementError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                Ok(match s {
+                    $(
+                        $el => Self::$i,
+                    )*
+                    _ => return Err(crate::element::NotElementError)
+                })
+            }
+        }
+
+        impl<'a, 'b> IntoElement<'a, 'b> for $name {
+            #[inline(always)]
+            fn encode(&self, v: &mut Batch) {
+                v.msg.push($discriminant);
+                v.msg.push(*self as u8);
+            }
+
+            #[inline(always)]
+            unsafe fn encode_prealloc(&self, v: &mut Batch) {
+                v.encode_u8_prealloc($discriminant);
+                v.encode_u8_prealloc(*self as u8);
+            }
+        }
+
+        impl<'a, 'b> From<$name> for AnyElement<'a, 'b> {
+            fn from(e: $name) -> Self {
+                AnyElement::$any(e)
+            }
+        }
+    };
+}
+
+macro_rules! namespaced_attributes {
+    ($(#[$attr: meta])* $name: ident, $any: ident, $offset: expr, $first: ident: $first_attr: literal $(, $i: ident: $a: literal)*) => {
+        $(#[$attr])*
+        #[derive(Copy, Clone)]
+        pub enum $name {
+            $first = $offset as isize,
+            $(
+                $i
+            ),*
+        }
+
+        impl $name {
+            #[allow(unused)]
+            pub(crate) const COUNT: usize = [$first_attr $(, $a)*].len();
+
+            /// Every attribute in the order they are encoded, starting at the offset
+            pub(crate) const ALL: [Self; Self::COUNT] = [Self::$first $(, Self::$i)*];
+
+            /// The case-sensitive name of the attribute
+            pub const fn as_str(&self) -> &'static str {
+                match self {
+                    Self::$first => $first_attr,
+                    $(
+                        Self::$i => $a,
+                    )*
+                }
+            }
+
+            /// Turn into an [`AnyAttribute`] in a const context
+            pub const fn any_attr_const<'a, 'b>(self) -> AnyAttribute<'a, 'b> {
+                AnyAttribute::$any(self)
+            }
+        }
+
+        impl std::str::FromStr for $name {
+            type Err = crate::attribute::NotElementError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                Ok(match s {
+                    $first_attr => Self::$first,
+                    $(
+                        $a => Self::$i,
+                    )*
+                    _ => return Err(crate::attribute::NotElementError)
+                })
+            }
+        }
+
+        impl<'a, 'b> IntoAttribue<'a, 'b> for $name {
+            const SINGLE_BYTE: bool = true;
+
+            #[inline(always)]
+            fn encode(self, v: &mut Batch) {
+                v.encode_bool(false);
+                v.encode_bool(false);
+                v.msg.push(self as u8);
+            }
+
+            #[inline(always)]
+            unsafe fn encode_prealloc(self, v: &mut Batch) {
+                v.encode_bool(false);
+                v.encode_bool(false);
+                v.encode_u8_prealloc(self as u8);
+            }
+
+            #[inline(always)]
+            unsafe fn encode_u8_discriminant_prealloc(self, v: &mut Batch) {
+                v.encode_u8_prealloc(self as u8)
+            }
+        }
+
+        impl<'a, 'b> From<$name> for AnyAttribute<'a, 'b> {
+            fn from(a: $name) -> Self {
+                AnyAttribute::$any(a)
+            }
+        }
+    };
+}
+
+namespaced_elements! {
+    /// Elements in the SVG namespace
+    /// These are created with the SVG namespace and encoded with two bytes
+    SvgElement,
+    Svg,
+    252,
+    a: "a",
+    animate: "animate",
+    animate_motion: "animateMotion",
+    animate_transform: "animateTransform",
+    circle: "circle",
+    clip_path: "clipPath",
+    defs: "defs",
+    desc: "desc",
+    ellipse: "ellipse",
+    fe_blend: "feBlend",
+    fe_color_matrix: "feColorMatrix",
+    fe_component_transfer: "feComponentTransfer",
+    fe_composite: "feComposite",
+    fe_convolve_matrix: "feConvolveMatrix",
+    fe_diffuse_lighting: "feDiffuseLighting",
+    fe_displacement_map: "feDisplacementMap",
+    fe_distant_light: "feDistantLight",
+    fe_drop_shadow: "feDropShadow",
+    fe_flood: "feFlood",
+    fe_func_a: "feFuncA",
+    fe_func_b: "feFuncB",
+    fe_func_g: "feFuncG",
+    fe_func_r: "feFuncR",
+    fe_gaussian_blur: "feGaussianBlur",
+    fe_image: "feImage",
+    fe_merge: "feMerge",
+    fe_merge_node: "feMergeNode",
+    fe_morphology: "feMorphology",
+    fe_offset: "feOffset",
+    fe_point_light: "fePointLight",
+    fe_specular_lighting: "feSpecularLighting",
+    fe_spot_light: "feSpotLight",
+    fe_tile: "feTile",
+    fe_turbulence: "feTurbulence",
+    filter: "filter",
+    foreign_object: "foreignObject",
+    g: "g",
+    image: "image",
+    line: "line",
+    linear_gradient: "linearGradient",
+    marker: "marker",
+    mask: "mask",
+    metadata: "metadata",
+    mpath: "mpath",
+    path: "path",
+    pattern: "pattern",
+    polygon: "polygon",
+    polyline: "polyline",
+    radial_gradient: "radialGradient",
+    rect: "rect",
+    script: "script",
+    set: "set",
+    stop: "stop",
+    style: "style",
+    svg: "svg",
+    switch: "switch",
+    symbol: "symbol",
+    text: "text",
+    text_path: "textPath",
+    title: "title",
+    tspan: "tspan",
+    r#use: "use",
+    view: "view"
+}
+
+namespaced_elements! {
+    /// Elements in the MathML namespace
+    /// These are created with the MathML namespace and encoded with two bytes
+    MathMlElement,
+    MathMl,
+    251,
+    annotation: "annotation",
+    annotation_xml: "annotation-xml",
+    math: "math",
+    merror: "merror",
+    mfrac: "mfrac",
+    mi: "mi",
+    mmultiscripts: "mmultiscripts",
+    mn: "mn",
+    mo: "mo",
+    mover: "mover",
+    mpadded: "mpadded",
+    mphantom: "mphantom",
+    mprescripts: "mprescripts",
+    mroot: "mroot",
+    mrow: "mrow",
+    ms: "ms",
+    mspace: "mspace",
+    msqrt: "msqrt",
+    mstyle: "mstyle",
+    msub: "msub",
+    msubsup: "msubsup",
+    msup: "msup",
+    mtable: "mtable",
+    mtd: "mtd",
+    mtext: "mtext",
+    mtr: "mtr",
+    munder: "munder",
+    munderover: "munderover",
+    semantics: "semantics"
+}
+
+namespaced_attributes! {
+    /// Attributes used by SVG elements
+    /// These are encoded with a single byte. Attributes shared with html elements like class, width and height are in [`Attribute`](crate::Attribute)
+    SvgAttribute,
+    Svg,
+    Attribute::COUNT,
+    attribute_name: "attributeName",
+    begin: "begin",
+    clip_path: "clip-path",
+    clip_path_units: "clipPathUnits",
+    clip_rule: "clip-rule",
+    cx: "cx",
+    cy: "cy",
+    d: "d",
+    dominant_baseline: "dominant-baseline",
+    dur: "dur",
+    dx: "dx",
+    dy: "dy",
+    fill: "fill",
+    fill_opacity: "fill-opacity",
+    fill_rule: "fill-rule",
+    filter: "filter",
+    filter_units: "filterUnits",
+    fr: "fr",
+    from: "from",
+    fx: "fx",
+    fy: "fy",
+    gradient_transform: "gradientTransform",
+    gradient_units: "gradientUnits",
+    r#in: "in",
+    in2: "in2",
+    length_adjust: "lengthAdjust",
+    marker_end: "marker-end",
+    marker_height: "markerHeight",
+    marker_mid: "marker-mid",
+    marker_start: "marker-start",
+    marker_units: "markerUnits",
+    marker_width: "markerWidth",
+    mask: "mask",
+    mask_content_units: "maskContentUnits",
+    mask_units: "maskUnits",
+    opacity: "opacity",
+    orient: "orient",
+    path_length: "pathLength",
+    pattern_content_units: "patternContentUnits",
+    pattern_transform: "patternTransform",
+    pattern_units: "patternUnits",
+    points: "points",
+    preserve_aspect_ratio: "preserveAspectRatio",
+    r: "r",
+    ref_x: "refX",
+    ref_y: "refY",
+    repeat_count: "repeatCount",
+    result: "result",
+    rx: "rx",
+    ry: "ry",
+    spread_method: "spreadMethod",
+    start_offset: "startOffset",
+    std_deviation: "stdDeviation",
+    stop_color: "stop-color",
+    stop_opacity: "stop-opacity",
+    stroke: "stroke",
+    stroke_dasharray: "stroke-dasharray",
+    stroke_dashoffset: "stroke-dashoffset",
+    stroke_linecap: "stroke-linecap",
+    stroke_linejoin: "stroke-linejoin",
+    stroke_miterlimit: "stroke-miterlimit",
+    stroke_opacity: "stroke-opacity",
+    stroke_width: "stroke-width",
+    text_anchor: "text-anchor",
+    text_length: "textLength",
+    to: "to",
+    transform: "transform",
+    values: "values",
+    vector_effect: "vector-effect",
+    view_box: "viewBox",
+    x: "x",
+    x1: "x1",
+    x2: "x2",
+    y: "y",
+    y1: "y1",
+    y2: "y2"
+}
+
+namespaced_attributes! {
+    /// Attributes used by MathML elements
+    /// These are encoded with a single byte. Attributes shared with html elements like class and dir are in [`Attribute`](crate::Attribute)
+    MathMlAttribute,
+    MathMl,
+    SvgAttribute::y2 as isize + 1,
+    accent: "accent",
+    accentunder: "accentunder",
+    columnspan: "columnspan",
+    display: "display",
+    displaystyle: "displaystyle",
+    fence: "fence",
+    largeop: "largeop",
+    linethickness: "linethickness",
+    lspace: "lspace",
+    mathbackground: "mathbackground",
+    mathcolor: "mathcolor",
+    mathsize: "mathsize",
+    mathvariant: "mathvariant",
+    maxsize: "maxsize",
+    minsize: "minsize",
+    movablelimits: "movablelimits",
+    rspace: "rspace",
+    scriptlevel: "scriptlevel",
+    separator: "separator",
+    stretchy: "stretchy",
+    symmetric: "symmetric",
+    voffset: "voffset"
+}
+
+// 253, 254 and 255 are used as discriminants for attributes that are not encoded as a single byte
+const _: () = assert!((MathMlAttribute::voffset as isize) < 253);
@@ -0,0 +1,301 @@
+//! Recordings of the batches a channel ran and the nodes it was given, which can be replayed to reconstruct the same DOM.
+//!
+//! Only the batches and the names of the nodes set with an id are recorded. A replay recreates every set node as an empty node with the recorded name, so the replayed DOM only matches if the nodes that were set were empty when the recording started, or if the replay is given nodes with the same content. Nodes that were released by setting them to nothing are not recorded.
+//!
+//! A recording is stored in a single binary log:
+//! - the magic bytes `SHRC` followed by the version of the log format as a u8, then the [`FORMAT_VERSION`] of the batches as a u8.
+//! - then one event after another until the end of the log. Every event starts with a u8 tag:
+//!   - `0`: a batch, encoded as a u32 length and the bytes of the message, then a u32 length and the bytes of the strings
+//!   - `1`: a node that was set with an id, encoded as the u32 id, then a u16 length and the utf-8 node name of the node
+//!
+//! All numbers are little endian.
+
+use std::io::{self, Read, Write};
+
+use crate::{batch::FORMAT_VERSION, interpreter::NativeInterpreter, InterpretError, NodeId};
+
+const MAGIC: &[u8; 4] = b"SHRC";
+const VERSION: u8 = 1;
+
+const BATCH: u8 = 0;
+const SET_NODE: u8 = 1;
+
+/// One thing a channel did, in the order it happened
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RecordedEvent {
+    /// A batch that was run
+    Batch {
+        /// The encoded operations
+        msg: Vec<u8>,
+        /// The encoded strings
+        str: Vec<u8>,
+    },
+    /// A node that was stored with an id from outside of a batch
+    SetNode {
+        /// The id the node was stored with
+        id: NodeId,
+        /// The `nodeName` of the node, like `BODY` or `#document-fragment`
+        name: String,
+    },
+}
+
+/// A log of the batches a channel ran and the nodes that were set on it
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Recording {
+    /// The events in the order they happened
+    pub events: Vec<RecordedEvent>,
+}
+
+/// Something a [`Recording`] can be replayed into
+pub trait Replay {
+    /// The error returned when a batch fails
+    type Error;
+
+    /// Store a node with an id. The name is the `nodeName` of the node that was originally set.
+    fn replay_set_node(&mut self, id: NodeId, name: &str) -> Result<(), Self::Error>;
+
+    /// Run a batch
+    fn replay_batch(&mut self, msg: &[u8], str: &[u8]) -> Result<(), Self::Error>;
+}
+
+impl Recording {
+    /// Record a batch that was run
+    pub fn record_batch(&mut self, msg: &[u8], str: &[u8]) {
+        self.events.push(RecordedEvent::Batch {
+            msg: msg.to_vec(),
+            str: str.to_vec(),
+        });
+    }
+
+    /// Record a node that was set with an id
+    pub fn record_set_node(&mut self, id: NodeId, name: &str) {
+        self.events.push(RecordedEvent::SetNode {
+            id,
+            name: name.to_string(),
+        });
+    }
+
+    /// Replay every event in order, stopping at the first error
+    pub fn replay<R: Replay>(&self, target: &mut R) -> Result<(), R::Error> {
+        for event in &self.events {
+            match event {
+                RecordedEvent::Batch { msg, str } => target.replay_batch(msg, str)?,
+                RecordedEvent::SetNode { id, name } => target.replay_set_node(*id, name)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the recording in the log format
+    pub fn write_to(&self, mut to: impl Write) -> io::Result<()> {
+        to.write_all(MAGIC)?;
+        to.write_all(&[VERSION, FORMAT_VERSION])?;
+        for event in &self.events {
+            match event {
+                RecordedEvent::Batch { msg, str } => {
+                    if msg.len() > u32::MAX as usize || str.len() > u32::MAX as usize {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidInput,
+                            "the batch is longer than u32::MAX bytes",
+                        ));
+                    }
+                    to.write_all(&[BATCH])?;
+                    to.write_all(&(msg.len() as u32).to_le_bytes())?;
+                    to.write_all(msg)?;
+                    to.write_all(&(str.len() as u32).to_le_bytes())?;
+                    to.write_all(str)?;
+                }
+                RecordedEvent::SetNode { id, name } => {
+                    if name.len() > u16::MAX as usize {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidInput,
+                            "the node name is longer than u16::MAX bytes",
+                        ));
+                    }
+                    to.write_all(&[SET_NODE])?;
+                    to.write_all(&id.0.to_le_bytes())?;
+                    to.write_all(&(name.len() as u16).to_le_bytes())?;
+                    to.write_all(name.as_bytes())?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Read a recording in the log format
+    pub fn read_from(mut from: impl Read) -> io::Result<Self> {
+        let mut header = [0; 6];
+        from.read_exact(&mut header)?;
+        if &header[..4] != MAGIC {
+            return Err(invalid_data("the log is not a sledgehammer recording"));
+        }
+        if header[4] != VERSION {
+            return Err(invalid_data(format!(
+                "the recording has version {}, but only version {VERSION} is supported",
+                header[4]
+            )));
+        }
+        let format_version = header[5];
+        if format_version != FORMAT_VERSION {
+            return Err(invalid_data(format!(
+                "the batches in the recording were encoded in version {format_version} of the sledgehammer format, but version {FORMAT_VERSION} is supported"
+            )));
+        }
+        let mut events = Vec::new();
+        loop {
+            let mut tag = [0];
+            if from.read(&mut tag)? == 0 {
+                break;
+            }
+            events.push(match tag[0] {
+                BATCH => {
+                    let len = read_u32(&mut from)?;
+                    let msg = read_bytes(&mut from, len as usize)?;
+                    let len = read_u32(&mut from)?;
+                    let str = read_bytes(&mut from, len as usize)?;
+                    RecordedEvent::Batch { msg, str }
+                }
+                SET_NODE => {
+                    let id = NodeId(read_u32(&mut from)?);
+                    let mut len = [0; 2];
+                    from.read_exact(&mut len)?;
+                    let name = read_bytes(&mut from, u16::from_le_bytes(len) as usize)?;
+                    let name = String::from_utf8(name).map_err(invalid_data)?;
+                    RecordedEvent::SetNode { id, name }
+                }
+                tag => return Err(invalid_data(format!("{tag} is not an event"))),
+            });
+        }
+        Ok(Self { events })
+    }
+}
+
+pub(crate) fn invalid_data(
+    error: impl Into<Box<dyn std::error::Error + Send + Sync>>,
+) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+pub(crate) fn read_u32(from: &mut impl Read) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    from.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+/// Read `len` bytes. The length comes from the data, so the buffer only grows as the bytes arrive.
+pub(crate) fn read_bytes(from: &mut impl Read, len: usize) -> io::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    from.by_ref().take(len as u64).read_to_end(&mut bytes)?;
+    if bytes.len() != len {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            format!(
+                "expected {len} bytes, but the data ended after {}",
+                bytes.len()
+            ),
+        ));
+    }
+    Ok(bytes)
+}
+
+/// Replaying into a [`NativeInterpreter`] creates a new node in its dom for every node that was set.
+impl Replay for NativeInterpreter {
+    type Error = InterpretError;
+
+    fn replay_set_node(&mut self, id: NodeId, name: &str) -> Result<(), Self::Error> {
+        let dom = self.dom_mut();
+        let node = match name {
+            "#document" => dom.create_document(),
+            "#document-fragment" => dom.create_fragment(),
+            "#text" => dom.create_text(""),
+            "#comment" => dom.create_comment(""),
+            // html elements report their name in upper case
+            name if !name.bytes().any(|b| b.is_ascii_lowercase()) => {
+                dom.create_element(&name.to_ascii_lowercase(), None)
+            }
+            name => dom.create_element(name, None),
+        };
+        self.set_node(id, Some(node));
+        Ok(())
+    }
+
+    fn replay_batch(&mut self, msg: &[u8], str: &[u8]) -> Result<(), Self::Error> {
+        self.run(msg, str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{batch::Batch, Attribute, Element, MaybeId};
+
+    #[test]
+    fn replay_written_recording() {
+        let mut recording = Recording::default();
+        recording.record_set_node(NodeId(0), "BODY");
+        let mut batch = Batch::default();
+        batch.create_element(Element::div, Some(NodeId(1)));
+        batch.set_attribute(Attribute::class, "a", MaybeId::LastNode);
+        batch.append_child(MaybeId::Node(NodeId(0)), MaybeId::LastNode);
+        batch.set_inner_html(
+            "<b>bold</b> text",
+            MaybeId::Node(NodeId(1)),
+            Some(NodeId(2)),
+        );
+        batch.set_text("BOLD", MaybeId::Node(NodeId(2)));
+        let batch = batch.finalize();
+        recording.record_batch(&batch.msg, &batch.str);
+
+        let mut log = Vec::new();
+        recording.write_to(&mut log).unwrap();
+        let read = Recording::read_from(&log[..]).unwrap();
+        assert_eq!(read, recording);
+
+        let mut interpreter = NativeInterpreter::default();
+        read.replay(&mut interpreter).unwrap();
+        let body = interpreter.get_node(NodeId(0)).unwrap();
+        assert_eq!(
+            interpreter.dom().to_html(body),
+            r#"<body><div class="a"><b>BOLD</b> text</div></body>"#
+        );
+    }
+
+    #[test]
+    fn long_node_names_are_rejected() {
+        let mut recording = Recording::default();
+        recording.record_set_node(NodeId(0), &"a".repeat(u16::MAX as usize + 1));
+        let error = recording.write_to(Vec::new()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn truncated_recordings_are_rejected() {
+        // a batch that claims to be 4GB long is only read as far as the log goes
+        let mut log = Vec::new();
+        Recording::default().write_to(&mut log).unwrap();
+        log.push(BATCH);
+        log.extend_from_slice(&u32::MAX.to_le_bytes());
+        let error = Recording::read_from(&log[..]).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn checks_format_version() {
+        let error = Recording::read_from(&b"SHRC\x02\x01"[..]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "the recording has version 2, but only version 1 is supported"
+        );
+
+        let error =
+            Recording::read_from(&[b'S', b'H', b'R', b'C', VERSION, FORMAT_VERSION + 1][..])
+                .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "the batches in the recording were encoded in version {} of the sledgehammer format, but version {FORMAT_VERSION} is supported",
+                FORMAT_VERSION + 1
+            )
+        );
+    }
+}
@@ -0,0 +1,480 @@
+//! The specification of the binary format, shared by the encoder and the javascript interpreters.
+//!
+//! The operations and their operands are declared once in `batch.rs`, see [`Op::operands`] and [`Op::js`], and the element, attribute and style tables are the enums of this crate. The encoder methods of [`Batch`](crate::batch::Batch) and the decoder of the [`NativeInterpreter`](crate::NativeInterpreter) are generated from the list by macros, and this module generates the javascript side from it, so the sides cannot drift:
+//! - the region between [`GENERATED_START`] and [`GENERATED_END`] in `interpreter.js` is replaced with [`js_definitions`], which has the tables and the functions that decode and run every operation
+//! - `interpreter_opt.js` is generated from `interpreter.js` with [`optimize_js`]
+//! - `interpreter_debug.js` is generated from `interpreter.js` and the checks in `debug_checks.js` with [`debug_js`]
+//!
+//! The generated files are checked in. A test of the `sledgehammer` crate fails if they are out of date, and regenerates them when it runs with `SLEDGEHAMMER_BLESS=1`. `CONTRIBUTING.md` in the repository describes the workflow:
+//! ```sh
+//! SLEDGEHAMMER_BLESS=1 cargo test -p sledgehammer --test interpreters
+//! ```
+
+use std::fmt::Write;
+
+use crate::{
+    batch::{Op, Operand, EXTENDED_OP, FORMAT_VERSION},
+    dom::{MATHML_NAMESPACE, SVG_NAMESPACE},
+    interpreter::POSITIONS,
+    Attribute, Element, MathMlAttribute, MathMlElement, Style, SvgAttribute, SvgElement,
+};
+
+/// The line the generated region of an interpreter starts with
+pub const GENERATED_START: &str =
+    "// @generated start: generated from the specification in sledgehammer-encoder, do not edit by hand";
+
+/// The line the generated region of an interpreter ends with
+pub const GENERATED_END: &str = "// @generated end";
+
+/// The properties of the interpreter that are shortened in `interpreter_opt.js`
+const SHORT_PROPERTIES: &[(&str, &str)] = &[
+    ("lastNode", "l"),
+    ("nodes", "n"),
+    ("view", "v"),
+    ("u8BufPos", "u"),
+    ("strings", "s"),
+    ("strPos", "o"),
+    ("strBytes", "sb"),
+    ("strByte", "ob"),
+    ("templates", "t"),
+    ("styleSheets", "ss"),
+    ("shadowRoots", "sr"),
+    ("stack", "p"),
+    ("last_start_pos", "lp"),
+    ("last_str_start", "ls"),
+    ("metadata_ptr", "m"),
+    ("ptr_ptr", "pt"),
+    ("str_ptr_ptr", "sp"),
+    ("str_len_ptr", "sl"),
+    ("decoder", "d"),
+    ("idSize", "i"),
+];
+
+/// The name of the javascript constant of an operation, like `CREATE_ELEMENT`
+pub fn js_op_name(op: Op) -> String {
+    snake_case(op.name()).to_uppercase()
+}
+
+fn snake_case(name: &str) -> String {
+    let mut out = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_ascii_uppercase() && i > 0 {
+            out.push('_');
+        }
+        out.push(c.to_ascii_lowercase());
+    }
+    out
+}
+
+/// The constants and tables every interpreter reads: the format version, the operation codes and the element, attribute and style names. The debug interpreter also gets the names of the operations for its trace.
+pub fn js_definitions(debug: bool) -> String {
+    js_definitions_inner(debug, true)
+}
+
+fn js_definitions_inner(debug: bool, op_constants: bool) -> String {
+    let mut out = String::new();
+    out.push_str(GENERATED_START);
+    out.push('\n');
+    let _ = writeln!(out, "const FORMAT_VERSION = {FORMAT_VERSION};");
+    if op_constants {
+        out.push_str("\n// the codes of the operations\n");
+        for &op in Op::ALL {
+            let _ = writeln!(out, "const {} = {};", js_op_name(op), op as u8);
+        }
+        out.push_str("// operations with this code or larger are encoded as this code followed by a byte with the code of the operation\n");
+        let _ = writeln!(out, "const EXTENDED_OP = {EXTENDED_OP};");
+    }
+
+    if debug {
+        let max = Op::ALL.iter().map(|&op| op as u8).max().unwrap_or(0);
+        let names = (0..=max).map(|code| match Op::from_code(code) {
+            Some(op) => snake_case(op.name()),
+            None if code == EXTENDED_OP => "extended".to_string(),
+            None => format!("unknown({code})"),
+        });
+        js_table(&mut out, "opNames", [(None, names.collect())]);
+    }
+
+    js_table(&mut out, "positions", [(None, to_strings(&POSITIONS))]);
+    js_table(
+        &mut out,
+        "styles",
+        [(
+            None,
+            Style::ALL.iter().map(|s| s.as_str().to_string()).collect(),
+        )],
+    );
+    js_table(
+        &mut out,
+        "els",
+        [(
+            None,
+            Element::ALL
+                .iter()
+                .map(|e| e.as_str().to_string())
+                .collect(),
+        )],
+    );
+    let _ = writeln!(out, "\nconst svgNs = \"{SVG_NAMESPACE}\";");
+    let _ = writeln!(out, "const mathMlNs = \"{MATHML_NAMESPACE}\";");
+    js_table(
+        &mut out,
+        "svgEls",
+        [(
+            None,
+            SvgElement::ALL
+                .iter()
+                .map(|e| e.as_str().to_string())
+                .collect(),
+        )],
+    );
+    js_table(
+        &mut out,
+        "mathMlEls",
+        [(
+            None,
+            MathMlElement::ALL
+                .iter()
+                .map(|e| e.as_str().to_string())
+                .collect(),
+        )],
+    );
+    // namespaced attributes are encoded after the html attributes in the same table
+    js_table(
+        &mut out,
+        "attrs",
+        [
+            (
+                None,
+                Attribute::ALL
+                    .iter()
+                    .map(|a| a.as_str().to_string())
+                    .collect(),
+            ),
+            (
+                Some("svg attributes"),
+                SvgAttribute::ALL
+                    .iter()
+                    .map(|a| a.as_str().to_string())
+                    .collect(),
+            ),
+            (
+                Some("MathML attributes"),
+                MathMlAttribute::ALL
+                    .iter()
+                    .map(|a| a.as_str().to_string())
+                    .collect(),
+            ),
+        ],
+    );
+    js_ops(&mut out);
+    out.push_str(GENERATED_END);
+    out.push('\n');
+    out
+}
+
+/// Write the functions that decode the operands of every operation and run it
+fn js_ops(out: &mut String) {
+    out.push_str("\n// runs the operation in the lowest byte of op, and returns true if it was the stop operation\n");
+    out.push_str("function exOp() {\n    switch (op & 0x1F) {\n");
+    for op in Op::ALL.iter().filter(|&&op| (op as u8) < EXTENDED_OP) {
+        js_case(out, *op);
+    }
+    out.push_str("        case EXTENDED_OP:\n            return exExtOp();\n    }\n}\n");
+
+    out.push_str("\n// runs the operations encoded as EXTENDED_OP followed by a byte with their code. The bools are still encoded in op\n");
+    out.push_str("function exExtOp() {\n    switch (inptr.view.getUint8(inptr.u8BufPos++)) {\n");
+    for op in Op::ALL.iter().filter(|&&op| (op as u8) > EXTENDED_OP) {
+        js_case(out, *op);
+    }
+    out.push_str("    }\n}\n");
+}
+
+/// Write the case of an operation: the operands are decoded into constants with their names, and then the javascript of the operation runs
+fn js_case(out: &mut String, op: Op) {
+    let mut lines = Vec::new();
+    let mut bools = 0;
+    for &(name, operand) in op.operands() {
+        js_operand(&mut lines, name, operand, bools);
+        bools += operand.bools();
+    }
+    lines.extend(op.js().iter().map(|line| line.to_string()));
+    if !op
+        .js()
+        .last()
+        .is_some_and(|line| line.starts_with("return"))
+    {
+        lines.push("break;".to_string());
+    }
+    let _ = write!(out, "        case {}:", js_op_name(op));
+    if op.operands().is_empty() {
+        out.push('\n');
+        for line in lines {
+            let _ = writeln!(out, "            {line}");
+        }
+    } else {
+        // the operands are block scoped constants
+        out.push_str(" {\n");
+        for line in lines {
+            let _ = writeln!(out, "            {line}");
+        }
+        out.push_str("        }\n");
+    }
+}
+
+/// Decode an operand into constants, using the bools of the operation starting at `bool`
+fn js_operand(lines: &mut Vec<String>, name: &str, operand: Operand, bool: u8) {
+    let mask = |i: u8| format!("0x{:X}", 0x20u16 << (bool + i));
+    let str = "inptr.str(inptr.decodeU16())";
+    let value = match operand {
+        Operand::Id | Operand::U32 | Operand::TemplateId | Operand::StyleSheetId => {
+            "inptr.decodeU32()".to_string()
+        }
+        Operand::U16 => "inptr.decodeU16()".to_string(),
+        Operand::Position => "inptr.view.getUint8(inptr.u8BufPos++)".to_string(),
+        Operand::Store => format!("op & {} ? inptr.decodeU32() : -1", mask(0)),
+        Operand::Node => format!("op & {} ? inptr.node() : inptr.lastNode", mask(0)),
+        Operand::Nodes => format!(
+            "op & {} ? inptr.decodeNodes() : [op & {} ? inptr.node() : inptr.lastNode]",
+            mask(0),
+            mask(1)
+        ),
+        Operand::Flag => format!("op & {}", mask(0)),
+        Operand::Mode => format!("op & {} ? \"closed\" : \"open\"", mask(0)),
+        Operand::Str => str.to_string(),
+        Operand::LongStr => "inptr.str(inptr.decodeU32())".to_string(),
+        Operand::Element => "inptr.createElement()".to_string(),
+        Operand::FullElement => "inptr.createFullElement()".to_string(),
+        Operand::Roots => "inptr.decodeRoots()".to_string(),
+        Operand::Style => "inptr.styleName()".to_string(),
+        Operand::Styles => "inptr.decodeStyles()".to_string(),
+        // the name and the namespace are decoded into two constants
+        Operand::Attribute => {
+            lines.push(format!(
+                "const {name} = op & {} ? {str} : attrs[inptr.view.getUint8(inptr.u8BufPos++)];",
+                mask(0)
+            ));
+            format!("op & {} ? {str} : null", mask(1))
+        }
+        // the start and the end of the range are decoded into two constants
+        Operand::IdRange => {
+            lines.push(format!("const {name} = inptr.decodeU32();"));
+            format!("op & {} ? inptr.decodeU32() : {name} + 1", mask(0))
+        }
+    };
+    let name = match operand {
+        Operand::Attribute => format!("{name}Ns"),
+        Operand::IdRange => format!("{name}End"),
+        _ => name.to_string(),
+    };
+    lines.push(format!("const {name} = {value};"));
+}
+
+fn to_strings(strs: &[&str]) -> Vec<String> {
+    strs.iter().map(|s| s.to_string()).collect()
+}
+
+/// Write a `const name = [...]` table of strings with a comment before every section
+fn js_table<const N: usize>(
+    out: &mut String,
+    name: &str,
+    sections: [(Option<&str>, Vec<String>); N],
+) {
+    let _ = writeln!(out, "\nconst {name} = [");
+    for (comment, values) in sections {
+        if let Some(comment) = comment {
+            let _ = writeln!(out, "    // {comment}");
+        }
+        for value in values {
+            let _ = writeln!(out, "    \"{value}\",");
+        }
+    }
+    out.push_str("];\n");
+}
+
+/// Replace the generated region of an interpreter with new definitions. Returns `None` if the interpreter has no generated region.
+pub fn replace_generated(interpreter: &str, definitions: &str) -> Option<String> {
+    let start = interpreter.find(GENERATED_START)?;
+    let end = start + interpreter[start..].find(GENERATED_END)?;
+    let end = interpreter[end..]
+        .find('\n')
+        .map_or(interpreter.len(), |i| end + i + 1);
+    let mut out = String::with_capacity(interpreter.len());
+    out.push_str(&interpreter[..start]);
+    out.push_str(definitions);
+    out.push_str(&interpreter[end..]);
+    Some(out)
+}
+
+/// Generate `interpreter_debug.js` from `interpreter.js` and `debug_checks.js`. The interpreter class is renamed to `Interpreter`, and the checks extend it with the exported `JsInterpreter`, so the operations are shared with the other interpreters.
+///
+/// Returns `None` if the interpreter has no generated region or no exported `JsInterpreter` class.
+pub fn debug_js(interpreter: &str, checks: &str) -> Option<String> {
+    let interpreter = replace_generated(interpreter, &js_definitions(true))?;
+    let class = "export class JsInterpreter {";
+    interpreter.find(class)?;
+    let mut out = String::from(
+        "// @generated from interpreter.js and debug_checks.js by sledgehammer-encoder, do not edit by hand\n",
+    );
+    out.push_str(&interpreter.replacen(class, "class Interpreter {", 1));
+    out.push('\n');
+    out.push_str(checks);
+    Some(out)
+}
+
+/// Generate `interpreter_opt.js` from `interpreter.js`: the operation codes are inlined, the properties of the interpreter are shortened, and comments and indentation are removed.
+///
+/// Returns `None` if the interpreter has no generated region.
+pub fn optimize_js(interpreter: &str) -> Option<String> {
+    let interpreter = replace_generated(interpreter, &js_definitions_inner(false, false))?;
+    let op_codes: Vec<(String, u8)> = Op::ALL
+        .iter()
+        .map(|&op| (js_op_name(op), op as u8))
+        .chain(std::iter::once(("EXTENDED_OP".to_string(), EXTENDED_OP)))
+        .collect();
+
+    let mut out = String::from(
+        "// @generated from interpreter.js by sledgehammer-encoder, do not edit by hand\n",
+    );
+    let mut line = String::new();
+    // the last two tokens that were not whitespace or comments, to find property accesses like `this.nodes`
+    let mut before_dot: Option<&str> = None;
+    let mut last: Option<&str> = None;
+    for token in Tokens::new(&interpreter) {
+        match token {
+            Token::Comment => {}
+            Token::Newline => {
+                let trimmed = line.trim();
+                if !trimmed.is_empty() {
+                    out.push_str(trimmed);
+                    out.push('\n');
+                }
+                line.clear();
+            }
+            Token::Other(text) => {
+                let mut replaced = text;
+                if is_ident(text) {
+                    if last == Some(".") {
+                        if matches!(before_dot, Some("this" | "inptr")) {
+                            if let Some((_, short)) =
+                                SHORT_PROPERTIES.iter().find(|(long, _)| *long == text)
+                            {
+                                replaced = short;
+                            }
+                        }
+                    } else if let Some((_, code)) = op_codes.iter().find(|(name, _)| name == text) {
+                        let _ = write!(line, "{code}");
+                        before_dot = last;
+                        last = Some(text);
+                        continue;
+                    }
+                }
+                line.push_str(replaced);
+                if !text.trim().is_empty() {
+                    before_dot = last;
+                    last = Some(text);
+                }
+            }
+        }
+    }
+    let trimmed = line.trim();
+    if !trimmed.is_empty() {
+        out.push_str(trimmed);
+        out.push('\n');
+    }
+    Some(out)
+}
+
+fn is_ident(text: &str) -> bool {
+    text.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_' || c == '$')
+}
+
+enum Token<'a> {
+    Comment,
+    Newline,
+    Other(&'a str),
+}
+
+/// Splits javascript into identifiers, strings, comments, newlines and single characters. Regular expression literals are not supported, and the interpreters do not use them.
+struct Tokens<'a> {
+    src: &'a str,
+    pos: usize,
+}
+
+impl<'a> Tokens<'a> {
+    fn new(src: &'a str) -> Self {
+        Self { src, pos: 0 }
+    }
+
+    /// The end of a string that starts at `start` with the quote `quote`
+    fn string_end(&self, start: usize, quote: u8) -> usize {
+        let bytes = self.src.as_bytes();
+        let mut i = start + 1;
+        while i < bytes.len() && bytes[i] != quote {
+            i += if bytes[i] == b'\\' { 2 } else { 1 };
+        }
+        (i + 1).min(bytes.len())
+    }
+}
+
+impl<'a> Iterator for Tokens<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        let rest = &self.src[self.pos..];
+        let first = *rest.as_bytes().first()?;
+        let start = self.pos;
+        let end = match first {
+            b'\n' => {
+                self.pos += 1;
+                return Some(Token::Newline);
+            }
+            b'"' | b'\'' | b'`' => self.string_end(start, first),
+            b'/' if rest.starts_with("//") => {
+                self.pos += rest.find('\n').unwrap_or(rest.len());
+                return Some(Token::Comment);
+            }
+            b'/' if rest.starts_with("/*") => {
+                self.pos += rest.find("*/").map_or(rest.len(), |i| i + 2);
+                return Some(Token::Comment);
+            }
+            c if c.is_ascii_alphanumeric() || c == b'_' || c == b'$' => {
+                start
+                    + rest
+                        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '$'))
+                        .unwrap_or(rest.len())
+            }
+            _ => start + rest.chars().next().map_or(1, char::len_utf8),
+        };
+        self.pos = end;
+        Some(Token::Other(&self.src[start..end]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn optimize_shortens_properties_and_inlines_ops() {
+        let src = format!(
+            "let nodes;\n{}\nfunction f() {{\n    // a comment\n    switch (op) {{\n        case CREATE_ELEMENT:\n            inptr.nodes.push(\"this.nodes\", ...nodes, inptr.view.buffer);\n    }}\n}}\n",
+            js_definitions(false)
+        );
+        let optimized = optimize_js(&src).unwrap();
+        assert!(optimized.contains(&format!(
+            "case {}:\ninptr.n.push(\"this.nodes\", ...nodes, inptr.v.buffer);",
+            Op::CreateElement as u8
+        )));
+        assert!(!optimized.contains("CREATE_ELEMENT"));
+        assert!(!optimized.contains("a comment"));
+    }
+
+    #[test]
+    fn operands_fit_in_the_op_bools() {
+        for op in Op::ALL {
+            let bools: u8 = op.operands().iter().map(|(_, kind)| kind.bools()).sum();
+            assert!(bools <= 3, "{} uses {bools} bools", op.name());
+        }
+    }
+}
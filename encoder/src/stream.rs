@@ -0,0 +1,375 @@
+//! A framed byte stream for applying batches to a dom in another process, like a desktop webview or a browser connected to a server.
+//!
+//! A stream is sent in one direction, from the process that encodes batches to the process with the dom:
+//! - the magic bytes `SHST` followed by the version of the stream format as a u8, then the [`FORMAT_VERSION`] of the batches as a u8.
+//! - then one frame after another until the stream is closed. Every frame starts with a u8 tag:
+//!   - `0`: a batch, encoded as a u32 length and the bytes of the message, then a u32 length and the bytes of the strings
+//!   - `1`: a node to store with an id, encoded as the u32 id, then a u16 length and the utf-8 css selector of the node. An empty selector is the document.
+//!   - `2`: an event the receiver should report back, encoded as the u32 id of the node to listen on, then a u16 length and the utf-8 name of the event. How events are reported is up to the receiver, see the liveview crate for an example.
+//!
+//! All numbers are little endian. The receiver in `web/stream_receiver.js` applies frames to the javascript interpreter, and [`FrameReader::receive`] applies them to a [`NativeInterpreter`].
+
+use std::io::{self, Read, Write};
+
+use crate::{
+    batch::{PreparedBatch, FORMAT_VERSION},
+    dom::{Dom, DomId},
+    interpreter::NativeInterpreter,
+    record::{invalid_data, read_bytes, read_u32},
+    InterpretError, NodeId,
+};
+
+/// The magic bytes a stream starts with
+pub const MAGIC: &[u8; 4] = b"SHST";
+/// The version of the stream format, which follows the magic bytes
+pub const VERSION: u8 = 1;
+
+const BATCH: u8 = 0;
+const SET_NODE: u8 = 1;
+const LISTEN: u8 = 2;
+
+/// One message in a stream
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Frame {
+    /// A batch to run
+    Batch {
+        /// The encoded operations
+        msg: Vec<u8>,
+        /// The encoded strings
+        str: Vec<u8>,
+    },
+    /// A node the receiver should store with an id
+    SetNode {
+        /// The id to store the node with
+        id: NodeId,
+        /// The css selector the receiver finds the node with. An empty selector is the document.
+        selector: String,
+    },
+    /// An event the receiver should report back
+    Listen {
+        /// The id of the node to listen on
+        id: NodeId,
+        /// The name of the event, like `click`
+        event: String,
+    },
+}
+
+/// Writes frames to a stream. The header is written when the writer is created.
+///
+/// Frames are written directly to the inner writer, so wrap it in a [`BufWriter`](std::io::BufWriter) if it does not buffer.
+#[derive(Debug)]
+pub struct FrameWriter<W: Write> {
+    inner: W,
+}
+
+impl<W: Write> FrameWriter<W> {
+    /// Start a stream by writing the header
+    pub fn new(mut inner: W) -> io::Result<Self> {
+        inner.write_all(MAGIC)?;
+        inner.write_all(&[VERSION, FORMAT_VERSION])?;
+        Ok(Self { inner })
+    }
+
+    /// Write a batch that has been finalized. Batches encoded in another version of the format are rejected.
+    pub fn write_batch(&mut self, batch: impl PreparedBatch) -> io::Result<()> {
+        batch
+            .check_version()
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+        self.write_batch_parts(batch.msg(), batch.str())
+    }
+
+    /// Write a batch from its message and strings. The message must end with [`Op::Stop`](crate::Op::Stop).
+    pub fn write_batch_parts(&mut self, msg: &[u8], str: &[u8]) -> io::Result<()> {
+        if msg.len() > u32::MAX as usize || str.len() > u32::MAX as usize {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the batch is longer than u32::MAX bytes",
+            ));
+        }
+        self.inner.write_all(&[BATCH])?;
+        self.inner.write_all(&(msg.len() as u32).to_le_bytes())?;
+        self.inner.write_all(msg)?;
+        self.inner.write_all(&(str.len() as u32).to_le_bytes())?;
+        self.inner.write_all(str)
+    }
+
+    /// Tell the receiver to store the node the selector matches with an id. An empty selector is the document.
+    pub fn write_set_node(&mut self, id: NodeId, selector: &str) -> io::Result<()> {
+        self.write_id_and_str(SET_NODE, id, selector)
+    }
+
+    /// Tell the receiver to report an event on the node stored with an id
+    pub fn write_listen(&mut self, id: NodeId, event: &str) -> io::Result<()> {
+        self.write_id_and_str(LISTEN, id, event)
+    }
+
+    fn write_id_and_str(&mut self, tag: u8, id: NodeId, str: &str) -> io::Result<()> {
+        if str.len() > u16::MAX as usize {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the string is longer than u16::MAX bytes",
+            ));
+        }
+        self.inner.write_all(&[tag])?;
+        self.inner.write_all(&id.0.to_le_bytes())?;
+        self.inner.write_all(&(str.len() as u16).to_le_bytes())?;
+        self.inner.write_all(str.as_bytes())
+    }
+
+    /// Write any frame
+    pub fn write_frame(&mut self, frame: &Frame) -> io::Result<()> {
+        match frame {
+            Frame::Batch { msg, str } => self.write_batch_parts(msg, str),
+            Frame::SetNode { id, selector } => self.write_set_node(*id, selector),
+            Frame::Listen { id, event } => self.write_listen(*id, event),
+        }
+    }
+
+    /// Flush the inner writer
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+
+    /// The inner writer
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// The inner writer. Writing to it directly corrupts the stream.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Take the inner writer out of the frame writer
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+/// Reads frames from a stream. The header is checked when the reader is created.
+#[derive(Debug)]
+pub struct FrameReader<R: Read> {
+    inner: R,
+}
+
+impl<R: Read> FrameReader<R> {
+    /// Start reading a stream by checking the header
+    pub fn new(mut inner: R) -> io::Result<Self> {
+        let mut header = [0; 6];
+        inner.read_exact(&mut header)?;
+        if &header[..4] != MAGIC {
+            return Err(invalid_data("the stream is not a sledgehammer stream"));
+        }
+        if header[4] != VERSION {
+            return Err(invalid_data(format!(
+                "the stream has version {}, but only version {VERSION} is supported",
+                header[4]
+            )));
+        }
+        let format_version = header[5];
+        if format_version != FORMAT_VERSION {
+            return Err(invalid_data(format!(
+                "the batches in the stream were encoded in version {format_version} of the sledgehammer format, but version {FORMAT_VERSION} is supported"
+            )));
+        }
+        Ok(Self { inner })
+    }
+
+    /// Read the next frame, or `None` if the stream was closed between frames
+    pub fn read_frame(&mut self) -> io::Result<Option<Frame>> {
+        let mut tag = [0];
+        loop {
+            match self.inner.read(&mut tag) {
+                Ok(0) => return Ok(None),
+                Ok(_) => break,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+        let from = &mut self.inner;
+        Ok(Some(match tag[0] {
+            BATCH => {
+                let len = read_u32(from)?;
+                let msg = read_bytes(from, len as usize)?;
+                let len = read_u32(from)?;
+                let str = read_bytes(from, len as usize)?;
+                Frame::Batch { msg, str }
+            }
+            SET_NODE => {
+                let id = NodeId(read_u32(from)?);
+                let selector = read_str(from)?;
+                Frame::SetNode { id, selector }
+            }
+            LISTEN => {
+                let id = NodeId(read_u32(from)?);
+                let event = read_str(from)?;
+                Frame::Listen { id, event }
+            }
+            tag => return Err(invalid_data(format!("{tag} is not a frame"))),
+        }))
+    }
+
+    /// Read the next frame and apply it to an interpreter. Nodes are found with `select`, which is given the dom of the interpreter and the selector of the node.
+    /// The in-memory dom has no events, so [`Frame::Listen`] is ignored.
+    ///
+    /// Returns `false` if the stream was closed between frames.
+    pub fn receive(
+        &mut self,
+        interpreter: &mut NativeInterpreter,
+        mut select: impl FnMut(&Dom, &str) -> Option<DomId>,
+    ) -> Result<bool, ReceiveError> {
+        match self.read_frame()? {
+            Some(Frame::Batch { msg, str }) => interpreter.run(&msg, &str)?,
+            Some(Frame::SetNode { id, selector }) => {
+                let node = select(interpreter.dom(), &selector);
+                interpreter.set_node(id, node);
+            }
+            Some(Frame::Listen { .. }) => {}
+            None => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    /// The inner reader
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Take the inner reader out of the frame reader
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> Iterator for FrameReader<R> {
+    type Item = io::Result<Frame>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_frame().transpose()
+    }
+}
+
+/// Read a u16 length and that many bytes of utf-8
+fn read_str(from: &mut impl Read) -> io::Result<String> {
+    let mut len = [0; 2];
+    from.read_exact(&mut len)?;
+    let str = read_bytes(from, u16::from_le_bytes(len) as usize)?;
+    String::from_utf8(str).map_err(invalid_data)
+}
+
+/// An error while receiving a frame with [`FrameReader::receive`]
+#[derive(Debug)]
+pub enum ReceiveError {
+    /// The stream could not be read or is not valid
+    Io(io::Error),
+    /// A batch failed
+    Interpret(InterpretError),
+}
+
+impl std::fmt::Display for ReceiveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReceiveError::Io(err) => write!(f, "failed to read the stream: {err}"),
+            ReceiveError::Interpret(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for ReceiveError {}
+
+impl From<io::Error> for ReceiveError {
+    fn from(err: io::Error) -> Self {
+        ReceiveError::Io(err)
+    }
+}
+
+impl From<InterpretError> for ReceiveError {
+    fn from(err: InterpretError) -> Self {
+        ReceiveError::Interpret(err)
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::os::unix::net::UnixStream;
+
+    use super::*;
+    use crate::{batch::Batch, Attribute, Element, MaybeId};
+
+    #[test]
+    fn socket_pair() {
+        let (sender, receiver) = UnixStream::pair().unwrap();
+        let sending = std::thread::spawn(move || {
+            let mut writer = FrameWriter::new(sender).unwrap();
+            writer.write_set_node(NodeId(0), "body").unwrap();
+            let mut batch = Batch::default();
+            batch.create_element(Element::ul, Some(NodeId(1)));
+            batch.set_attribute(Attribute::class, "items", MaybeId::LastNode);
+            batch.append_child(MaybeId::Node(NodeId(0)), MaybeId::LastNode);
+            writer.write_batch(batch.finalize()).unwrap();
+            for i in 0..3 {
+                let mut batch = Batch::default();
+                batch.create_element(Element::li, None);
+                batch.set_text(format!("item {i}").as_str(), MaybeId::LastNode);
+                batch.append_child(MaybeId::Node(NodeId(1)), MaybeId::LastNode);
+                writer.write_batch(batch.finalize()).unwrap();
+            }
+            let mut batch = Batch::default();
+            batch.remove(MaybeId::Node(NodeId(5)));
+            writer.write_batch(batch.finalize()).unwrap();
+            writer.flush().unwrap();
+        });
+
+        let (mut interpreter, body) = NativeInterpreter::with_body();
+        let mut reader = FrameReader::new(receiver).unwrap();
+        let mut select = |_: &Dom, selector: &str| (selector == "body").then_some(body);
+        for _ in 0..5 {
+            assert!(reader.receive(&mut interpreter, &mut select).unwrap());
+        }
+        let error = reader.receive(&mut interpreter, &mut select).unwrap_err();
+        assert!(matches!(error, ReceiveError::Interpret(error) if error.node == Some(NodeId(5))));
+        assert!(!reader.receive(&mut interpreter, &mut select).unwrap());
+        sending.join().unwrap();
+
+        assert_eq!(
+            interpreter.dom().to_html(body),
+            r#"<body><ul class="items"><li>item 0</li><li>item 1</li><li>item 2</li></ul></body>"#
+        );
+    }
+
+    #[test]
+    fn truncated_frames_are_rejected() {
+        let mut stream = Vec::new();
+        FrameWriter::new(&mut stream).unwrap();
+        // a batch that claims to be 4GB long is only read as far as the stream goes
+        stream.push(BATCH);
+        stream.extend_from_slice(&u32::MAX.to_le_bytes());
+        stream.extend_from_slice(&[0; 16]);
+        let error = FrameReader::new(&stream[..])
+            .unwrap()
+            .read_frame()
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn rejects_other_streams() {
+        let error = FrameReader::new(&b"SHRC\x01\x01"[..]).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        let error = FrameReader::new(&b"SHST\x02\x01"[..]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "the stream has version 2, but only version 1 is supported"
+        );
+        let mut header = MAGIC.to_vec();
+        header.extend_from_slice(&[VERSION, FORMAT_VERSION + 1]);
+        let error = FrameReader::new(&header[..]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "the batches in the stream were encoded in version {} of the sledgehammer format, but version {FORMAT_VERSION} is supported",
+                FORMAT_VERSION + 1
+            )
+        );
+    }
+}
@@ -0,0 +1,249 @@
+#![allow(non_camel_case_types)]
+
+use self::sealed::Sealed;
+use crate::batch::Batch;
+
+mod sealed {
+    use crate::{style::AnyStyle, CustomProperty, Style};
+
+    pub trait Sealed {}
+
+    impl Sealed for Style {}
+    impl Sealed for &str {}
+    impl Sealed for CustomProperty<'_> {}
+    impl Sealed for AnyStyle<'_> {}
+}
+
+/// A css custom property like `--main-color`. The name is stored without the leading `--`.
+#[derive(Clone, Copy)]
+pub struct CustomProperty<'a>(pub &'a str);
+
+#[derive(Clone, Copy)]
+pub enum AnyStyle<'a> {
+    Style(Style),
+    Str(&'a str),
+    CustomProperty(CustomProperty<'a>),
+}
+
+/// Anything that can be turned into a style property
+pub trait IntoStyle<'a>: Sealed + Into<AnyStyle<'a>> {
+    /// If the style property can be encoded in a single byte
+    const SINGLE_BYTE: bool = false;
+
+    /// The number of bytes the style property takes to encode with a u8 discriminant
+    fn size_with_u8_discriminant(&self) -> usize;
+
+    /// Encode the style property into the message channel with a u8 discriminant
+    ///
+    /// # Safety
+    ///
+    /// This is only safe if the batch is preallocated to the correct size
+    unsafe fn encode_u8_discriminant_prealloc(self, v: &mut Batch);
+}
+
+impl<'a> IntoStyle<'a> for Style {
+    const SINGLE_BYTE: bool = true;
+
+    #[inline(always)]
+    fn size_with_u8_discriminant(&self) -> usize {
+        1
+    }
+
+    #[inline(always)]
+    unsafe fn encode_u8_discriminant_prealloc(self, v: &mut Batch) {
+        v.encode_u8_prealloc(self as u8)
+    }
+}
+
+impl<'a> From<Style> for AnyStyle<'a> {
+    fn from(s: Style) -> Self {
+        AnyStyle::Style(s)
+    }
+}
+
+impl<'a> IntoStyle<'a> for &'a str {
+    fn size_with_u8_discriminant(&self) -> usize {
+        1 + 2
+    }
+
+    unsafe fn encode_u8_discriminant_prealloc(self, v: &mut Batch) {
+        v.encode_u8_prealloc(255);
+        v.encode_str_prealloc(self);
+    }
+}
+
+impl<'a> From<&'a str> for AnyStyle<'a> {
+    fn from(s: &'a str) -> Self {
+        AnyStyle::Str(s)
+    }
+}
+
+impl<'a> IntoStyle<'a> for CustomProperty<'a> {
+    fn size_with_u8_discriminant(&self) -> usize {
+        1 + 2
+    }
+
+    unsafe fn encode_u8_discriminant_prealloc(self, v: &mut Batch) {
+        v.encode_u8_prealloc(254);
+        v.encode_str_prealloc(self.0);
+    }
+}
+
+impl<'a> From<CustomProperty<'a>> for AnyStyle<'a> {
+    fn from(s: CustomProperty<'a>) -> Self {
+        AnyStyle::CustomProperty(s)
+    }
+}
+
+impl<'a> IntoStyle<'a> for AnyStyle<'a> {
+    fn size_with_u8_discriminant(&self) -> usize {
+        match self {
+            AnyStyle::Style(s) => s.size_with_u8_discriminant(),
+            AnyStyle::Str(s) => s.size_with_u8_discriminant(),
+            AnyStyle::CustomProperty(s) => s.size_with_u8_discriminant(),
+        }
+    }
+
+    unsafe fn encode_u8_discriminant_prealloc(self, v: &mut Batch) {
+        match self {
+            AnyStyle::Style(s) => s.encode_u8_discriminant_prealloc(v),
+            AnyStyle::Str(s) => s.encode_u8_discriminant_prealloc(v),
+            AnyStyle::CustomProperty(s) => s.encode_u8_discriminant_prealloc(v),
+        }
+    }
+}
+
+macro_rules! styles {
+    ($($i: ident: $name: literal),*) => {
+        /// Common css properties
+        /// These are the style properties that can be encoded with a single byte so they are more efficient (but less flexable) than a &str property
+        #[derive(Copy, Clone)]
+        pub enum Style {
+            $(
+                $i
+            ),*
+        }
+
+        impl Style {
+            #[allow(unused)]
+            pub(crate) const COUNT: usize = [$($name),*].len();
+
+            /// Every style property in the order they are encoded
+            pub(crate) const ALL: [Self; Self::COUNT] = [$(Self::$i),*];
+
+            /// The name of the css property
+            pub const fn as_str(&self) -> &'static str {
+                match self {
+                    $(
+                        Self::$i => $name,
+                    )*
+                }
+            }
+        }
+
+        pub struct NotStyleError;
+
+        impl std::str::FromStr for Style {
+            type Err = NotStyleError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                Ok(match s {
+                    $(
+                        $name => Self::$i,
+                    )*
+                    _ => return Err(NotStyleError)
+                })
+            }
+        }
+    };
+}
+
+styles! {
+    align_content: "align-content",
+    align_items: "align-items",
+    align_self: "align-self",
+    background: "background",
+    background_color: "background-color",
+    background_image: "background-image",
+    background_position: "background-position",
+    background_repeat: "background-repeat",
+    background_size: "background-size",
+    border: "border",
+    border_bottom: "border-bottom",
+    border_color: "border-color",
+    border_left: "border-left",
+    border_radius: "border-radius",
+    border_right: "border-right",
+    border_style: "border-style",
+    border_top: "border-top",
+    border_width: "border-width",
+    bottom: "bottom",
+    box_shadow: "box-shadow",
+    box_sizing: "box-sizing",
+    color: "color",
+    cursor: "cursor",
+    display: "display",
+    flex: "flex",
+    flex_basis: "flex-basis",
+    flex_direction: "flex-direction",
+    flex_grow: "flex-grow",
+    flex_shrink: "flex-shrink",
+    flex_wrap: "flex-wrap",
+    float: "float",
+    font: "font",
+    font_family: "font-family",
+    font_size: "font-size",
+    font_style: "font-style",
+    font_weight: "font-weight",
+    gap: "gap",
+    grid_area: "grid-area",
+    grid_column: "grid-column",
+    grid_row: "grid-row",
+    grid_template_areas: "grid-template-areas",
+    grid_template_columns: "grid-template-columns",
+    grid_template_rows: "grid-template-rows",
+    height: "height",
+    justify_content: "justify-content",
+    justify_items: "justify-items",
+    justify_self: "justify-self",
+    left: "left",
+    letter_spacing: "letter-spacing",
+    line_height: "line-height",
+    margin: "margin",
+    margin_bottom: "margin-bottom",
+    margin_left: "margin-left",
+    margin_right: "margin-right",
+    margin_top: "margin-top",
+    max_height: "max-height",
+    max_width: "max-width",
+    min_height: "min-height",
+    min_width: "min-width",
+    opacity: "opacity",
+    outline: "outline",
+    overflow: "overflow",
+    overflow_x: "overflow-x",
+    overflow_y: "overflow-y",
+    padding: "padding",
+    padding_bottom: "padding-bottom",
+    padding_left: "padding-left",
+    padding_right: "padding-right",
+    padding_top: "padding-top",
+    pointer_events: "pointer-events",
+    position: "position",
+    right: "right",
+    text_align: "text-align",
+    text_decoration: "text-decoration",
+    text_overflow: "text-overflow",
+    text_transform: "text-transform",
+    top: "top",
+    transform: "transform",
+    transform_origin: "transform-origin",
+    transition: "transition",
+    user_select: "user-select",
+    vertical_align: "vertical-align",
+    visibility: "visibility",
+    white_space: "white-space",
+    width: "width",
+    word_break: "word-break",
+    z_index: "z-index"
+}
@@ -0,0 +1,395 @@
+//! Drive a browser dom from a server. The server keeps a [`Session`] for every client, sends its batches over a WebSocket, and runs Rust handlers for the events the client sends back.
+//!
+//! - The server sends the client a [stream](sledgehammer_encoder::stream) of batches. Listeners are added with [`Frame::Listen`](sledgehammer_encoder::Frame::Listen) frames.
+//! - The client sends events back as [`ClientMessage`]s, and the server answers every event with the batches its handler queued.
+//! - A client that reconnects with the token of its session gets a new stream that clears the root element and rebuilds the whole dom, so node ids and listeners survive the reconnect.
+//! - A session without a connection ends after the [session timeout](LiveView::set_session_timeout), so the sessions of clients that left do not pile up.
+//!
+//! The server is not tied to a WebSocket library. Implement [`Socket`] for the WebSocket type of your framework and call [`LiveView::serve`] for every connection. The client is `web/liveview_client.js`.
+//!
+//! # Example
+//!
+//! ```
+//! use sledgehammer_encoder::{Element, MaybeId, NodeId};
+//! use sledgehammer_liveview::{LiveView, Session};
+//!
+//! let liveview = LiveView::new("#main", |session| {
+//!     let batch = session.batch();
+//!     batch.create_element(Element::button, Some(NodeId(1)));
+//!     batch.set_text("0", MaybeId::LastNode);
+//!     batch.append_child(MaybeId::Node(Session::ROOT), MaybeId::LastNode);
+//!     let mut count = 0;
+//!     session.on(NodeId(1), "click", move |session, _| {
+//!         count += 1;
+//!         let count = count.to_string();
+//!         session.batch().set_text(count.as_str(), MaybeId::Node(NodeId(1)));
+//!     });
+//! });
+//! // for every WebSocket connection, on its own thread:
+//! // liveview.serve(socket)?;
+//! ```
+
+pub mod protocol;
+pub mod session;
+pub mod socket;
+
+use std::{
+    collections::{hash_map::RandomState, HashMap},
+    hash::{BuildHasher, Hasher},
+    io, mem,
+    sync::{Arc, Mutex, MutexGuard, PoisonError, TryLockError},
+    time::{Duration, Instant},
+};
+
+use sledgehammer_encoder::FrameWriter;
+
+pub use protocol::{ClientMessage, Event};
+pub use session::Session;
+pub use socket::{loopback, LoopbackSocket, Socket};
+
+/// A server that keeps a [`Session`] for every client
+pub struct LiveView {
+    root: String,
+    init: Box<dyn Fn(&mut Session) + Send + Sync>,
+    sessions: Mutex<HashMap<String, Arc<Mutex<Session>>>>,
+    session_timeout: Duration,
+}
+
+impl LiveView {
+    /// How long a session is kept after its connection closed, unless [`LiveView::set_session_timeout`] changes it
+    pub const DEFAULT_SESSION_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+    /// Create a server. The css selector `root` finds the element every session is rendered into, and `init` builds the dom of a new session.
+    pub fn new(root: &str, init: impl Fn(&mut Session) + Send + Sync + 'static) -> Self {
+        Self {
+            root: root.to_string(),
+            init: Box::new(init),
+            sessions: Mutex::new(HashMap::new()),
+            session_timeout: Self::DEFAULT_SESSION_TIMEOUT,
+        }
+    }
+
+    /// Set how long a session is kept after its connection closed. A client that reconnects later starts a new session.
+    ///
+    /// Sessions that timed out are removed when the next client connects.
+    pub fn set_session_timeout(&mut self, timeout: Duration) {
+        self.session_timeout = timeout;
+    }
+
+    /// Serve one connection until the client closes it or reconnects with another connection.
+    ///
+    /// The client picks the session with its first message. A new session is started if the client has no token, or if its session has ended or timed out.
+    pub fn serve(&self, mut socket: impl Socket) -> io::Result<()> {
+        let Some(hello) = socket.recv()? else {
+            return Ok(());
+        };
+        let ClientMessage::Hello { session: token } = ClientMessage::decode(&hello)? else {
+            return Err(invalid_data("the first message must be a hello"));
+        };
+        let (token, session) = self.resume_or_start(token);
+        let connection = {
+            let mut session = lock(&session);
+            session.connection += 1;
+            session.disconnected = None;
+            session.connection
+        };
+        let result = self.serve_session(socket, &session, connection, token);
+        let mut session = lock(&session);
+        if session.connection == connection {
+            // the timeout starts when the last connection of the session closes
+            session.disconnected = Some(Instant::now());
+        }
+        result
+    }
+
+    fn serve_session(
+        &self,
+        mut socket: impl Socket,
+        session: &Mutex<Session>,
+        connection: u64,
+        token: String,
+    ) -> io::Result<()> {
+        let mut writer = FrameWriter::new(Vec::new())?;
+        lock(session).write_resync(&mut writer, &self.root)?;
+        socket.send(token.into_bytes())?;
+        socket.send(mem::take(writer.get_mut()))?;
+
+        while let Some(msg) = socket.recv()? {
+            let ClientMessage::Event(event) = ClientMessage::decode(&msg)? else {
+                return Err(invalid_data("only the first message can be a hello"));
+            };
+            {
+                let mut session = lock(session);
+                if session.connection != connection {
+                    // the client reconnected, so this connection is stale
+                    break;
+                }
+                session.dispatch(&event);
+                session.write_unsent(&mut writer)?;
+            }
+            let update = mem::take(writer.get_mut());
+            if !update.is_empty() {
+                socket.send(update)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Get a session by its token. Operations queued in the session are sent after the next event of the client.
+    pub fn session(&self, token: &str) -> Option<Arc<Mutex<Session>>> {
+        lock(&self.sessions).get(token).cloned()
+    }
+
+    /// End a session so it cannot be resumed. Returns `false` if there was no session with the token.
+    pub fn end_session(&self, token: &str) -> bool {
+        lock(&self.sessions).remove(token).is_some()
+    }
+
+    fn resume_or_start(&self, token: String) -> (String, Arc<Mutex<Session>>) {
+        let mut sessions = lock(&self.sessions);
+        sessions.retain(|_, session| !self.timed_out(session));
+        if let Some(session) = sessions.get(&token) {
+            return (token, session.clone());
+        }
+        let mut session = Session::new();
+        (self.init)(&mut session);
+        let session = Arc::new(Mutex::new(session));
+        let token = new_token();
+        sessions.insert(token.clone(), session.clone());
+        (token, session)
+    }
+
+    fn timed_out(&self, session: &Mutex<Session>) -> bool {
+        let disconnected = match session.try_lock() {
+            Ok(session) => session.disconnected,
+            Err(TryLockError::Poisoned(session)) => session.into_inner().disconnected,
+            // a locked session is handling an event, and a handler may be waiting for the sessions
+            Err(TryLockError::WouldBlock) => return false,
+        };
+        disconnected.is_some_and(|since| since.elapsed() >= self.session_timeout)
+    }
+}
+
+impl std::fmt::Debug for LiveView {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LiveView")
+            .field("root", &self.root)
+            .field("sessions", &lock(&self.sessions).len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// A random token for a new session. The randomness comes from the keys of [`RandomState`], which makes tokens hard to guess but not cryptographically secure, so authenticate users separately.
+fn new_token() -> String {
+    let a = RandomState::new().build_hasher().finish();
+    let b = RandomState::new().build_hasher().finish();
+    format!("{a:016x}{b:016x}")
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn invalid_data(err: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
+
+#[cfg(test)]
+mod tests {
+    use sledgehammer_encoder::{
+        dom::DomId, stream, Element, Frame, FrameReader, MaybeId, NativeInterpreter, NodeId,
+        FORMAT_VERSION,
+    };
+
+    use super::*;
+
+    fn counter() -> LiveView {
+        LiveView::new("#main", |session| {
+            let batch = session.batch();
+            batch.create_element(Element::button, Some(NodeId(1)));
+            batch.set_text("0", MaybeId::LastNode);
+            batch.append_child(MaybeId::Node(Session::ROOT), MaybeId::LastNode);
+            let mut count = 0;
+            session.on(NodeId(1), "click", move |session, _| {
+                count += 1;
+                let count = count.to_string();
+                session
+                    .batch()
+                    .set_text(count.as_str(), MaybeId::Node(NodeId(1)));
+            });
+        })
+    }
+
+    /// A client that applies the stream to an in-memory dom like `client.js` does in the browser
+    struct Client {
+        socket: LoopbackSocket,
+        interpreter: NativeInterpreter,
+        root: DomId,
+        listeners: Vec<(NodeId, String)>,
+    }
+
+    impl Client {
+        /// Pick a session on a new connection and apply the resync, returning the token of the session
+        fn hello(&mut self, socket: LoopbackSocket, token: &str) -> String {
+            self.socket = socket;
+            self.listeners.clear();
+            let hello = ClientMessage::Hello {
+                session: token.to_string(),
+            };
+            self.socket.send(hello.encode()).unwrap();
+            let token = String::from_utf8(self.socket.recv().unwrap().unwrap()).unwrap();
+            self.apply(true);
+            token
+        }
+
+        /// Close the connection
+        fn disconnect(&mut self) {
+            self.socket = loopback().0;
+        }
+
+        fn apply(&mut self, header: bool) {
+            let msg = self.socket.recv().unwrap().unwrap();
+            let mut stream = Vec::new();
+            if !header {
+                stream.extend_from_slice(stream::MAGIC);
+                stream.extend_from_slice(&[stream::VERSION, FORMAT_VERSION]);
+            }
+            stream.extend_from_slice(&msg);
+            for frame in FrameReader::new(&stream[..]).unwrap() {
+                match frame.unwrap() {
+                    Frame::Batch { msg, str } => self.interpreter.run(&msg, &str).unwrap(),
+                    Frame::SetNode { id, selector } => {
+                        assert_eq!(selector, "#main");
+                        self.interpreter.set_node(id, Some(self.root));
+                    }
+                    Frame::Listen { id, event } => self.listeners.push((id, event)),
+                }
+            }
+        }
+
+        fn click(&mut self, node: NodeId) {
+            let event = Event {
+                node,
+                name: "click".to_string(),
+                value: String::new(),
+            };
+            self.socket
+                .send(ClientMessage::Event(event).encode())
+                .unwrap();
+            self.apply(false);
+        }
+
+        fn html(&self) -> String {
+            self.interpreter.dom().to_html(self.root)
+        }
+    }
+
+    #[test]
+    fn reconnect_resyncs() {
+        let liveview = counter();
+        let mut interpreter = NativeInterpreter::default();
+        let root = interpreter.dom_mut().create_element("div", None);
+        let mut client = Client {
+            socket: loopback().0,
+            interpreter,
+            root,
+            listeners: Vec::new(),
+        };
+
+        std::thread::scope(|scope| {
+            let (server, socket) = loopback();
+            let serving = scope.spawn(|| liveview.serve(server));
+            let token = client.hello(socket, "");
+            assert_eq!(client.html(), "<div><button>0</button></div>");
+            assert_eq!(client.listeners, [(NodeId(1), "click".to_string())]);
+            client.click(NodeId(1));
+            client.click(NodeId(1));
+            assert_eq!(client.html(), "<div><button>2</button></div>");
+            client.disconnect();
+            serving.join().unwrap().unwrap();
+
+            // the resync clears the stale dom and rebuilds it with the same ids and listeners
+            let (server, socket) = loopback();
+            let serving = scope.spawn(|| liveview.serve(server));
+            assert_eq!(client.hello(socket, &token), token);
+            assert_eq!(client.html(), "<div><button>2</button></div>");
+            assert_eq!(client.listeners, [(NodeId(1), "click".to_string())]);
+            client.click(NodeId(1));
+            assert_eq!(client.html(), "<div><button>3</button></div>");
+            client.disconnect();
+            serving.join().unwrap().unwrap();
+
+            // a session that ended starts over
+            assert!(liveview.end_session(&token));
+            let (server, socket) = loopback();
+            let serving = scope.spawn(|| liveview.serve(server));
+            assert_ne!(client.hello(socket, &token), token);
+            assert_eq!(client.html(), "<div><button>0</button></div>");
+            client.disconnect();
+            serving.join().unwrap().unwrap();
+        });
+    }
+
+    #[test]
+    fn closed_sessions_time_out() {
+        let mut liveview = counter();
+        liveview.set_session_timeout(Duration::ZERO);
+        let client = || {
+            let mut interpreter = NativeInterpreter::default();
+            let root = interpreter.dom_mut().create_element("div", None);
+            Client {
+                socket: loopback().0,
+                interpreter,
+                root,
+                listeners: Vec::new(),
+            }
+        };
+        let (mut first, mut second) = (client(), client());
+
+        std::thread::scope(|scope| {
+            let (server, socket) = loopback();
+            let first_serving = scope.spawn(|| liveview.serve(server));
+            let first_token = first.hello(socket, "");
+
+            // a connected session does not time out
+            let (server, socket) = loopback();
+            let second_serving = scope.spawn(|| liveview.serve(server));
+            let second_token = second.hello(socket, "");
+            assert!(liveview.session(&first_token).is_some());
+
+            // the session of a dropped connection is removed when the next client connects
+            first.disconnect();
+            first_serving.join().unwrap().unwrap();
+            let (server, socket) = loopback();
+            let serving = scope.spawn(|| liveview.serve(server));
+            assert_ne!(first.hello(socket, &first_token), first_token);
+            assert!(liveview.session(&first_token).is_none());
+            assert!(liveview.session(&second_token).is_some());
+            assert_eq!(lock(&liveview.sessions).len(), 2);
+
+            first.disconnect();
+            second.disconnect();
+            serving.join().unwrap().unwrap();
+            second_serving.join().unwrap().unwrap();
+        });
+    }
+
+    #[test]
+    fn client_messages_round_trip() {
+        let messages = [
+            ClientMessage::Hello {
+                session: "abc".to_string(),
+            },
+            ClientMessage::Event(Event {
+                node: NodeId(7),
+                name: "input".to_string(),
+                value: "héllo".to_string(),
+            }),
+        ];
+        for message in messages {
+            assert_eq!(ClientMessage::decode(&message.encode()).unwrap(), message);
+        }
+        let error = ClientMessage::decode(&[1, 0, 0]).unwrap_err();
+        assert_eq!(error.to_string(), "the message ended early");
+    }
+}
@@ -0,0 +1,108 @@
+//! Messages from the client to the server
+//!
+//! The server sends a [stream](sledgehammer_encoder::stream) to the client, and the client sends these messages back. Every message is one socket message starting with a u8 tag:
+//! - `0`: the first message of a connection, encoded as a u16 length and the utf-8 token of the session to resume. An empty token starts a new session.
+//! - `1`: an event, encoded as the u32 id of the node the event was listened for on, then a u16 length and the utf-8 name of the event, then a u16 length and the utf-8 value of the event target.
+//!
+//! All numbers are little endian. The server answers the first message with the token of the session as utf-8, then a new stream that rebuilds the dom of the session.
+
+use std::io;
+
+use sledgehammer_encoder::NodeId;
+
+const HELLO: u8 = 0;
+const EVENT: u8 = 1;
+
+/// A message from the client
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ClientMessage {
+    /// The first message of a connection
+    Hello {
+        /// The token of the session to resume, or empty to start a new session
+        session: String,
+    },
+    /// An event one of the session's listeners received
+    Event(Event),
+}
+
+/// An event the client received
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Event {
+    /// The node the listener was added to
+    pub node: NodeId,
+    /// The name of the event, like `click`
+    pub name: String,
+    /// The value of the event target, like the text in an input. Empty if the target has no value.
+    pub value: String,
+}
+
+impl ClientMessage {
+    /// Encode the message
+    pub fn encode(&self) -> Vec<u8> {
+        let mut msg = Vec::new();
+        match self {
+            ClientMessage::Hello { session } => {
+                msg.push(HELLO);
+                write_str(&mut msg, session);
+            }
+            ClientMessage::Event(event) => {
+                msg.push(EVENT);
+                msg.extend_from_slice(&event.node.0.to_le_bytes());
+                write_str(&mut msg, &event.name);
+                write_str(&mut msg, &event.value);
+            }
+        }
+        msg
+    }
+
+    /// Decode a message
+    pub fn decode(msg: &[u8]) -> io::Result<Self> {
+        let (&tag, mut rest) = msg
+            .split_first()
+            .ok_or_else(|| invalid_data("the message is empty"))?;
+        let message = match tag {
+            HELLO => ClientMessage::Hello {
+                session: read_str(&mut rest)?,
+            },
+            EVENT => {
+                let id = take(&mut rest, 4)?;
+                ClientMessage::Event(Event {
+                    node: NodeId(u32::from_le_bytes(id.try_into().unwrap())),
+                    name: read_str(&mut rest)?,
+                    value: read_str(&mut rest)?,
+                })
+            }
+            tag => return Err(invalid_data(format!("{tag} is not a client message"))),
+        };
+        if !rest.is_empty() {
+            return Err(invalid_data("the message has trailing bytes"));
+        }
+        Ok(message)
+    }
+}
+
+fn write_str(msg: &mut Vec<u8>, str: &str) {
+    let len = u16::try_from(str.len()).expect("the string is longer than u16::MAX bytes");
+    msg.extend_from_slice(&len.to_le_bytes());
+    msg.extend_from_slice(str.as_bytes());
+}
+
+fn read_str(from: &mut &[u8]) -> io::Result<String> {
+    let len = take(from, 2)?;
+    let len = u16::from_le_bytes([len[0], len[1]]) as usize;
+    let str = take(from, len)?;
+    String::from_utf8(str.to_vec()).map_err(invalid_data)
+}
+
+fn take<'a>(from: &mut &'a [u8], len: usize) -> io::Result<&'a [u8]> {
+    if from.len() < len {
+        return Err(invalid_data("the message ended early"));
+    }
+    let (taken, rest) = from.split_at(len);
+    *from = rest;
+    Ok(taken)
+}
+
+fn invalid_data(err: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
@@ -0,0 +1,159 @@
+//! The state the server keeps for each client
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt, io, mem,
+    time::Instant,
+};
+
+use sledgehammer_encoder::{batch::Batch, Frame, FrameWriter, MaybeId, NodeId};
+
+use crate::Event;
+
+type Handler = Box<dyn FnMut(&mut Session, &Event) + Send>;
+
+/// The dom of one client. Operations are queued in [`Session::batch`] and sent when the session is flushed.
+///
+/// The root element the [`LiveView`](crate::LiveView) was created with is stored as [`Session::ROOT`]. Every frame sent to the client is kept, so a client that reconnects gets the same dom and the same node ids.
+///
+/// The history is never compacted, so the memory of a session and the time a reconnect takes grow with every batch it sends. A view that updates for a long time should reset itself now and then by starting a new session, for example by reloading the page.
+pub struct Session {
+    batch: Batch,
+    /// Every frame since the session started
+    history: Vec<Frame>,
+    /// The number of frames in the history the current connection has been sent
+    sent: usize,
+    handlers: HashMap<(NodeId, String), Handler>,
+    /// The events the client listens for. A handler is removed from `handlers` while it runs, so this is tracked separately.
+    listening: HashSet<(NodeId, String)>,
+    /// Incremented for every connection, so an old connection stops when the client reconnects
+    pub(crate) connection: u64,
+    /// When the last connection closed, or `None` while the client is connected
+    pub(crate) disconnected: Option<Instant>,
+}
+
+impl Session {
+    /// The id the root element is stored with
+    pub const ROOT: NodeId = NodeId(0);
+
+    pub(crate) fn new() -> Self {
+        Self {
+            batch: Batch::default(),
+            history: Vec::new(),
+            sent: 0,
+            handlers: HashMap::new(),
+            listening: HashSet::new(),
+            connection: 0,
+            disconnected: None,
+        }
+    }
+
+    /// The batch operations are queued in
+    pub fn batch(&mut self) -> &mut Batch {
+        &mut self.batch
+    }
+
+    /// Finish the queued batch so it is sent to the client. The server flushes after every event, so this is only needed to order operations around [`Session::on`].
+    pub fn flush(&mut self) {
+        if self.batch.msg.is_empty() {
+            return;
+        }
+        let batch = mem::take(&mut self.batch).finalize();
+        self.history.push(Frame::Batch {
+            msg: batch.msg,
+            str: batch.str,
+        });
+    }
+
+    /// Call a handler every time the client receives an event on the node stored with an id. The node must exist when the session is flushed, so the queued batch is flushed first.
+    ///
+    /// A handler lasts as long as the session, and adding another handler for the same node and event replaces it.
+    pub fn on(
+        &mut self,
+        node: NodeId,
+        event: &str,
+        handler: impl FnMut(&mut Session, &Event) + Send + 'static,
+    ) {
+        self.flush();
+        let key = (node, event.to_string());
+        self.handlers.insert(key.clone(), Box::new(handler));
+        if self.listening.insert(key) {
+            self.history.push(Frame::Listen {
+                id: node,
+                event: event.to_string(),
+            });
+        }
+    }
+
+    /// Run the handler of an event and flush the operations it queued. Events without a handler are ignored.
+    pub fn dispatch(&mut self, event: &Event) {
+        let key = (event.node, event.name.clone());
+        if let Some(mut handler) = self.handlers.remove(&key) {
+            handler(self, event);
+            // the handler may have replaced itself
+            self.handlers.entry(key).or_insert(handler);
+        }
+        self.flush();
+    }
+
+    /// Write everything the client needs to rebuild the dom: the root, an operation to clear it, and every frame since the session started
+    pub(crate) fn write_resync(
+        &mut self,
+        writer: &mut FrameWriter<Vec<u8>>,
+        root: &str,
+    ) -> io::Result<()> {
+        self.flush();
+        writer.write_set_node(Self::ROOT, root)?;
+        let mut clear = Batch::default();
+        clear.set_text("", MaybeId::Node(Self::ROOT));
+        writer.write_batch(clear.finalize())?;
+        self.sent = 0;
+        self.write_unsent(writer)
+    }
+
+    /// Write the frames the current connection has not been sent
+    pub(crate) fn write_unsent(&mut self, writer: &mut FrameWriter<Vec<u8>>) -> io::Result<()> {
+        for frame in &self.history[self.sent..] {
+            writer.write_frame(frame)?;
+        }
+        self.sent = self.history.len();
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Session {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Session")
+            .field("frames", &self.history.len())
+            .field("handlers", &self.handlers.len())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn handlers_can_replace_themselves() {
+        fn replace(session: &mut Session, _: &Event) {
+            session.on(NodeId(1), "click", replace);
+        }
+        let mut session = Session::new();
+        session.on(NodeId(1), "click", replace);
+        let click = Event {
+            node: NodeId(1),
+            name: "click".to_string(),
+            value: String::new(),
+        };
+        session.dispatch(&click);
+        session.dispatch(&click);
+        let listens = session
+            .history
+            .iter()
+            .filter(|frame| matches!(frame, Frame::Listen { .. }))
+            .count();
+        assert_eq!(listens, 1);
+        assert_eq!(session.handlers.len(), 1);
+    }
+}
@@ -0,0 +1,72 @@
+//! The connection between the server and a client
+
+use std::{
+    io,
+    sync::mpsc::{channel, Receiver, Sender},
+};
+
+/// A message based connection to a client, like a WebSocket. Every message is binary.
+///
+/// Implement this for the WebSocket type of your server framework. A socket is used from the thread that calls [`LiveView::serve`](crate::LiveView::serve), so blocking is fine.
+pub trait Socket {
+    /// Send one message to the client
+    fn send(&mut self, msg: Vec<u8>) -> io::Result<()>;
+
+    /// Wait for the next message from the client, or `None` if the connection was closed
+    fn recv(&mut self) -> io::Result<Option<Vec<u8>>>;
+}
+
+impl<S: Socket + ?Sized> Socket for &mut S {
+    fn send(&mut self, msg: Vec<u8>) -> io::Result<()> {
+        (**self).send(msg)
+    }
+
+    fn recv(&mut self) -> io::Result<Option<Vec<u8>>> {
+        (**self).recv()
+    }
+}
+
+impl<S: Socket + ?Sized> Socket for Box<S> {
+    fn send(&mut self, msg: Vec<u8>) -> io::Result<()> {
+        (**self).send(msg)
+    }
+
+    fn recv(&mut self) -> io::Result<Option<Vec<u8>>> {
+        (**self).recv()
+    }
+}
+
+/// One end of an in-memory connection created with [`loopback`]. Useful for testing a [`LiveView`](crate::LiveView) without a network.
+#[derive(Debug)]
+pub struct LoopbackSocket {
+    sender: Sender<Vec<u8>>,
+    receiver: Receiver<Vec<u8>>,
+}
+
+/// Create two connected sockets. A message sent on one end is received on the other. Dropping one end closes the connection.
+pub fn loopback() -> (LoopbackSocket, LoopbackSocket) {
+    let (a_sender, b_receiver) = channel();
+    let (b_sender, a_receiver) = channel();
+    (
+        LoopbackSocket {
+            sender: a_sender,
+            receiver: a_receiver,
+        },
+        LoopbackSocket {
+            sender: b_sender,
+            receiver: b_receiver,
+        },
+    )
+}
+
+impl Socket for LoopbackSocket {
+    fn send(&mut self, msg: Vec<u8>) -> io::Result<()> {
+        self.sender
+            .send(msg)
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "the other end was dropped"))
+    }
+
+    fn recv(&mut self) -> io::Result<Option<Vec<u8>>> {
+        Ok(self.receiver.recv().ok())
+    }
+}
@@ -1,131 +1,324 @@
-use std::{any::Any, convert::TryFrom, str::FromStr, thread::Builder};
+use std::str::FromStr;
 
 use bumpalo::Bump;
 use proc_macro::TokenStream;
+use proc_macro2::{Ident, Span, TokenStream as TokenStream2};
 use quote::quote;
 use sledgehammer_encoder::{
-    attribute::AnyAttribute,
-    batch::{Batch, FinalizedBatch},
-    element::AnyElement,
-    Attribute, Element, ElementBuilder, NodeBuilder, NodeId, TextBuilder,
+    attribute::AnyAttribute, batch::Batch, element::AnyElement, Attribute, CommentBuilder, Element,
+    ElementBuilder, MaybeId, NodeBuilder, NodeId, TextBuilder, FORMAT_VERSION,
 };
-use syn::{Expr, Lit};
-use syn_rsx::{parse, Node, NodeType};
+use syn::{Expr, Lit, Stmt};
+use syn_rsx::{parse, Node};
 
 enum NodeInProgress {
     Element(ElementInProgress),
     Text(String),
+    Comment(String),
+    /// A `{expr}` hole, built as an empty text node and filled in when the batch is created
+    TextHole(Expr),
 }
 
 struct ElementInProgress {
     kind: String,
     attributes: Vec<(String, String)>,
+    /// Attributes with a `{expr}` value, set when the batch is created
+    attribute_holes: Vec<(String, Expr)>,
     children: Vec<NodeInProgress>,
 }
 
-fn walk_nodes(nodes: &Vec<Node>, inside: &mut Option<ElementInProgress>) {
+/// Collect the nodes in a list of syn-rsx nodes. The children of fragments are collected into the list.
+fn walk_nodes(nodes: &[Node], out: &mut Vec<NodeInProgress>) {
     for node in nodes {
         match node {
             Node::Doctype(_) => {}
             Node::Element(element) => {
-                let name = element.name.to_string();
-
-                let mut builder = Some(ElementInProgress {
-                    kind: name,
+                let mut el = ElementInProgress {
+                    kind: element.name.to_string(),
                     attributes: Vec::new(),
+                    attribute_holes: Vec::new(),
                     children: Vec::new(),
-                });
+                };
 
                 // attributes
-                walk_nodes(&element.attributes, &mut builder);
-
-                // children
-                walk_nodes(&element.children, &mut builder);
-
-                match inside {
-                    Some(el) => el.children.push(NodeInProgress::Element(builder.unwrap())),
-                    None => *inside = builder,
-                }
-            }
-            Node::Attribute(attribute) => {
-                let key_str = attribute.key.to_string();
-                if let Some(el) = inside {
+                for attribute in &element.attributes {
+                    let Node::Attribute(attribute) = attribute else {
+                        panic!("only attributes are supported in the attributes of an element")
+                    };
+                    let key_str = attribute.key.to_string();
                     if let Some(val) = &attribute.value {
-                        el.attributes.push((key_str, as_str_lit(&val)))
+                        match str_lit(val) {
+                            Some(val) => el.attributes.push((key_str, val)),
+                            None if key_str == "sledgehammer-id" => {
+                                panic!("sledgehammer-id must be a string literal")
+                            }
+                            None => el.attribute_holes.push((key_str, hole_value(val))),
+                        }
                     }
                 }
+
+                // children
+                walk_nodes(&element.children, &mut el.children);
+
+                out.push(NodeInProgress::Element(el));
             }
-            Node::Text(txt) => {
-                if let Some(el) = inside {
-                    el.children
-                        .push(NodeInProgress::Text(as_str_lit(&txt.value)))
-                }
-            }
-            Node::Fragment(_) => {
-                panic!("fragments are not supported")
-            }
-            Node::Comment(_) => {}
-            Node::Block(_) => {
-                panic!("blocks are not supported")
-            }
+            Node::Attribute(_) => unreachable!("attributes are only parsed inside elements"),
+            Node::Text(txt) => out.push(NodeInProgress::Text(as_str_lit(&txt.value))),
+            Node::Fragment(fragment) => walk_nodes(&fragment.children, out),
+            Node::Comment(comment) => out.push(NodeInProgress::Comment(as_str_lit(&comment.value))),
+            Node::Block(block) => out.push(NodeInProgress::TextHole(hole_value(&block.value))),
         }
     }
 }
 
 fn as_str_lit(expr: &Expr) -> String {
+    str_lit(expr).expect("expected string")
+}
+
+fn str_lit(expr: &Expr) -> Option<String> {
     if let Expr::Lit(u) = expr {
         if let Lit::Str(s) = &u.lit {
-            return s.value();
+            return Some(s.value());
+        }
+    }
+    None
+}
+
+/// The expression in a `{expr}` hole. The braces are removed so they do not trigger the `unused_braces` lint where the expression is used.
+fn hole_value(expr: &Expr) -> Expr {
+    if let Expr::Block(block) = expr {
+        if let [Stmt::Expr(inner)] = &block.block.stmts[..] {
+            if block.attrs.is_empty() && block.label.is_none() {
+                return inner.clone();
+            }
+        }
+    }
+    expr.clone()
+}
+
+/// An operation that fills in a hole after the template is built
+enum Patch<'a> {
+    Text(&'a Expr),
+    Attribute(&'a str, &'a Expr),
+}
+
+/// Find the holes in a node in document order, with the path to the node each hole is in. A path is the index of the child at every level below the root.
+fn collect_patches<'a>(
+    node: &'a NodeInProgress,
+    path: &mut Vec<usize>,
+    patches: &mut Vec<(Vec<usize>, Patch<'a>)>,
+) {
+    match node {
+        NodeInProgress::Element(el) => {
+            for (attr, value) in &el.attribute_holes {
+                patches.push((path.clone(), Patch::Attribute(attr, value)));
+            }
+            for (i, child) in el.children.iter().enumerate() {
+                path.push(i);
+                collect_patches(child, path, patches);
+                path.pop();
+            }
         }
+        NodeInProgress::TextHole(value) => patches.push((path.clone(), Patch::Text(value))),
+        NodeInProgress::Text(_) | NodeInProgress::Comment(_) => {}
     }
-    panic!("expected string")
 }
 
-/// Converts HTML to `String`.
+/// The variable that holds the batch in the generated code. It has a mixed site span so the expressions in holes cannot see or shadow it
+fn batch_ident() -> Ident {
+    Ident::new("batch", Span::mixed_site())
+}
+
+/// The operations that move the last node from the node at one path to the node at another
+fn navigate(from: &[usize], to: &[usize]) -> Vec<TokenStream2> {
+    let batch = batch_ident();
+    let common = from.iter().zip(to).take_while(|(a, b)| a == b).count();
+    // a later sibling of an ancestor can be reached without going up to its parent
+    let sibling = from.len() > common && to.len() > common && to[common] > from[common];
+    let mut ops = Vec::new();
+    for _ in 0..from.len() - common - usize::from(sibling) {
+        ops.push(quote! { #batch.parent_node(); });
+    }
+    let mut level = common;
+    if sibling {
+        for _ in from[common]..to[common] {
+            ops.push(quote! { #batch.next_sibling(); });
+        }
+        level += 1;
+    }
+    for &i in &to[level..] {
+        ops.push(quote! { #batch.first_child(); });
+        for _ in 0..i {
+            ops.push(quote! { #batch.next_sibling(); });
+        }
+    }
+    ops
+}
+
+/// The code that adds the operations encoded in a template so far to the batch, and starts a new template. The first template creates the batch.
+fn append_template(template: &mut Batch, first: bool) -> TokenStream2 {
+    let mut template = std::mem::take(template);
+    // the template is not finalized so operations can be encoded after it, and appending an empty batch fills its last group of operations
+    template.append(Batch::default());
+    let msg = &template.msg;
+    let str = &template.str_buf;
+    let batch = batch_ident();
+    let template = quote! {
+        ::sledgehammer_encoder::StaticBatch{
+            msg: &[#(#msg,)*],
+            str: &[#(#str,)*],
+            version: #FORMAT_VERSION,
+        }.into_template_batch()
+    };
+    if first {
+        quote! { let mut #batch = #template; }
+    } else {
+        quote! { #batch.append(#template); }
+    }
+}
+
+/// The attribute a patch sets, as the single byte [`Attribute`] if it is built in
+fn attribute_tokens(attr: &str) -> TokenStream2 {
+    match Attribute::from_str(attr) {
+        Ok(_) => {
+            // the variants of Attribute are the names of the attributes with - replaced by _
+            let name = attr.replace('-', "_");
+            let ident = syn::parse_str::<Ident>(&name)
+                .unwrap_or_else(|_| Ident::new_raw(&name, Span::call_site()));
+            quote! { ::sledgehammer_encoder::Attribute::#ident }
+        }
+        Err(_) => quote! { #attr },
+    }
+}
+
+/// Encodes HTML into a [`StaticBatch`](sledgehammer_encoder::StaticBatch) at compile time. Running the batch builds the node and leaves it as the last node.
+///
+/// The markup can have more than one root node, and `<>...</>` fragments are replaced with their children. With more than one root, every root is pushed onto the stack in order, so they can be added to the dom with operations like [`Batch::append_children`](sledgehammer_encoder::batch::Batch::append_children). Roots can be text and comments as well as elements.
 ///
-/// Values returned from braced blocks `{}` are expected to return something
-/// that implements `Display`.
+/// The batch records the format version of the encoder it was built with, so running it with an incompatible release of sledgehammer fails with an error instead of running the wrong operations.
+///
+/// Comments must be string literals, and text must be a string literal or a `{expr}` [hole](#holes). The `sledgehammer-id` attribute stores the element with that [`NodeId`] instead of setting an attribute.
 ///
 /// See [syn-rsx docs](https://docs.rs/syn-rsx/) for supported tags and syntax.
 ///
 /// # Example
 ///
 /// ```
-/// use html_to_string_macro::html;
+/// use sledgehammer_encoder::StaticBatch;
+/// use sledgehammer_prebuild::html;
 ///
-/// let world = "planet";
-/// assert_eq!(html!(<div>"hello "{world}</div>), "<div>hello planet</div>");
+/// const ROW: StaticBatch = html! {
+///     <tr sledgehammer-id="1">
+///         <td class="label">"hello planet"</td>
+///     </tr>
+/// };
+/// ```
+///
+/// # Holes
+///
+/// Text children and attribute values can be `{expr}` holes, where the expression is anything that implements [`WritableText`](sledgehammer_encoder::WritableText). Markup with holes creates a [`Batch`](sledgehammer_encoder::batch::Batch) instead: the markup is still encoded at compile time, and the values of the holes are encoded after it by navigating to their nodes. The roots are left as the last node or on the stack like a [`StaticBatch`](sledgehammer_encoder::StaticBatch).
+///
+/// ```
+/// use sledgehammer_encoder::batch::Batch;
+/// use sledgehammer_prebuild::html;
+///
+/// fn row(id: u32, label: &str, selected: bool) -> Batch {
+///     html! {
+///         <tr class={if selected { "danger" } else { "" }}>
+///             <td>{id}</td>
+///             <td><a>"label: "{label}</a></td>
+///         </tr>
+///     }
+/// }
+/// ```
+///
+/// # Multiple roots
+///
+/// ```
+/// use sledgehammer_encoder::{batch::Batch, *};
+/// use sledgehammer_prebuild::html;
+///
+/// fn list(first: &str) -> Batch {
+///     let mut batch = html! {
+///         <>
+///             <li>{first}</li>
+///             <li>"second"</li>
+///         </>
+///         "text after the list items"
+///     };
+///     // append the three roots to the node with the id 0
+///     batch.append_children(MaybeId::Node(NodeId(0)), 3);
+///     batch
+/// }
 /// ```
 #[proc_macro]
 pub fn html(tokens: TokenStream) -> TokenStream {
     match parse(tokens) {
         Ok(nodes) => {
-            let mut builder = None;
-            walk_nodes(&nodes, &mut builder);
-            match builder {
-                Some(builder) => {
-                    let bump = Bump::new();
-                    let builder = NodeInProgress::Element(builder);
-                    let builder = build_in_progress(&bump, &builder);
-                    let mut batch = Batch::default();
-                    match builder {
-                        NodeBuilder::Text(txt) => batch.build_text_node(txt),
-                        NodeBuilder::Element(el) => {
-                            batch.build_full_element(el);
-                        }
+            let mut roots = Vec::new();
+            walk_nodes(&nodes, &mut roots);
+            if roots.is_empty() {
+                panic!("empty html call");
+            }
+            let bump = Bump::new();
+            let mut template = Batch::default();
+            // the code that encodes the batch when it is created, if any root has holes
+            let mut ops = Vec::new();
+            let batch = batch_ident();
+            for root in &roots {
+                match build_in_progress(&bump, root) {
+                    NodeBuilder::Text(txt) => template.build_text_node(txt),
+                    NodeBuilder::Element(el) => {
+                        template.build_full_element(el);
                     }
-                    let finalized = batch.finalize();
-                    let msg = &finalized.msg;
-                    let str = &finalized.str;
-                    quote! {
-                        StaticBatch{
-                            msg: &[#(#msg,)*],
-                            str: &[#(#str,)*]
-                        }
+                    NodeBuilder::Comment(comment) => template.build_comment(comment),
+                }
+                let mut patches = Vec::new();
+                collect_patches(root, &mut Vec::new(), &mut patches);
+                if !patches.is_empty() {
+                    ops.push(append_template(&mut template, ops.is_empty()));
+                    let mut at: &[usize] = &[];
+                    for (path, patch) in &patches {
+                        ops.extend(navigate(at, path));
+                        ops.push(match patch {
+                            Patch::Text(value) => quote! {
+                                #batch.set_text(#value, ::sledgehammer_encoder::MaybeId::LastNode);
+                            },
+                            Patch::Attribute(attr, value) => {
+                                let attr = attribute_tokens(attr);
+                                quote! {
+                                    #batch.set_attribute(#attr, #value, ::sledgehammer_encoder::MaybeId::LastNode);
+                                }
+                            }
+                        });
+                        at = path;
+                    }
+                    ops.extend(navigate(at, &[]));
+                }
+                if roots.len() > 1 {
+                    template.push_root(MaybeId::LastNode);
+                }
+            }
+            if ops.is_empty() {
+                let finalized = template.finalize();
+                let msg = &finalized.msg;
+                let str = &finalized.str;
+                quote! {
+                    ::sledgehammer_encoder::StaticBatch{
+                        msg: &[#(#msg,)*],
+                        str: &[#(#str,)*],
+                        version: #FORMAT_VERSION,
                     }
                 }
-                None => {
-                    panic!("empty html call");
+            } else {
+                if !template.msg.is_empty() {
+                    ops.push(append_template(&mut template, false));
+                }
+                quote! {
+                    {
+                        #(#ops)*
+                        #batch
+                    }
                 }
             }
         }
@@ -173,5 +366,7 @@ fn build_in_progress<'a>(allocator: &'a Bump, node: &'a NodeInProgress) -> NodeB
             NodeBuilder::Element(builder)
         }
         NodeInProgress::Text(txt) => NodeBuilder::Text(TextBuilder::new(txt)),
+        NodeInProgress::Comment(txt) => NodeBuilder::Comment(CommentBuilder::new(txt)),
+        NodeInProgress::TextHole(_) => NodeBuilder::Text(TextBuilder::new("")),
     }
 }
@@ -0,0 +1,102 @@
+use sledgehammer_encoder::{batch::Batch, MaybeId, NativeInterpreter, NodeId, StaticBatch};
+use sledgehammer_prebuild::html;
+
+#[test]
+fn html_holes_patch_their_nodes() {
+    let row = |id: u32, label: &str| {
+        html! {
+            <tr data-id={id}>
+                <td><label for={label}>"#"{id}</label></td>
+                <td>"static"</td>
+                <td class="label">{label}</td>
+            </tr>
+        }
+    };
+    let (mut interpreter, body) = NativeInterpreter::with_body();
+    let mut batch = Batch::default();
+    for (id, label) in [(1, "one"), (2, "two")] {
+        batch.append(row(id, label));
+        batch.append_child(MaybeId::Node(NodeId(0)), MaybeId::LastNode);
+    }
+    interpreter.run_batch(batch.finalize()).unwrap();
+    assert_eq!(
+        interpreter.dom().to_html(body),
+        concat!(
+            r#"<body><tr data-id="1"><td><label for="one">#1</label></td><td>static</td><td class="label">one</td></tr>"#,
+            r#"<tr data-id="2"><td><label for="two">#2</label></td><td>static</td><td class="label">two</td></tr></body>"#,
+        )
+    );
+}
+
+#[test]
+fn html_holes_with_zero() {
+    let mut zeros = html! {
+        <tr data-count={0i32}><td>{0u32}</td><td>{-0i64}</td></tr>
+    };
+    zeros.append_child(MaybeId::Node(NodeId(0)), MaybeId::LastNode);
+    let (mut interpreter, body) = NativeInterpreter::with_body();
+    interpreter.run_batch(zeros.finalize()).unwrap();
+    assert_eq!(
+        interpreter.dom().to_html(body),
+        r#"<body><tr data-count="0"><td>0</td><td>0</td></tr></body>"#
+    );
+}
+
+#[test]
+fn html_holes_in_nested_and_sibling_nodes() {
+    // the holes can use a variable with the same name as the batch the macro creates
+    let batch = "batch";
+    let (first, second, third) = ("first", "second", "third");
+    let mut nested = html! {
+        <div>
+            <section>
+                <ul>
+                    <li><b>{first}</b></li>
+                    <li>"static"</li>
+                    <li><i>{second}</i>{third}</li>
+                </ul>
+                <p title={batch}></p>
+            </section>
+            {batch}
+            <span data-a={first} data-b={second}>{third}</span>
+        </div>
+    };
+    nested.append_child(MaybeId::Node(NodeId(0)), MaybeId::LastNode);
+    let (mut interpreter, body) = NativeInterpreter::with_body();
+    interpreter.run_batch(nested.finalize()).unwrap();
+    assert_eq!(
+        interpreter.dom().to_html(body),
+        concat!(
+            r#"<body><div><section><ul><li><b>first</b></li><li>static</li><li><i>second</i>third</li></ul><p title="batch"></p></section>"#,
+            r#"batch<span data-a="first" data-b="second">third</span></div></body>"#,
+        )
+    );
+}
+
+#[test]
+fn html_roots_are_pushed_onto_the_stack() {
+    const ITEMS: StaticBatch = html! {
+        <>
+            <li>"one"</li>
+            <li>"two"</li>
+        </>
+        "three"
+    };
+    let (mut interpreter, body) = NativeInterpreter::with_body();
+    interpreter.run_batch(&ITEMS).unwrap();
+    let mut batch = Batch::default();
+    batch.append_children(MaybeId::Node(NodeId(0)), 3);
+    let label = "five";
+    let mut items = html! {
+        <li class={label}>"four"</li>
+        {label}
+        <li>"six"</li>
+    };
+    items.append_children(MaybeId::Node(NodeId(0)), 3);
+    batch.append(items);
+    interpreter.run_batch(batch.finalize()).unwrap();
+    assert_eq!(
+        interpreter.dom().to_html(body),
+        r#"<body><li>one</li><li>two</li>three<li class="five">four</li>five<li>six</li></body>"#
+    );
+}
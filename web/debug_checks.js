@@ -0,0 +1,160 @@
+// The checks of the debug interpreter, selected with the `debug-interpreter` feature of the sledgehammer crate.
+// interpreter_debug.js is generated from interpreter.js followed by this file, so the operations are only written once.
+// The debug interpreter runs the same operations, but checks every read from the message against the bounds of the batch,
+// checks that operations never read past the end of the decoded strings, asserts that the last node is defined before it is used,
+// and can log every operation it runs.
+
+// a view of the wasm memory that throws when a read falls outside of the region that is currently being decoded
+class CheckedView extends DataView {
+    // only allow reads in start..end
+    limit(start, end) {
+        this.start = start;
+        this.end = end;
+    }
+
+    check(pos, size) {
+        if (pos < this.start || pos + size > this.end) {
+            throw new RangeError(`read of ${size} bytes at ${pos} is outside of the batch (${this.start}..${this.end})`);
+        }
+    }
+
+    getUint8(pos) {
+        this.check(pos, 1);
+        return super.getUint8(pos);
+    }
+
+    getUint16(pos, littleEndian) {
+        this.check(pos, 2);
+        return super.getUint16(pos, littleEndian);
+    }
+
+    getUint32(pos, littleEndian) {
+        this.check(pos, 4);
+        return super.getUint32(pos, littleEndian);
+    }
+}
+
+export class JsInterpreter extends Interpreter {
+    constructor(mem, _metadata_ptr, _ptr_ptr, _str_ptr_ptr, _str_len_ptr, _doc) {
+        super(mem, _metadata_ptr, _ptr_ptr, _str_ptr_ptr, _str_len_ptr, _doc);
+        // the length of the next message, set by the channel before every batch
+        this.msgLen = 0;
+        // the length of the strings of the batch in bytes
+        this.strLen = 0;
+        // log every operation as it runs
+        this.trace = false;
+    }
+
+    get lastNode() {
+        if (this._lastNode == null) {
+            throw new Error(`the last node is ${this._lastNode}; navigate to or create a node before using it`);
+        }
+        return this._lastNode;
+    }
+
+    set lastNode(node) {
+        this._lastNode = node;
+    }
+
+    SetMsgLen(len) {
+        this.msgLen = len;
+    }
+
+    SetTrace(trace) {
+        this.trace = trace;
+    }
+
+    UpdateMemory(mem) {
+        this.view = new CheckedView(mem.buffer);
+        this.view.limit(0, this.view.byteLength);
+    }
+
+    Work() {
+        // the metadata and the pointers to the batch can be anywhere in memory
+        this.view.limit(0, this.view.byteLength);
+        // this batch may have no strings, so any string read would be past the end
+        this.strings = "";
+        this.strPos = 0;
+        this.strBytes = undefined;
+        this.strByte = 0;
+        this.strLen = 0;
+        return super.Work();
+    }
+
+    decodeStrings() {
+        // strings that end with three bytes are read as a u32, so allow reading one byte past the end
+        this.view.limit(this.last_str_start, this.last_str_start + len + 1);
+        this.strLen = len;
+        super.decodeStrings();
+    }
+
+    str(len) {
+        // the lengths of the strings are in utf-8 bytes, and the position is only kept in bytes if the text has characters longer than a byte
+        const end = (this.strBytes === undefined ? this.strPos : this.strByte) + len;
+        if (end > this.strLen) {
+            throw new RangeError(`read strings up to ${end}, but the batch only has ${this.strLen} bytes of strings`);
+        }
+        return super.str(len);
+    }
+
+    WorkTransferred(msg, str) {
+        // the transferred buffer holds only the message
+        this.msgLen = msg.byteLength;
+        this.strLen = str.byteLength;
+        return super.WorkTransferred(msg, str);
+    }
+
+    runOps() {
+        // some operands are read as a u32 and only some of the bytes are used, so allow reading up to 3 bytes past the end
+        this.view.limit(this.u8BufPos, this.u8BufPos + this.msgLen + 3);
+        opIdx = 0;
+        missingAt = -1;
+        try {
+            for (; ;) {
+                op = this.view.getUint32(this.u8BufPos, true);
+                this.u8BufPos += 4;
+                for (let k = 0; k < 4; k++) {
+                    opStart = this.u8BufPos;
+                    const strStart = this.strPos;
+                    const done = exOp();
+                    if (this.trace) {
+                        console.log(this.disassemble(strStart));
+                    }
+                    if (done) return;
+                    // padding is not counted, so the index matches the operations that were queued
+                    if ((op & 0x1F) !== NO_OP) opIdx++;
+                    op >>>= 8;
+                }
+            }
+        }
+        catch (e) {
+            return this.opError(e);
+        }
+    }
+
+    opError(e) {
+        // the error may have come from reading outside of the batch, so describe it without the bounds checks
+        this.view.limit(0, this.view.byteLength);
+        return super.opError(e);
+    }
+
+    // formats the operation that just ran as "index: name flags operand-bytes strings"
+    disassemble(strStart) {
+        j = op & 0x1F;
+        pos = opStart;
+        if (j === EXTENDED_OP) {
+            j = this.view.getUint8(pos++);
+        }
+        out = `${opIdx}: ${opNames[j] || `unknown(${j})`}`;
+        if (op & 0xE0) {
+            out += ` ${op & 0x20 ? "a" : "-"}${op & 0x40 ? "b" : "-"}${op & 0x80 ? "c" : "-"}`;
+        }
+        for (; pos < this.u8BufPos; pos++) {
+            out += ` ${this.view.getUint8(pos).toString(16).padStart(2, "0")}`;
+        }
+        if (this.strPos > strStart) {
+            out += ` ${JSON.stringify(this.strings.substring(strStart, this.strPos))}`;
+        }
+        return out;
+    }
+}
@@ -1,4 +1,4 @@
-use sledgehammer::{channel::MaybeId, *};
+use sledgehammer::*;
 use wasm_bindgen::JsCast;
 
 fn main() {
@@ -24,7 +24,8 @@ fn main() {
     // append the new node to the body
     body.append_child(&div).unwrap();
 
-    let mut channel = MsgChannel::default();
+    // the web-sys nodes are passed to the javascript interpreter
+    let mut channel = MsgChannel::with_backend(JsBackend::default());
 
     // assign the NodeId(0) to the body element from web-sys
     channel.set_node(NodeId(0), JsCast::dyn_into(body).unwrap());
@@ -38,11 +39,8 @@ fn main() {
                     .id(NodeId(2))
                     .attrs(&[(Attribute::style.into(), "color: blue")])
                     .into(),
-                ElementBuilder::new("svg".in_namespace("http://www.w3.org/2000/svg").into())
-                    .attrs(&[(
-                        "width".in_namespace("http://www.w3.org/2000/svg").into(),
-                        "100%",
-                    )])
+                ElementBuilder::new(SvgElement::svg.into())
+                    .attrs(&[(Attribute::width.into(), "100%")])
                     .into(),
             ]),
     );
@@ -50,13 +48,10 @@ fn main() {
     channel.set_text("Hello from sledehammer!", MaybeId::Node(NodeId(2)));
 
     // append the new node to the body
-    channel.append_child(
-        MaybeId::Node(NodeId(0)),
-        sledgehammer::channel::MaybeId::Node(NodeId(1)),
-    );
+    channel.append_child(MaybeId::Node(NodeId(0)), MaybeId::Node(NodeId(1)));
 
     // execute the queued operations
-    channel.flush();
+    channel.flush().unwrap();
 
     // we can also get web-sys nodes out of sledgehammer
     let element = channel.get_node(NodeId(2));
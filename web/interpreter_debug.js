@@ -0,0 +1,1559 @@
+// @generated from interpreter.js and debug_checks.js by sledgehammer-encoder, do not edit by hand
+// the operations are generated at the end of the file from the specification in sledgehammer-encoder
+let op, len, ns, attr, i, j, value, element, pos, out, char, numAttributes, endRounded, inptr, doc, metadata, parent, node, id, nodes, opIdx, opStart, missingId, missingAt;
+
+class Interpreter {
+    constructor(mem, _metadata_ptr, _ptr_ptr, _str_ptr_ptr, _str_len_ptr, _doc) {
+        this.lastNode = undefined;
+        this.nodes = [];
+        this.templates = [];
+        this.styleSheets = [];
+        // the shadow roots attached by the interpreter by their host, because closed shadow roots are not in host.shadowRoot
+        this.shadowRoots = new WeakMap();
+        this.stack = [];
+        this.UpdateMemory(mem);
+        this.last_start_pos;
+        this.last_str_start;
+        this.metadata_ptr = _metadata_ptr;
+        this.ptr_ptr = _ptr_ptr;
+        this.str_ptr_ptr = _str_ptr_ptr;
+        this.str_len_ptr = _str_len_ptr;
+        this.strings = "";
+        this.strPos = 0;
+        // the utf-8 bytes of the strings and the position in them, if the decoded text has characters longer than a byte
+        this.strBytes = undefined;
+        this.strByte = 0;
+        this.decoder = new TextDecoder();
+        this.idSize = 1;
+        // the document new nodes are created in
+        this.doc = _doc || document;
+    }
+
+    NeedsMemory() {
+        return this.view.buffer.byteLength === 0;
+    }
+
+    UpdateMemory(mem) {
+        this.view = new DataView(mem.buffer);
+    }
+
+    Work() {
+        // the operations use the interpreter and document in these globals
+        inptr = this;
+        doc = this.doc;
+        metadata = this.view.getUint8(this.metadata_ptr);
+        if (metadata & 0x01) {
+            this.last_start_pos = this.view.getUint32(this.ptr_ptr, true);
+        }
+        this.u8BufPos = this.last_start_pos;
+        if (metadata & 0x04) {
+            len = this.view.getUint32(this.str_len_ptr, true);
+            if (metadata & 0x02) {
+                this.last_str_start = this.view.getUint32(this.str_ptr_ptr, true);
+            }
+            this.decodeStrings();
+            this.strPos = 0;
+            this.strByte = 0;
+        }
+
+        return this.runOps();
+    }
+
+    // decodes the len bytes of strings at last_str_start
+    decodeStrings() {
+        // for small strings decoding them in javascript to avoid the overhead of native calls is faster
+        // the fourth boolean contains information about whether the string is all ascii or utf8 and small
+        if (metadata & 0x08) {
+            this.strBytes = undefined;
+            pos = this.last_str_start;
+            this.strings = "";
+            endRounded = pos + ((len / 4) | 0) * 4;
+            while (pos < endRounded) {
+                char = this.view.getUint32(pos);
+                this.strings += String.fromCharCode(char >> 24, (char & 0x00FF0000) >> 16, (char & 0x0000FF00) >> 8, (char & 0x000000FF));
+                pos += 4;
+            }
+            switch (this.last_str_start + len - pos) {
+                case 3:
+                    char = this.view.getUint32(pos);
+                    this.strings += String.fromCharCode(char >> 24, (char & 0x00FF0000) >> 16, (char & 0x0000FF00) >> 8);
+                    break;
+                case 2:
+                    char = this.view.getUint16(pos);
+                    this.strings += String.fromCharCode(char >> 8, char & 0xFF);
+                    break;
+                case 1:
+                    this.strings += String.fromCharCode(this.view.getUint8(pos));
+                    break;
+                case 0:
+                    break;
+            }
+        }
+        else {
+            this.strings = this.decoder.decode(new DataView(this.view.buffer, this.last_str_start, len));
+            this.strBytes = this.strings.length === len ? undefined : new Uint8Array(this.view.buffer, this.last_str_start, len);
+        }
+    }
+
+    // reads the next string, where len is its length in utf-8 bytes
+    str(len) {
+        // if every character is one byte, the lengths are the same in the decoded text
+        if (this.strBytes === undefined) {
+            return this.strings.substring(this.strPos, this.strPos += len);
+        }
+        // the strings are decoded once per batch, so count the utf-16 code units of the string's bytes to find it in the decoded text
+        // every byte except a continuation byte starts a character, and characters of four bytes are two code units
+        let units = 0;
+        const end = this.strByte + len;
+        for (let b = this.strByte; b < end; b++) {
+            const byte = this.strBytes[b];
+            if ((byte & 0xC0) !== 0x80) units += byte >= 0xF0 ? 2 : 1;
+        }
+        this.strByte = end;
+        return this.strings.substring(this.strPos, this.strPos += units);
+    }
+
+    // runs a batch that was encoded somewhere else (like a web worker) and transferred as two array buffers
+    // msg contains the operations starting at byte 0, and str contains the utf-8 encoded strings
+    WorkTransferred(msg, str) {
+        inptr = this;
+        doc = this.doc;
+        const view = this.view;
+        this.UpdateMemory({ buffer: msg });
+        this.u8BufPos = 0;
+        this.strings = this.decoder.decode(str);
+        this.strBytes = this.strings.length === str.byteLength ? undefined : new Uint8Array(str);
+        this.strPos = 0;
+        this.strByte = 0;
+        i = this.runOps();
+        // go back to reading from the wasm memory
+        this.view = view;
+        return i;
+    }
+
+    // runs operations until the stop operation
+    // if an operation throws, the rest of the batch is skipped and a description of the error is returned
+    runOps() {
+        opIdx = 0;
+        missingAt = -1;
+        try {
+            // this is faster than a while(true) loop
+            for (; ;) {
+                // op = this.view.getUint8(this.u8BufPos++);
+                // if (this.exOp(op & 0x1F)) return;
+                op = this.view.getUint32(this.u8BufPos, true);
+                this.u8BufPos += 4;
+                // padding is not counted, so the index matches the operations that were queued
+                opStart = this.u8BufPos;
+                if (exOp()) return;
+                if ((op & 0x1F) !== NO_OP) opIdx++;
+                op >>>= 8;
+                opStart = this.u8BufPos;
+                if (exOp()) return;
+                if ((op & 0x1F) !== NO_OP) opIdx++;
+                op >>>= 8;
+                opStart = this.u8BufPos;
+                if (exOp()) return;
+                if ((op & 0x1F) !== NO_OP) opIdx++;
+                op >>>= 8;
+                opStart = this.u8BufPos;
+                if (exOp()) return;
+                if ((op & 0x1F) !== NO_OP) opIdx++;
+            }
+        }
+        catch (e) {
+            return this.opError(e);
+        }
+    }
+
+    // describes the operation that threw as [operation index, opcode, missing node id or undefined, message]
+    opError(e) {
+        j = op & 0x1F;
+        pos = opStart;
+        if (j === EXTENDED_OP) {
+            // extended operations store their opcode in the first byte of their data
+            j = this.view.getUint8(pos++);
+        }
+        // the id of a node the operation looked up that is not stored
+        id = missingAt === opStart ? missingId : undefined;
+        return [opIdx, j, id, String(e && e.message || e)];
+    }
+
+    createElement() {
+        j = this.view.getUint32(this.u8BufPos, true);
+        element = j & 0xFF;
+        switch (element) {
+            case 255:
+                // the element is encoded as an enum and the namespace is encoded as a string
+                // we use all 4 bytes of i just read
+                this.u8BufPos += 4;
+                element = doc.createElement(els[(j & 0xFF00) >>> 8], this.str((j & 0xFFFF0000) >>> 16));
+                return element;
+            case 254:
+                // the element is encoded as a string
+                // we use 3 bytes of i just read
+                this.u8BufPos += 3;
+                element = doc.createElement(this.str((j & 0xFFFF00) >>> 8));
+                return element;
+            case 253:
+                // the element and namespace are encoded as strings
+                // we use 3 bytes of i just read
+                this.u8BufPos += 3;
+                element = this.str((j & 0xFFFF00) >>> 8);
+                element = doc.createElementNS(this.str(this.view.getUint16(this.u8BufPos, true)), element);
+                this.u8BufPos += 2;
+                return element;
+            case 252:
+                // the element is a svg element encoded as an enum
+                // we use 2 bytes of i just read
+                this.u8BufPos += 2;
+                return doc.createElementNS(svgNs, svgEls[(j & 0xFF00) >>> 8]);
+            case 251:
+                // the element is a MathML element encoded as an enum
+                // we use 2 bytes of i just read
+                this.u8BufPos += 2;
+                return doc.createElementNS(mathMlNs, mathMlEls[(j & 0xFF00) >>> 8]);
+            default:
+                this.u8BufPos++;
+                // the element is encoded as an enum
+                return doc.createElement(els[element]);
+        }
+    }
+
+    createFullElement() {
+        let parent_id;
+        j = this.view.getUint8(this.u8BufPos++);
+        if (j & 0x1) {
+            parent_id = this.view.getUint32(this.u8BufPos, true);
+            this.u8BufPos += 4;
+        }
+        if (j & 0x2) {
+            node = doc.createTextNode(this.str(this.view.getUint16(this.u8BufPos, true)));
+            this.u8BufPos += 2;
+            if (parent_id !== null) {
+                this.nodes[parent_id] = node;
+            }
+            return node;
+        }
+        else if (j & 0x4) {
+            node = doc.createComment(this.str(this.view.getUint16(this.u8BufPos, true)));
+            this.u8BufPos += 2;
+            if (j & 0x1) {
+                this.nodes[parent_id] = node;
+            }
+            return node;
+        }
+        else {
+            const parent_element = this.createElement();
+            j = this.view.getUint16(this.u8BufPos, true);
+            this.u8BufPos += 2;
+            numAttributes = j & 0xFF;
+            const numChildren = (j & 0xFF00) >>> 8;
+            for (i = 0; i < numAttributes; i++) {
+                j = this.view.getUint32(this.u8BufPos, true);
+                attr = j & 0xFF;
+                switch (attr) {
+                    case 255:
+                        // the attribute is encoded as an enum and the namespace is encoded as a string
+                        // we use all 4 bytes of j just read
+                        this.u8BufPos += 4;
+                        attr = attrs[this.view.getUint8((j & 0xFF00) >>> 8)];
+                        parent_element.setAttributeNS(this.str((j & 0xFFFF0000) >>> 16), attr);
+                        break;
+                    case 254:
+                        // the attribute is encoded as a string
+                        // move one byte forward to skip the byte for attr
+                        this.u8BufPos++;
+                        j = this.view.getUint32(this.u8BufPos, true);
+                        this.u8BufPos += 4;
+                        attr = this.str(j & 0xFFFF);
+                        parent_element.setAttribute(attr, this.str((j & 0xFFFF0000) >>> 16));
+                        break;
+                    case 253:
+                        // the attribute and namespace are encoded as strings
+                        // we use 3 bytes of j just read
+                        this.u8BufPos += 3;
+                        attr = this.str((j & 0xFFFF00) >>> 8);
+                        j = this.view.getUint32(this.u8BufPos, true);
+                        this.u8BufPos += 4;
+                        ns = this.str(j & 0xFFFF);
+                        value = this.str((j & 0xFFFF0000) >>> 16);
+                        parent_element.setAttributeNS(ns, attr, value);
+                        break;
+                    default:
+                        // we use 3 bytes of j just read
+                        this.u8BufPos += 3;
+                        parent_element.setAttribute(attrs[attr], this.str((j & 0xFFFF00) >>> 8));
+                        break;
+                }
+            }
+            for (let w = 0; w < numChildren; w++) {
+                parent_element.appendChild(this.createFullElement());
+            }
+            if (parent_id !== null) {
+                this.nodes[parent_id] = parent_element;
+            }
+            return parent_element;
+        }
+    }
+
+    // decodes a u8 count of nodes built with a NodeBuilder
+    decodeRoots() {
+        const roots = [];
+        for (let w = this.view.getUint8(this.u8BufPos++); w > 0; w--) {
+            roots.push(this.createFullElement());
+        }
+        return roots;
+    }
+
+    // releases the ids of root and of every node in its subtree, including the nodes in the shadow roots attached to them
+    freeContained(root) {
+        const removed = new Set();
+        const stack = [root];
+        while (stack.length) {
+            const next = stack.pop();
+            removed.add(next);
+            for (let c = 0; c < next.childNodes.length; c++) stack.push(next.childNodes[c]);
+            const shadowRoot = this.shadowRoots.get(next);
+            if (shadowRoot) stack.push(shadowRoot);
+        }
+        for (let w = 0; w < this.nodes.length; w++) {
+            if (removed.has(this.nodes[w])) this.nodes[w] = undefined;
+        }
+    }
+
+    // pops the topmost n nodes from the stack in the order they were pushed
+    popStack(n) {
+        if (n > this.stack.length) {
+            throw new Error("popped " + n + " nodes, but the stack only has " + this.stack.length + " nodes");
+        }
+        return this.stack.splice(this.stack.length - n);
+    }
+
+    // parses html and inserts it at a position relative to root, and returns the first inserted node
+    insertAdjacentHtml(root, position, html) {
+        // remember the nodes around the insertion point so we can find the first inserted node
+        switch (position) {
+            // before begin
+            case 0:
+                parent = root.previousSibling;
+                element = root;
+                break;
+            // after begin
+            case 1:
+                parent = null;
+                element = root.firstChild;
+                break;
+            // before end
+            case 2:
+                parent = root.lastChild;
+                element = null;
+                break;
+            // after end
+            case 3:
+                parent = root;
+                element = root.nextSibling;
+                break;
+        }
+        root.insertAdjacentHTML(positions[position], html);
+        parent = parent ? parent.nextSibling : (position === 0 ? root.parentNode : root).firstChild;
+        // if the html was empty, the node after the insertion point is not the first inserted node
+        return parent === element ? undefined : parent;
+    }
+
+    // decodes the nodes of an operation that takes many nodes, each with a u8 discriminant
+    decodeNodes() {
+        nodes = [];
+        for (let w = this.view.getUint8(this.u8BufPos++); w > 0; w--) {
+            nodes.push(this.view.getUint8(this.u8BufPos++) ? this.node() : this.lastNode);
+        }
+        return nodes;
+    }
+
+    // looks up the node with the id at the current position, and remembers the id for the error of the operation if no node is stored with it
+    node() {
+        id = this.decodeU32();
+        node = this.nodes[id];
+        if (node === undefined) {
+            missingId = id;
+            missingAt = opStart;
+        }
+        return node;
+    }
+
+    // decodes a style property name encoded with a u8 discriminant
+    styleName() {
+        j = this.view.getUint8(this.u8BufPos++);
+        switch (j) {
+            // the property is a string
+            case 255:
+                j = this.view.getUint16(this.u8BufPos, true);
+                this.u8BufPos += 2;
+                return this.str(j);
+            // the property is a custom property without the leading --
+            case 254:
+                j = this.view.getUint16(this.u8BufPos, true);
+                this.u8BufPos += 2;
+                return "--" + this.str(j);
+            default:
+                return styles[j];
+        }
+    }
+
+    // decodes a u16 count of style property names and values into a flat array
+    decodeStyles() {
+        const values = [];
+        for (let w = this.decodeU16(); w > 0; w--) {
+            values.push(this.styleName(), this.str(this.decodeU16()));
+        }
+        return values;
+    }
+
+    decodeU32() {
+        this.u8BufPos += 4;
+        return this.view.getUint32(this.u8BufPos - 4, true);
+    }
+
+    decodeU16() {
+        this.u8BufPos += 2;
+        return this.view.getUint16(this.u8BufPos - 2, true);
+    }
+
+    SetNode(id, node) {
+        this.nodes[id] = node;
+    }
+
+    GetNode(id) {
+        return this.nodes[id];
+    }
+
+    // the version of the batch format this interpreter reads, which must match FORMAT_VERSION in sledgehammer-encoder
+    FormatVersion() {
+        return FORMAT_VERSION;
+    }
+}
+
+// @generated start: generated from the specification in sledgehammer-encoder, do not edit by hand
+const FORMAT_VERSION = 3;
+
+// the codes of the operations
+const FIRST_CHILD = 0;
+const NEXT_SIBLING = 1;
+const PARENT_NODE = 2;
+const STORE_WITH_ID = 3;
+const SET_LAST_NODE = 4;
+const STOP = 5;
+const BUILD_FULL_ELEMENT = 6;
+const APPEND_CHILDREN = 7;
+const REPLACE_WITH = 8;
+const INSERT_AFTER = 9;
+const INSERT_BEFORE = 10;
+const REMOVE = 11;
+const CREATE_TEXT_NODE = 12;
+const CREATE_ELEMENT = 13;
+const SET_TEXT = 14;
+const SET_ATTRIBUTE = 15;
+const REMOVE_ATTRIBUTE = 16;
+const SET_STYLE = 17;
+const REMOVE_STYLE = 18;
+const CLONE_NODE = 19;
+const NO_OP = 20;
+const SET_INNER_HTML = 21;
+const INSERT_ADJACENT_HTML = 22;
+const CREATE_COMMENT = 23;
+const CREATE_FRAGMENT = 24;
+const CREATE_TEMPLATE = 25;
+const CLONE_TEMPLATE = 26;
+const ATTACH_SHADOW = 27;
+const CREATE_STYLE_SHEET = 28;
+const ADOPT_STYLE_SHEET = 29;
+const FREE_IDS = 30;
+const PUSH_ROOT = 32;
+const APPEND_CHILDREN_FROM_STACK = 33;
+const REPLACE_WITH_FROM_STACK = 34;
+const INSERT_AFTER_FROM_STACK = 35;
+const INSERT_BEFORE_FROM_STACK = 36;
+const APPEND_DATA = 37;
+const INSERT_DATA = 38;
+const DELETE_DATA = 39;
+const REPLACE_DATA = 40;
+const SET_STYLES = 41;
+const FREE_TEMPLATE = 42;
+const FREE_STYLE_SHEET = 43;
+// operations with this code or larger are encoded as this code followed by a byte with the code of the operation
+const EXTENDED_OP = 31;
+
+const opNames = [
+    "first_child",
+    "next_sibling",
+    "parent_node",
+    "store_with_id",
+    "set_last_node",
+    "stop",
+    "build_full_element",
+    "append_children",
+    "replace_with",
+    "insert_after",
+    "insert_before",
+    "remove",
+    "create_text_node",
+    "create_element",
+    "set_text",
+    "set_attribute",
+    "remove_attribute",
+    "set_style",
+    "remove_style",
+    "clone_node",
+    "no_op",
+    "set_inner_html",
+    "insert_adjacent_html",
+    "create_comment",
+    "create_fragment",
+    "create_template",
+    "clone_template",
+    "attach_shadow",
+    "create_style_sheet",
+    "adopt_style_sheet",
+    "free_ids",
+    "extended",
+    "push_root",
+    "append_children_from_stack",
+    "replace_with_from_stack",
+    "insert_after_from_stack",
+    "insert_before_from_stack",
+    "append_data",
+    "insert_data",
+    "delete_data",
+    "replace_data",
+    "set_styles",
+    "free_template",
+    "free_style_sheet",
+];
+
+const positions = [
+    "beforebegin",
+    "afterbegin",
+    "beforeend",
+    "afterend",
+];
+
+const styles = [
+    "align-content",
+    "align-items",
+    "align-self",
+    "background",
+    "background-color",
+    "background-image",
+    "background-position",
+    "background-repeat",
+    "background-size",
+    "border",
+    "border-bottom",
+    "border-color",
+    "border-left",
+    "border-radius",
+    "border-right",
+    "border-style",
+    "border-top",
+    "border-width",
+    "bottom",
+    "box-shadow",
+    "box-sizing",
+    "color",
+    "cursor",
+    "display",
+    "flex",
+    "flex-basis",
+    "flex-direction",
+    "flex-grow",
+    "flex-shrink",
+    "flex-wrap",
+    "float",
+    "font",
+    "font-family",
+    "font-size",
+    "font-style",
+    "font-weight",
+    "gap",
+    "grid-area",
+    "grid-column",
+    "grid-row",
+    "grid-template-areas",
+    "grid-template-columns",
+    "grid-template-rows",
+    "height",
+    "justify-content",
+    "justify-items",
+    "justify-self",
+    "left",
+    "letter-spacing",
+    "line-height",
+    "margin",
+    "margin-bottom",
+    "margin-left",
+    "margin-right",
+    "margin-top",
+    "max-height",
+    "max-width",
+    "min-height",
+    "min-width",
+    "opacity",
+    "outline",
+    "overflow",
+    "overflow-x",
+    "overflow-y",
+    "padding",
+    "padding-bottom",
+    "padding-left",
+    "padding-right",
+    "padding-top",
+    "pointer-events",
+    "position",
+    "right",
+    "text-align",
+    "text-decoration",
+    "text-overflow",
+    "text-transform",
+    "top",
+    "transform",
+    "transform-origin",
+    "transition",
+    "user-select",
+    "vertical-align",
+    "visibility",
+    "white-space",
+    "width",
+    "word-break",
+    "z-index",
+];
+
+const els = [
+    "a",
+    "abbr",
+    "acronym",
+    "address",
+    "applet",
+    "area",
+    "article",
+    "aside",
+    "audio",
+    "b",
+    "base",
+    "bdi",
+    "bdo",
+    "bgsound",
+    "big",
+    "blink",
+    "blockquote",
+    "body",
+    "br",
+    "button",
+    "canvas",
+    "caption",
+    "center",
+    "cite",
+    "code",
+    "col",
+    "colgroup",
+    "content",
+    "data",
+    "datalist",
+    "dd",
+    "del",
+    "details",
+    "dfn",
+    "dialog",
+    "dir",
+    "div",
+    "dl",
+    "dt",
+    "em",
+    "embed",
+    "fieldset",
+    "figcaption",
+    "figure",
+    "font",
+    "footer",
+    "form",
+    "frame",
+    "frameset",
+    "h1",
+    "head",
+    "header",
+    "hgroup",
+    "hr",
+    "html",
+    "i",
+    "iframe",
+    "image",
+    "img",
+    "input",
+    "ins",
+    "kbd",
+    "keygen",
+    "label",
+    "legend",
+    "li",
+    "link",
+    "main",
+    "map",
+    "mark",
+    "marquee",
+    "menu",
+    "menuitem",
+    "meta",
+    "meter",
+    "nav",
+    "nobr",
+    "noembed",
+    "noframes",
+    "noscript",
+    "object",
+    "ol",
+    "optgroup",
+    "option",
+    "output",
+    "p",
+    "param",
+    "picture",
+    "plaintext",
+    "portal",
+    "pre",
+    "progress",
+    "q",
+    "rb",
+    "rp",
+    "rt",
+    "rtc",
+    "ruby",
+    "s",
+    "samp",
+    "script",
+    "section",
+    "select",
+    "shadow",
+    "slot",
+    "small",
+    "source",
+    "spacer",
+    "span",
+    "strike",
+    "strong",
+    "style",
+    "sub",
+    "summary",
+    "sup",
+    "table",
+    "tbody",
+    "td",
+    "template",
+    "textarea",
+    "tfoot",
+    "th",
+    "thead",
+    "time",
+    "title",
+    "tr",
+    "track",
+    "tt",
+    "u",
+    "ul",
+    "var",
+    "video",
+    "wbr",
+    "xmp",
+];
+
+const svgNs = "http://www.w3.org/2000/svg";
+const mathMlNs = "http://www.w3.org/1998/Math/MathML";
+
+const svgEls = [
+    "a",
+    "animate",
+    "animateMotion",
+    "animateTransform",
+    "circle",
+    "clipPath",
+    "defs",
+    "desc",
+    "ellipse",
+    "feBlend",
+    "feColorMatrix",
+    "feComponentTransfer",
+    "feComposite",
+    "feConvolveMatrix",
+    "feDiffuseLighting",
+    "feDisplacementMap",
+    "feDistantLight",
+    "feDropShadow",
+    "feFlood",
+    "feFuncA",
+    "feFuncB",
+    "feFuncG",
+    "feFuncR",
+    "feGaussianBlur",
+    "feImage",
+    "feMerge",
+    "feMergeNode",
+    "feMorphology",
+    "feOffset",
+    "fePointLight",
+    "feSpecularLighting",
+    "feSpotLight",
+    "feTile",
+    "feTurbulence",
+    "filter",
+    "foreignObject",
+    "g",
+    "image",
+    "line",
+    "linearGradient",
+    "marker",
+    "mask",
+    "metadata",
+    "mpath",
+    "path",
+    "pattern",
+    "polygon",
+    "polyline",
+    "radialGradient",
+    "rect",
+    "script",
+    "set",
+    "stop",
+    "style",
+    "svg",
+    "switch",
+    "symbol",
+    "text",
+    "textPath",
+    "title",
+    "tspan",
+    "use",
+    "view",
+];
+
+const mathMlEls = [
+    "annotation",
+    "annotation-xml",
+    "math",
+    "merror",
+    "mfrac",
+    "mi",
+    "mmultiscripts",
+    "mn",
+    "mo",
+    "mover",
+    "mpadded",
+    "mphantom",
+    "mprescripts",
+    "mroot",
+    "mrow",
+    "ms",
+    "mspace",
+    "msqrt",
+    "mstyle",
+    "msub",
+    "msubsup",
+    "msup",
+    "mtable",
+    "mtd",
+    "mtext",
+    "mtr",
+    "munder",
+    "munderover",
+    "semantics",
+];
+
+const attrs = [
+    "accept-charset",
+    "accept",
+    "accesskey",
+    "action",
+    "align",
+    "allow",
+    "alt",
+    "aria-atomic",
+    "aria-busy",
+    "aria-controls",
+    "aria-current",
+    "aria-describedby",
+    "aria-description",
+    "aria-details",
+    "aria-disabled",
+    "aria-dropeffect",
+    "aria-errormessage",
+    "aria-flowto",
+    "aria-grabbed",
+    "aria-haspopup",
+    "aria-hidden",
+    "aria-invalid",
+    "aria-keyshortcuts",
+    "aria-label",
+    "aria-labelledby",
+    "aria-live",
+    "aria-owns",
+    "aria-relevant",
+    "aria-roledescription",
+    "async",
+    "autocapitalize",
+    "autocomplete",
+    "autofocus",
+    "autoplay",
+    "background",
+    "bgcolor",
+    "border",
+    "buffered",
+    "capture",
+    "challenge",
+    "charset",
+    "checked",
+    "cite",
+    "class",
+    "code",
+    "codebase",
+    "color",
+    "cols",
+    "colspan",
+    "content",
+    "contenteditable",
+    "contextmenu",
+    "controls",
+    "coords",
+    "crossorigin",
+    "csp",
+    "data",
+    "datetime",
+    "decoding",
+    "default",
+    "defer",
+    "dir",
+    "dirname",
+    "disabled",
+    "download",
+    "draggable",
+    "enctype",
+    "enterkeyhint",
+    "for",
+    "form",
+    "formaction",
+    "formenctype",
+    "formmethod",
+    "formnovalidate",
+    "formtarget",
+    "headers",
+    "height",
+    "hidden",
+    "high",
+    "href",
+    "hreflang",
+    "http-equiv",
+    "icon",
+    "id",
+    "importance",
+    "inputmode",
+    "integrity",
+    "intrinsicsize",
+    "ismap",
+    "itemprop",
+    "keytype",
+    "kind",
+    "label",
+    "lang",
+    "language",
+    "list",
+    "loading",
+    "loop",
+    "low",
+    "manifest",
+    "max",
+    "maxlength",
+    "media",
+    "method",
+    "min",
+    "minlength",
+    "multiple",
+    "muted",
+    "name",
+    "novalidate",
+    "open",
+    "optimum",
+    "pattern",
+    "ping",
+    "placeholder",
+    "poster",
+    "preload",
+    "radiogroup",
+    "readonly",
+    "referrerpolicy",
+    "rel",
+    "required",
+    "reversed",
+    "role",
+    "rows",
+    "rowspan",
+    "sandbox",
+    "scope",
+    "scoped",
+    "selected",
+    "shape",
+    "size",
+    "sizes",
+    "slot",
+    "span",
+    "spellcheck",
+    "src",
+    "srcdoc",
+    "srclang",
+    "srcset",
+    "start",
+    "step",
+    "style",
+    "summary",
+    "tabindex",
+    "target",
+    "title",
+    "translate",
+    "type",
+    "usemap",
+    "value",
+    "width",
+    "wrap",
+    // svg attributes
+    "attributeName",
+    "begin",
+    "clip-path",
+    "clipPathUnits",
+    "clip-rule",
+    "cx",
+    "cy",
+    "d",
+    "dominant-baseline",
+    "dur",
+    "dx",
+    "dy",
+    "fill",
+    "fill-opacity",
+    "fill-rule",
+    "filter",
+    "filterUnits",
+    "fr",
+    "from",
+    "fx",
+    "fy",
+    "gradientTransform",
+    "gradientUnits",
+    "in",
+    "in2",
+    "lengthAdjust",
+    "marker-end",
+    "markerHeight",
+    "marker-mid",
+    "marker-start",
+    "markerUnits",
+    "markerWidth",
+    "mask",
+    "maskContentUnits",
+    "maskUnits",
+    "opacity",
+    "orient",
+    "pathLength",
+    "patternContentUnits",
+    "patternTransform",
+    "patternUnits",
+    "points",
+    "preserveAspectRatio",
+    "r",
+    "refX",
+    "refY",
+    "repeatCount",
+    "result",
+    "rx",
+    "ry",
+    "spreadMethod",
+    "startOffset",
+    "stdDeviation",
+    "stop-color",
+    "stop-opacity",
+    "stroke",
+    "stroke-dasharray",
+    "stroke-dashoffset",
+    "stroke-linecap",
+    "stroke-linejoin",
+    "stroke-miterlimit",
+    "stroke-opacity",
+    "stroke-width",
+    "text-anchor",
+    "textLength",
+    "to",
+    "transform",
+    "values",
+    "vector-effect",
+    "viewBox",
+    "x",
+    "x1",
+    "x2",
+    "y",
+    "y1",
+    "y2",
+    // MathML attributes
+    "accent",
+    "accentunder",
+    "columnspan",
+    "display",
+    "displaystyle",
+    "fence",
+    "largeop",
+    "linethickness",
+    "lspace",
+    "mathbackground",
+    "mathcolor",
+    "mathsize",
+    "mathvariant",
+    "maxsize",
+    "minsize",
+    "movablelimits",
+    "rspace",
+    "scriptlevel",
+    "separator",
+    "stretchy",
+    "symmetric",
+    "voffset",
+];
+
+// runs the operation in the lowest byte of op, and returns true if it was the stop operation
+function exOp() {
+    switch (op & 0x1F) {
+        case FIRST_CHILD:
+            inptr.lastNode = inptr.lastNode.firstChild;
+            break;
+        case NEXT_SIBLING:
+            inptr.lastNode = inptr.lastNode.nextSibling;
+            break;
+        case PARENT_NODE:
+            inptr.lastNode = inptr.lastNode.parentNode;
+            break;
+        case STORE_WITH_ID: {
+            const id = inptr.decodeU32();
+            inptr.nodes[id] = inptr.lastNode;
+            break;
+        }
+        case SET_LAST_NODE: {
+            const id = inptr.decodeU32();
+            inptr.lastNode = inptr.nodes[id];
+            break;
+        }
+        case STOP:
+            return true;
+        case BUILD_FULL_ELEMENT: {
+            const el = inptr.createFullElement();
+            inptr.lastNode = el;
+            break;
+        }
+        case APPEND_CHILDREN: {
+            const root = op & 0x20 ? inptr.node() : inptr.lastNode;
+            const child = op & 0x40 ? inptr.node() : inptr.lastNode;
+            root.appendChild(child);
+            break;
+        }
+        case REPLACE_WITH: {
+            const root = op & 0x20 ? inptr.node() : inptr.lastNode;
+            const nodes = op & 0x40 ? inptr.decodeNodes() : [op & 0x80 ? inptr.node() : inptr.lastNode];
+            root.replaceWith(...nodes);
+            break;
+        }
+        case INSERT_AFTER: {
+            const root = op & 0x20 ? inptr.node() : inptr.lastNode;
+            const nodes = op & 0x40 ? inptr.decodeNodes() : [op & 0x80 ? inptr.node() : inptr.lastNode];
+            root.after(...nodes);
+            break;
+        }
+        case INSERT_BEFORE: {
+            const root = op & 0x20 ? inptr.node() : inptr.lastNode;
+            const nodes = op & 0x40 ? inptr.decodeNodes() : [op & 0x80 ? inptr.node() : inptr.lastNode];
+            root.before(...nodes);
+            break;
+        }
+        case REMOVE: {
+            const node = op & 0x20 ? inptr.node() : inptr.lastNode;
+            const free = op & 0x40;
+            node.remove();
+            if (free) inptr.freeContained(node);
+            break;
+        }
+        case CREATE_TEXT_NODE: {
+            const text = inptr.str(inptr.decodeU16());
+            const id = op & 0x20 ? inptr.decodeU32() : -1;
+            inptr.lastNode = doc.createTextNode(text);
+            if (id >= 0) inptr.nodes[id] = inptr.lastNode;
+            break;
+        }
+        case CREATE_ELEMENT: {
+            const tag = inptr.createElement();
+            const id = op & 0x20 ? inptr.decodeU32() : -1;
+            inptr.lastNode = tag;
+            if (id >= 0) inptr.nodes[id] = tag;
+            break;
+        }
+        case SET_TEXT: {
+            const text = inptr.str(inptr.decodeU16());
+            const root = op & 0x20 ? inptr.node() : inptr.lastNode;
+            root.textContent = text;
+            break;
+        }
+        case SET_ATTRIBUTE: {
+            const attr = op & 0x20 ? inptr.str(inptr.decodeU16()) : attrs[inptr.view.getUint8(inptr.u8BufPos++)];
+            const attrNs = op & 0x40 ? inptr.str(inptr.decodeU16()) : null;
+            const value = inptr.str(inptr.decodeU16());
+            const root = op & 0x80 ? inptr.node() : inptr.lastNode;
+            if (attrNs === null) root.setAttribute(attr, value);
+            else root.setAttributeNS(attrNs, attr, value);
+            break;
+        }
+        case REMOVE_ATTRIBUTE: {
+            const attr = op & 0x20 ? inptr.str(inptr.decodeU16()) : attrs[inptr.view.getUint8(inptr.u8BufPos++)];
+            const attrNs = op & 0x40 ? inptr.str(inptr.decodeU16()) : null;
+            const root = op & 0x80 ? inptr.node() : inptr.lastNode;
+            if (attrNs === null) root.removeAttribute(attr);
+            else root.removeAttributeNS(attrNs, attr);
+            break;
+        }
+        case SET_STYLE: {
+            const style = inptr.styleName();
+            const value = inptr.str(inptr.decodeU16());
+            const important = op & 0x20;
+            const root = op & 0x40 ? inptr.node() : inptr.lastNode;
+            root.style.setProperty(style, value, important ? "important" : "");
+            break;
+        }
+        case REMOVE_STYLE: {
+            const style = inptr.styleName();
+            const root = op & 0x20 ? inptr.node() : inptr.lastNode;
+            root.style.removeProperty(style);
+            break;
+        }
+        case CLONE_NODE: {
+            const node = op & 0x20 ? inptr.node() : inptr.lastNode;
+            const id = op & 0x40 ? inptr.decodeU32() : -1;
+            inptr.lastNode = node.cloneNode(true);
+            if (id >= 0) inptr.nodes[id] = inptr.lastNode;
+            break;
+        }
+        case NO_OP:
+            break;
+        case SET_INNER_HTML: {
+            const html = inptr.str(inptr.decodeU32());
+            const root = op & 0x20 ? inptr.node() : inptr.lastNode;
+            const id = op & 0x40 ? inptr.decodeU32() : -1;
+            root.innerHTML = html;
+            if (id >= 0) inptr.nodes[id] = root.firstChild;
+            break;
+        }
+        case INSERT_ADJACENT_HTML: {
+            const position = inptr.view.getUint8(inptr.u8BufPos++);
+            const html = inptr.str(inptr.decodeU32());
+            const root = op & 0x20 ? inptr.node() : inptr.lastNode;
+            const id = op & 0x40 ? inptr.decodeU32() : -1;
+            node = inptr.insertAdjacentHtml(root, position, html);
+            if (id >= 0) inptr.nodes[id] = node;
+            break;
+        }
+        case CREATE_COMMENT: {
+            const text = inptr.str(inptr.decodeU16());
+            const id = op & 0x20 ? inptr.decodeU32() : -1;
+            inptr.lastNode = doc.createComment(text);
+            if (id >= 0) inptr.nodes[id] = inptr.lastNode;
+            break;
+        }
+        case CREATE_FRAGMENT: {
+            const id = op & 0x20 ? inptr.decodeU32() : -1;
+            inptr.lastNode = doc.createDocumentFragment();
+            if (id >= 0) inptr.nodes[id] = inptr.lastNode;
+            break;
+        }
+        case CREATE_TEMPLATE: {
+            const id = inptr.decodeU32();
+            const roots = inptr.decodeRoots();
+            inptr.templates[id] = doc.createElement("template");
+            inptr.templates[id].content.append(...roots);
+            break;
+        }
+        case CLONE_TEMPLATE: {
+            const template = inptr.decodeU32();
+            const id = op & 0x20 ? inptr.decodeU32() : -1;
+            inptr.lastNode = doc.importNode(inptr.templates[template].content, true);
+            if (id >= 0) inptr.nodes[id] = inptr.lastNode;
+            break;
+        }
+        case ATTACH_SHADOW: {
+            const root = op & 0x20 ? inptr.node() : inptr.lastNode;
+            const mode = op & 0x40 ? "closed" : "open";
+            const id = inptr.decodeU32();
+            inptr.lastNode = inptr.nodes[id] = root.attachShadow({ mode });
+            inptr.shadowRoots.set(root, inptr.lastNode);
+            break;
+        }
+        case CREATE_STYLE_SHEET: {
+            const css = inptr.str(inptr.decodeU32());
+            const id = inptr.decodeU32();
+            inptr.styleSheets[id] = new (doc.defaultView || window).CSSStyleSheet();
+            inptr.styleSheets[id].replaceSync(css);
+            break;
+        }
+        case ADOPT_STYLE_SHEET: {
+            const root = op & 0x20 ? inptr.node() : inptr.lastNode;
+            const sheet = inptr.decodeU32();
+            root.adoptedStyleSheets = [...root.adoptedStyleSheets, inptr.styleSheets[sheet]];
+            break;
+        }
+        case FREE_IDS: {
+            const ids = inptr.decodeU32();
+            const idsEnd = op & 0x20 ? inptr.decodeU32() : ids + 1;
+            inptr.nodes.fill(undefined, ids, idsEnd);
+            break;
+        }
+        case EXTENDED_OP:
+            return exExtOp();
+    }
+}
+
+// runs the operations encoded as EXTENDED_OP followed by a byte with their code. The bools are still encoded in op
+function exExtOp() {
+    switch (inptr.view.getUint8(inptr.u8BufPos++)) {
+        case PUSH_ROOT: {
+            const root = op & 0x20 ? inptr.node() : inptr.lastNode;
+            inptr.stack.push(root);
+            break;
+        }
+        case APPEND_CHILDREN_FROM_STACK: {
+            const root = op & 0x20 ? inptr.node() : inptr.lastNode;
+            const n = inptr.decodeU16();
+            root.append(...inptr.popStack(n));
+            break;
+        }
+        case REPLACE_WITH_FROM_STACK: {
+            const root = op & 0x20 ? inptr.node() : inptr.lastNode;
+            const n = inptr.decodeU16();
+            root.replaceWith(...inptr.popStack(n));
+            break;
+        }
+        case INSERT_AFTER_FROM_STACK: {
+            const root = op & 0x20 ? inptr.node() : inptr.lastNode;
+            const n = inptr.decodeU16();
+            root.after(...inptr.popStack(n));
+            break;
+        }
+        case INSERT_BEFORE_FROM_STACK: {
+            const root = op & 0x20 ? inptr.node() : inptr.lastNode;
+            const n = inptr.decodeU16();
+            root.before(...inptr.popStack(n));
+            break;
+        }
+        case APPEND_DATA: {
+            const text = inptr.str(inptr.decodeU16());
+            const root = op & 0x20 ? inptr.node() : inptr.lastNode;
+            root.appendData(text);
+            break;
+        }
+        case INSERT_DATA: {
+            const offset = inptr.decodeU32();
+            const text = inptr.str(inptr.decodeU16());
+            const root = op & 0x20 ? inptr.node() : inptr.lastNode;
+            root.insertData(offset, text);
+            break;
+        }
+        case DELETE_DATA: {
+            const offset = inptr.decodeU32();
+            const count = inptr.decodeU32();
+            const root = op & 0x20 ? inptr.node() : inptr.lastNode;
+            root.deleteData(offset, count);
+            break;
+        }
+        case REPLACE_DATA: {
+            const offset = inptr.decodeU32();
+            const count = inptr.decodeU32();
+            const text = inptr.str(inptr.decodeU16());
+            const root = op & 0x20 ? inptr.node() : inptr.lastNode;
+            root.replaceData(offset, count, text);
+            break;
+        }
+        case SET_STYLES: {
+            const styles = inptr.decodeStyles();
+            const root = op & 0x20 ? inptr.node() : inptr.lastNode;
+            for (i = 0; i < styles.length; i += 2) root.style.setProperty(styles[i], styles[i + 1]);
+            break;
+        }
+        case FREE_TEMPLATE: {
+            const template = inptr.decodeU32();
+            inptr.templates[template] = undefined;
+            break;
+        }
+        case FREE_STYLE_SHEET: {
+            const sheet = inptr.decodeU32();
+            inptr.styleSheets[sheet] = undefined;
+            break;
+        }
+    }
+}
+// @generated end
+
+// The checks of the debug interpreter, selected with the `debug-interpreter` feature of the sledgehammer crate.
+// interpreter_debug.js is generated from interpreter.js followed by this file, so the operations are only written once.
+// The debug interpreter runs the same operations, but checks every read from the message against the bounds of the batch,
+// checks that operations never read past the end of the decoded strings, asserts that the last node is defined before it is used,
+// and can log every operation it runs.
+
+// a view of the wasm memory that throws when a read falls outside of the region that is currently being decoded
+class CheckedView extends DataView {
+    // only allow reads in start..end
+    limit(start, end) {
+        this.start = start;
+        this.end = end;
+    }
+
+    check(pos, size) {
+        if (pos < this.start || pos + size > this.end) {
+            throw new RangeError(`read of ${size} bytes at ${pos} is outside of the batch (${this.start}..${this.end})`);
+        }
+    }
+
+    getUint8(pos) {
+        this.check(pos, 1);
+        return super.getUint8(pos);
+    }
+
+    getUint16(pos, littleEndian) {
+        this.check(pos, 2);
+        return super.getUint16(pos, littleEndian);
+    }
+
+    getUint32(pos, littleEndian) {
+        this.check(pos, 4);
+        return super.getUint32(pos, littleEndian);
+    }
+}
+
+export class JsInterpreter extends Interpreter {
+    constructor(mem, _metadata_ptr, _ptr_ptr, _str_ptr_ptr, _str_len_ptr, _doc) {
+        super(mem, _metadata_ptr, _ptr_ptr, _str_ptr_ptr, _str_len_ptr, _doc);
+        // the length of the next message, set by the channel before every batch
+        this.msgLen = 0;
+        // the length of the strings of the batch in bytes
+        this.strLen = 0;
+        // log every operation as it runs
+        this.trace = false;
+    }
+
+    get lastNode() {
+        if (this._lastNode == null) {
+            throw new Error(`the last node is ${this._lastNode}; navigate to or create a node before using it`);
+        }
+        return this._lastNode;
+    }
+
+    set lastNode(node) {
+        this._lastNode = node;
+    }
+
+    SetMsgLen(len) {
+        this.msgLen = len;
+    }
+
+    SetTrace(trace) {
+        this.trace = trace;
+    }
+
+    UpdateMemory(mem) {
+        this.view = new CheckedView(mem.buffer);
+        this.view.limit(0, this.view.byteLength);
+    }
+
+    Work() {
+        // the metadata and the pointers to the batch can be anywhere in memory
+        this.view.limit(0, this.view.byteLength);
+        // this batch may have no strings, so any string read would be past the end
+        this.strings = "";
+        this.strPos = 0;
+        this.strBytes = undefined;
+        this.strByte = 0;
+        this.strLen = 0;
+        return super.Work();
+    }
+
+    decodeStrings() {
+        // strings that end with three bytes are read as a u32, so allow reading one byte past the end
+        this.view.limit(this.last_str_start, this.last_str_start + len + 1);
+        this.strLen = len;
+        super.decodeStrings();
+    }
+
+    str(len) {
+        // the lengths of the strings are in utf-8 bytes, and the position is only kept in bytes if the text has characters longer than a byte
+        const end = (this.strBytes === undefined ? this.strPos : this.strByte) + len;
+        if (end > this.strLen) {
+            throw new RangeError(`read strings up to ${end}, but the batch only has ${this.strLen} bytes of strings`);
+        }
+        return super.str(len);
+    }
+
+    WorkTransferred(msg, str) {
+        // the transferred buffer holds only the message
+        this.msgLen = msg.byteLength;
+        this.strLen = str.byteLength;
+        return super.WorkTransferred(msg, str);
+    }
+
+    runOps() {
+        // some operands are read as a u32 and only some of the bytes are used, so allow reading up to 3 bytes past the end
+        this.view.limit(this.u8BufPos, this.u8BufPos + this.msgLen + 3);
+        opIdx = 0;
+        missingAt = -1;
+        try {
+            for (; ;) {
+                op = this.view.getUint32(this.u8BufPos, true);
+                this.u8BufPos += 4;
+                for (let k = 0; k < 4; k++) {
+                    opStart = this.u8BufPos;
+                    const strStart = this.strPos;
+                    const done = exOp();
+                    if (this.trace) {
+                        console.log(this.disassemble(strStart));
+                    }
+                    if (done) return;
+                    // padding is not counted, so the index matches the operations that were queued
+                    if ((op & 0x1F) !== NO_OP) opIdx++;
+                    op >>>= 8;
+                }
+            }
+        }
+        catch (e) {
+            return this.opError(e);
+        }
+    }
+
+    opError(e) {
+        // the error may have come from reading outside of the batch, so describe it without the bounds checks
+        this.view.limit(0, this.view.byteLength);
+        return super.opError(e);
+    }
+
+    // formats the operation that just ran as "index: name flags operand-bytes strings"
+    disassemble(strStart) {
+        j = op & 0x1F;
+        pos = opStart;
+        if (j === EXTENDED_OP) {
+            j = this.view.getUint8(pos++);
+        }
+        out = `${opIdx}: ${opNames[j] || `unknown(${j})`}`;
+        if (op & 0xE0) {
+            out += ` ${op & 0x20 ? "a" : "-"}${op & 0x40 ? "b" : "-"}${op & 0x80 ? "c" : "-"}`;
+        }
+        for (; pos < this.u8BufPos; pos++) {
+            out += ` ${this.view.getUint8(pos).toString(16).padStart(2, "0")}`;
+        }
+        if (this.strPos > strStart) {
+            out += ` ${JSON.stringify(this.strings.substring(strStart, this.strPos))}`;
+        }
+        return out;
+    }
+}
@@ -1 +1,1204 @@
-let e,t,s,r,i,u,a,o,l,h,c,d,b,p,f,m,v,g,k;export function work_last_created(){b.Work()}export function update_last_memory(e){b.UpdateMemory(e)}function y(){switch(e&31){case 0:b.l=b.l.firstChild;break;case 1:b.l=b.l.nextSibling;break;case 2:b.l=b.l.parentNode;break;case 3:b.n[b.v.u32(b.u,true)]=b.l;b.u+=4;break;case 4:b.l=b.n[b.v.u32(b.u,true)];b.u+=4;break;case 5:return true;case 6:b.l=b.createFullElement();break;case 7:if(e&32){f=b.n[b.v.u32(b.u,true)];b.u+=4}else{f=b.l}if(e&64){f.appendChild(b.n[b.v.u32(b.u,true)]);b.u+=4}else{f.appendChild(b.l)}break;case 8:if(e&64){f=b.n[b.v.u32(b.u,true)];b.u+=4}else{f=b.l}if(e&32){m=b.v.u8(b.u++,true);k=[];for(i=0;i<m;i++){if(b.v.u8(b.u++,true)){k.push(b.n[b.v.u32(b.u,true)]);b.u+=4}else{k.push(b.l)}}f.replaceWith(...n)}else{if(e&128){f.replaceWith(b.n[b.v.u32(b.u,true)]);b.u+=4}else{f.replaceWith(b.l)}}break;case 9:if(e&64){f=b.n[b.v.u32(b.u,true)];b.u+=4}else{f=b.l}if(e&32){m=b.v.u8(b.u++,true);k=[];for(i=0;i<m;i++){if(b.v.u8(b.u++,true)){k.push(b.n[b.v.u32(b.u,true)]);b.u+=4}else{k.push(b.l)}}f.after(...n)}else{if(e&128){f.after(b.n[b.v.u32(b.u,true)]);b.u+=4}else{f.after(b.l)}}break;case 10:if(e&64){f=b.n[b.v.u32(b.u,true)];b.u+=4}else{f=b.l}if(e&32){m=b.v.u8(b.u++,true);k=[];for(i=0;i<m;i++){if(b.v.u8(b.u++,true)){k.push(b.n[b.v.u32(b.u,true)]);b.u+=4}else{k.push(b.l)}}f.before(...n)}else{if(e&128){f.before(b.n[b.v.u32(b.u,true)]);b.u+=4}else{f.before(b.l)}}break;case 11:if(e&32){b.n[b.v.u32(b.u,true)].remove();b.u+=4}else{b.l.remove()}break;case 12:b.l=document.createTextNode(b.s.substring(b.o,b.o+=b.v.u16(b.u,true)));b.u+=2;if(e&32){b.n[b.v.u32(b.u,true)]=b.l;b.u+=4}break;case 13:b.l=b.createElement();if(e&32){b.n[b.v.u32(b.u,true)]=b.l;b.u+=4}break;case 14:if(e&32){g=b.v.u32(b.u,true);b.u+=4;b.n[g].textContent=b.s.substring(b.o,b.o+=b.v.u16(b.u,true));b.u+=2}else{b.l.textContent=b.s.substring(b.o,b.o+=b.v.u16(b.u,true));b.u+=2}break;case 15:if(e&32){v=b.n[b.v.u32(b.u,true)];b.u+=4}else{v=b.l}if(e&64){i=b.v.u32(b.u,true);b.u+=4;r=b.s.substring(b.o,b.o+=i&65535);if(e&128){v.setAttributeNS(b.s.substring(b.o,b.o+=(i&4294901760)>>>16),r,b.s.substring(b.o,b.o+=b.v.u16(b.u,true)));b.u+=2}else{v.setAttribute(r,b.s.substring(b.o,b.o+=(i&4294901760)>>>16))}}else{i=b.v.u32(b.u,true);b.u+=3;if(e&128){s=b.s.substring(b.o,b.o+=i&65535);v.setAttributeNS(s,x[(i&16711680)>>>16],b.s.substring(b.o,b.o+=b.v.u16(b.u,true)));b.u+=2}else{v.setAttribute(x[i&255],b.s.substring(b.o,b.o+=(i&16776960)>>>8))}}break;case 16:if(e&32){v=b.n[b.v.u32(b.u,true)];b.u+=4}else{v=b.l}if(e&64){if(e&128){i=b.v.u32(b.u,true);b.u+=4;r=b.s.substring(b.o,b.o+=i&65535);v.removeAttributeNS(b.s.substring(b.o,b.o+=(i&4294901760)>>>16),r)}else{v.removeAttribute(b.s.substring(b.o,b.o+=b.v.u16(b.u,true)));b.u+=2}}else{if(e&128){i=b.v.u32(b.u,true);b.u+=3;r=x[i&255];v.removeAttributeNS(b.s.substring(b.o,b.o+=(i&16776960)>>>8),r)}else{v.removeAttribute(x[b.v.u8(b.u++)])}}break;case 17:if(e&32){v=b.n[b.v.u32(b.u,true)];b.u+=4}else{v=b.l}i=b.v.u32(b.u,true);b.u+=4;v.style.setProperty(b.s.substring(b.o,b.o+=i&65535),b.s.substring(b.o,b.o+=(i&4294901760)>>>16));break;case 18:if(e&32){v=b.n[b.v.u32(b.u,true)];b.u+=4}else{v=b.l}v.style.removeProperty(b.s.substring(b.o,b.o+=b.v.u16(b.u,true)));b.u+=2;break;case 19:if(e&32){b.l=b.n[b.v.u32(b.u,true)].cloneNode(true);b.u+=4}else{b.l=b.l.cloneNode(true)}if(e&64){b.n[b.v.u32(b.u,true)]=b.l;b.u+=4}break;default:break}}export class JsInterpreter{constructor(e,t,s,r,i){this.l;this.n=[];this.p=[];this.UpdateMemory(e);this.lp;this.ls;this.m=t;this.pt=s;this.sp=r;this.sl=i;this.s="";this.o=0;this.d=new TextDecoder;this.i=1;b=this}NeedsMemory(){return this.v.buffer.byteLength===0}UpdateMemory(e){this.v=new DataView(e.buffer);this.v.u32=this.v.getUint32;this.v.u16=this.v.getUint16;this.v.u8=this.v.getUint8}Work(){p=this.v.u8(this.m);if(p&1){this.lp=this.v.u32(this.pt,true)}this.u=this.lp;if(p&4){t=this.v.u32(this.sl,true);if(p&2){this.ls=this.v.u32(this.sp,true)}if(p&8){l=this.ls;this.s="";d=l+(t/4|0)*4;while(l<d){h=this.v.u32(l);this.s+=String.fromCharCode(h>>24,(h&16711680)>>16,(h&65280)>>8,h&255);l+=4}switch(this.ls+t-l){case 3:h=this.v.u32(l);this.s+=String.fromCharCode(h>>24,(h&16711680)>>16,(h&65280)>>8);break;case 2:h=this.v.u16(l);this.s+=String.fromCharCode(h>>8,h&255);break;case 1:this.s+=String.fromCharCode(this.v.u8(l));break;case 0:break}}else{this.s=this.d.decode(new DataView(this.v.buffer,this.ls,t))}this.o=0}for(;;){e=this.v.u32(this.u,true);this.u+=4;if(y())return;e>>>=8;if(y())return;e>>>=8;if(y())return;e>>>=8;if(y())return}}createElement(){u=this.v.u32(this.u,true);o=u&255;switch(o){case 255:this.u+=4;o=document.createElement(w[(u&65280)>>>8],this.s.substring(this.o,this.o+=(u&4294901760)>>>16));return o;case 254:this.u+=3;o=document.createElement(this.s.substring(this.o,this.o+=(u&16776960)>>>8));return o;case 253:this.u+=3;o=this.s.substring(this.o,this.o+=(u&16776960)>>>8);o=document.createElementNS(this.s.substring(this.o,this.o+=this.v.u16(this.u,true)),o);this.u+=2;return o;default:this.u++;return document.createElement(w[o])}}createFullElement(){let e;u=this.v.u8(this.u++);if(u&1){e=this.v.u32(this.u,true);this.u+=4}if(u&2){v=document.createTextNode(this.s.substring(this.o,this.o+=this.v.u16(this.u,true)));this.u+=2;if(e!==null){this.n[e]=v}return v}else{const t=this.createElement();u=this.v.u16(this.u,true);this.u+=2;c=u&255;const o=(u&65280)>>>8;for(i=0;i<c;i++){u=this.v.u32(this.u,true);r=u&255;switch(r){case 255:this.u+=4;r=x[this.v.u8((u&65280)>>>8)];t.setAttributeNS(this.s.substring(this.o,this.o+=(u&4294901760)>>>16),r);break;case 254:this.u++;u=this.v.u32(this.u,true);this.u+=4;r=this.s.substring(this.o,this.o+=u&65535);t.setAttribute(r,this.s.substring(this.o,this.o+=(u&4294901760)>>>16));break;case 253:this.u+=3;r=this.s.substring(this.o,this.o+=(u&16776960)>>>8);u=this.v.u32(this.u,true);this.u+=4;s=this.s.substring(this.o,this.o+=u&65535);a=this.s.substring(this.o,this.o+=(u&4294901760)>>>16);t.setAttributeNS(s,r,a);break;default:this.u+=3;t.setAttribute(x[r],this.s.substring(this.o,this.o+=(u&16776960)>>>8));break}}for(let l=0;l<o;l++){t.appendChild(this.createFullElement())}if(e!==null){this.n[e]=t}return t}}decodeU32(){this.u+=4;return this.v.u32(this.u-4,true)}SetNode(e,t){this.n[e]=t}GetNode(e){return this.n[e]}}const w=["a","abbr","acronym","address","applet","area","article","aside","audio","b","base","bdi","bdo","bgsound","big","blink","blockquote","body","br","button","canvas","caption","center","cite","code","col","colgroup","content","data","datalist","dd","del","details","dfn","dialog","dir","div","dl","dt","em","embed","fieldset","figcaption","figure","font","footer","form","frame","frameset","h1","head","header","hgroup","hr","html","i","iframe","image","img","input","ins","kbd","keygen","label","legend","li","link","main","map","mark","marquee","menu","menuitem","meta","meter","nav","nobr","noembed","noframes","noscript","object","ol","optgroup","option","output","p","param","picture","plaintext","portal","pre","progress","q","rb","rp","rt","rtc","ruby","s","samp","script","section","select","shadow","slot","small","source","spacer","span","strike","strong","style","sub","summary","sup","table","tbody","td","template","textarea","tfoot","th","thead","time","title","tr","track","tt","u","ul","var","video","wbr","xmp"];const x=["accept-charset","accept","accesskey","action","align","allow","alt","aria-atomic","aria-busy","aria-controls","aria-current","aria-describedby","aria-description","aria-details","aria-disabled","aria-dropeffect","aria-errormessage","aria-flowto","aria-grabbed","aria-haspopup","aria-hidden","aria-invalid","aria-keyshortcuts","aria-label","aria-labelledby","aria-live","aria-owns","aria-relevant","aria-roledescription","async","autocapitalize","autocomplete","autofocus","autoplay","background","bgcolor","border","buffered","capture","challenge","charset","checked","cite","class","code","codebase","color","cols","colspan","content","contenteditable","contextmenu","controls","coords","crossorigin","csp","data","datetime","decoding","default","defer","dir","dirname","disabled","download","draggable","enctype","enterkeyhint","for","form","formaction","formenctype","formmethod","formnovalidate","formtarget","headers","height","hidden","high","href","hreflang","http-equiv","icon","id","importance","inputmode","integrity","intrinsicsize","ismap","itemprop","keytype","kind","label","lang","language","list","loading","loop","low","manifest","max","maxlength","media","method","min","minlength","multiple","muted","name","novalidate","open","optimum","pattern","ping","placeholder","poster","preload","radiogroup","readonly","referrerpolicy","rel","required","reversed","role","rows","rowspan","sandbox","scope","scoped","selected","shape","size","sizes","slot","span","spellcheck","src","srcdoc","srclang","srcset","start","step","style","summary","tabindex","target","title","translate","type","usemap","value","width","wrap"];
\ No newline at end of file
+// @generated from interpreter.js by sledgehammer-encoder, do not edit by hand
+let op, len, ns, attr, i, j, value, element, pos, out, char, numAttributes, endRounded, inptr, doc, metadata, parent, node, id, nodes, opIdx, opStart, missingId, missingAt;
+export class JsInterpreter {
+constructor(mem, _metadata_ptr, _ptr_ptr, _str_ptr_ptr, _str_len_ptr, _doc) {
+this.l = undefined;
+this.n = [];
+this.t = [];
+this.ss = [];
+this.sr = new WeakMap();
+this.p = [];
+this.UpdateMemory(mem);
+this.lp;
+this.ls;
+this.m = _metadata_ptr;
+this.pt = _ptr_ptr;
+this.sp = _str_ptr_ptr;
+this.sl = _str_len_ptr;
+this.s = "";
+this.o = 0;
+this.sb = undefined;
+this.ob = 0;
+this.d = new TextDecoder();
+this.i = 1;
+this.doc = _doc || document;
+}
+NeedsMemory() {
+return this.v.buffer.byteLength === 0;
+}
+UpdateMemory(mem) {
+this.v = new DataView(mem.buffer);
+}
+Work() {
+inptr = this;
+doc = this.doc;
+metadata = this.v.getUint8(this.m);
+if (metadata & 0x01) {
+this.lp = this.v.getUint32(this.pt, true);
+}
+this.u = this.lp;
+if (metadata & 0x04) {
+len = this.v.getUint32(this.sl, true);
+if (metadata & 0x02) {
+this.ls = this.v.getUint32(this.sp, true);
+}
+this.decodeStrings();
+this.o = 0;
+this.ob = 0;
+}
+return this.runOps();
+}
+decodeStrings() {
+if (metadata & 0x08) {
+this.sb = undefined;
+pos = this.ls;
+this.s = "";
+endRounded = pos + ((len / 4) | 0) * 4;
+while (pos < endRounded) {
+char = this.v.getUint32(pos);
+this.s += String.fromCharCode(char >> 24, (char & 0x00FF0000) >> 16, (char & 0x0000FF00) >> 8, (char & 0x000000FF));
+pos += 4;
+}
+switch (this.ls + len - pos) {
+case 3:
+char = this.v.getUint32(pos);
+this.s += String.fromCharCode(char >> 24, (char & 0x00FF0000) >> 16, (char & 0x0000FF00) >> 8);
+break;
+case 2:
+char = this.v.getUint16(pos);
+this.s += String.fromCharCode(char >> 8, char & 0xFF);
+break;
+case 1:
+this.s += String.fromCharCode(this.v.getUint8(pos));
+break;
+case 0:
+break;
+}
+}
+else {
+this.s = this.d.decode(new DataView(this.v.buffer, this.ls, len));
+this.sb = this.s.length === len ? undefined : new Uint8Array(this.v.buffer, this.ls, len);
+}
+}
+str(len) {
+if (this.sb === undefined) {
+return this.s.substring(this.o, this.o += len);
+}
+let units = 0;
+const end = this.ob + len;
+for (let b = this.ob; b < end; b++) {
+const byte = this.sb[b];
+if ((byte & 0xC0) !== 0x80) units += byte >= 0xF0 ? 2 : 1;
+}
+this.ob = end;
+return this.s.substring(this.o, this.o += units);
+}
+WorkTransferred(msg, str) {
+inptr = this;
+doc = this.doc;
+const view = this.v;
+this.UpdateMemory({ buffer: msg });
+this.u = 0;
+this.s = this.d.decode(str);
+this.sb = this.s.length === str.byteLength ? undefined : new Uint8Array(str);
+this.o = 0;
+this.ob = 0;
+i = this.runOps();
+this.v = view;
+return i;
+}
+runOps() {
+opIdx = 0;
+missingAt = -1;
+try {
+for (; ;) {
+op = this.v.getUint32(this.u, true);
+this.u += 4;
+opStart = this.u;
+if (exOp()) return;
+if ((op & 0x1F) !== 20) opIdx++;
+op >>>= 8;
+opStart = this.u;
+if (exOp()) return;
+if ((op & 0x1F) !== 20) opIdx++;
+op >>>= 8;
+opStart = this.u;
+if (exOp()) return;
+if ((op & 0x1F) !== 20) opIdx++;
+op >>>= 8;
+opStart = this.u;
+if (exOp()) return;
+if ((op & 0x1F) !== 20) opIdx++;
+}
+}
+catch (e) {
+return this.opError(e);
+}
+}
+opError(e) {
+j = op & 0x1F;
+pos = opStart;
+if (j === 31) {
+j = this.v.getUint8(pos++);
+}
+id = missingAt === opStart ? missingId : undefined;
+return [opIdx, j, id, String(e && e.message || e)];
+}
+createElement() {
+j = this.v.getUint32(this.u, true);
+element = j & 0xFF;
+switch (element) {
+case 255:
+this.u += 4;
+element = doc.createElement(els[(j & 0xFF00) >>> 8], this.str((j & 0xFFFF0000) >>> 16));
+return element;
+case 254:
+this.u += 3;
+element = doc.createElement(this.str((j & 0xFFFF00) >>> 8));
+return element;
+case 253:
+this.u += 3;
+element = this.str((j & 0xFFFF00) >>> 8);
+element = doc.createElementNS(this.str(this.v.getUint16(this.u, true)), element);
+this.u += 2;
+return element;
+case 252:
+this.u += 2;
+return doc.createElementNS(svgNs, svgEls[(j & 0xFF00) >>> 8]);
+case 251:
+this.u += 2;
+return doc.createElementNS(mathMlNs, mathMlEls[(j & 0xFF00) >>> 8]);
+default:
+this.u++;
+return doc.createElement(els[element]);
+}
+}
+createFullElement() {
+let parent_id;
+j = this.v.getUint8(this.u++);
+if (j & 0x1) {
+parent_id = this.v.getUint32(this.u, true);
+this.u += 4;
+}
+if (j & 0x2) {
+node = doc.createTextNode(this.str(this.v.getUint16(this.u, true)));
+this.u += 2;
+if (parent_id !== null) {
+this.n[parent_id] = node;
+}
+return node;
+}
+else if (j & 0x4) {
+node = doc.createComment(this.str(this.v.getUint16(this.u, true)));
+this.u += 2;
+if (j & 0x1) {
+this.n[parent_id] = node;
+}
+return node;
+}
+else {
+const parent_element = this.createElement();
+j = this.v.getUint16(this.u, true);
+this.u += 2;
+numAttributes = j & 0xFF;
+const numChildren = (j & 0xFF00) >>> 8;
+for (i = 0; i < numAttributes; i++) {
+j = this.v.getUint32(this.u, true);
+attr = j & 0xFF;
+switch (attr) {
+case 255:
+this.u += 4;
+attr = attrs[this.v.getUint8((j & 0xFF00) >>> 8)];
+parent_element.setAttributeNS(this.str((j & 0xFFFF0000) >>> 16), attr);
+break;
+case 254:
+this.u++;
+j = this.v.getUint32(this.u, true);
+this.u += 4;
+attr = this.str(j & 0xFFFF);
+parent_element.setAttribute(attr, this.str((j & 0xFFFF0000) >>> 16));
+break;
+case 253:
+this.u += 3;
+attr = this.str((j & 0xFFFF00) >>> 8);
+j = this.v.getUint32(this.u, true);
+this.u += 4;
+ns = this.str(j & 0xFFFF);
+value = this.str((j & 0xFFFF0000) >>> 16);
+parent_element.setAttributeNS(ns, attr, value);
+break;
+default:
+this.u += 3;
+parent_element.setAttribute(attrs[attr], this.str((j & 0xFFFF00) >>> 8));
+break;
+}
+}
+for (let w = 0; w < numChildren; w++) {
+parent_element.appendChild(this.createFullElement());
+}
+if (parent_id !== null) {
+this.n[parent_id] = parent_element;
+}
+return parent_element;
+}
+}
+decodeRoots() {
+const roots = [];
+for (let w = this.v.getUint8(this.u++); w > 0; w--) {
+roots.push(this.createFullElement());
+}
+return roots;
+}
+freeContained(root) {
+const removed = new Set();
+const stack = [root];
+while (stack.length) {
+const next = stack.pop();
+removed.add(next);
+for (let c = 0; c < next.childNodes.length; c++) stack.push(next.childNodes[c]);
+const shadowRoot = this.sr.get(next);
+if (shadowRoot) stack.push(shadowRoot);
+}
+for (let w = 0; w < this.n.length; w++) {
+if (removed.has(this.n[w])) this.n[w] = undefined;
+}
+}
+popStack(n) {
+if (n > this.p.length) {
+throw new Error("popped " + n + " nodes, but the stack only has " + this.p.length + " nodes");
+}
+return this.p.splice(this.p.length - n);
+}
+insertAdjacentHtml(root, position, html) {
+switch (position) {
+case 0:
+parent = root.previousSibling;
+element = root;
+break;
+case 1:
+parent = null;
+element = root.firstChild;
+break;
+case 2:
+parent = root.lastChild;
+element = null;
+break;
+case 3:
+parent = root;
+element = root.nextSibling;
+break;
+}
+root.insertAdjacentHTML(positions[position], html);
+parent = parent ? parent.nextSibling : (position === 0 ? root.parentNode : root).firstChild;
+return parent === element ? undefined : parent;
+}
+decodeNodes() {
+nodes = [];
+for (let w = this.v.getUint8(this.u++); w > 0; w--) {
+nodes.push(this.v.getUint8(this.u++) ? this.node() : this.l);
+}
+return nodes;
+}
+node() {
+id = this.decodeU32();
+node = this.n[id];
+if (node === undefined) {
+missingId = id;
+missingAt = opStart;
+}
+return node;
+}
+styleName() {
+j = this.v.getUint8(this.u++);
+switch (j) {
+case 255:
+j = this.v.getUint16(this.u, true);
+this.u += 2;
+return this.str(j);
+case 254:
+j = this.v.getUint16(this.u, true);
+this.u += 2;
+return "--" + this.str(j);
+default:
+return styles[j];
+}
+}
+decodeStyles() {
+const values = [];
+for (let w = this.decodeU16(); w > 0; w--) {
+values.push(this.styleName(), this.str(this.decodeU16()));
+}
+return values;
+}
+decodeU32() {
+this.u += 4;
+return this.v.getUint32(this.u - 4, true);
+}
+decodeU16() {
+this.u += 2;
+return this.v.getUint16(this.u - 2, true);
+}
+SetNode(id, node) {
+this.n[id] = node;
+}
+GetNode(id) {
+return this.n[id];
+}
+FormatVersion() {
+return FORMAT_VERSION;
+}
+}
+const FORMAT_VERSION = 3;
+const positions = [
+"beforebegin",
+"afterbegin",
+"beforeend",
+"afterend",
+];
+const styles = [
+"align-content",
+"align-items",
+"align-self",
+"background",
+"background-color",
+"background-image",
+"background-position",
+"background-repeat",
+"background-size",
+"border",
+"border-bottom",
+"border-color",
+"border-left",
+"border-radius",
+"border-right",
+"border-style",
+"border-top",
+"border-width",
+"bottom",
+"box-shadow",
+"box-sizing",
+"color",
+"cursor",
+"display",
+"flex",
+"flex-basis",
+"flex-direction",
+"flex-grow",
+"flex-shrink",
+"flex-wrap",
+"float",
+"font",
+"font-family",
+"font-size",
+"font-style",
+"font-weight",
+"gap",
+"grid-area",
+"grid-column",
+"grid-row",
+"grid-template-areas",
+"grid-template-columns",
+"grid-template-rows",
+"height",
+"justify-content",
+"justify-items",
+"justify-self",
+"left",
+"letter-spacing",
+"line-height",
+"margin",
+"margin-bottom",
+"margin-left",
+"margin-right",
+"margin-top",
+"max-height",
+"max-width",
+"min-height",
+"min-width",
+"opacity",
+"outline",
+"overflow",
+"overflow-x",
+"overflow-y",
+"padding",
+"padding-bottom",
+"padding-left",
+"padding-right",
+"padding-top",
+"pointer-events",
+"position",
+"right",
+"text-align",
+"text-decoration",
+"text-overflow",
+"text-transform",
+"top",
+"transform",
+"transform-origin",
+"transition",
+"user-select",
+"vertical-align",
+"visibility",
+"white-space",
+"width",
+"word-break",
+"z-index",
+];
+const els = [
+"a",
+"abbr",
+"acronym",
+"address",
+"applet",
+"area",
+"article",
+"aside",
+"audio",
+"b",
+"base",
+"bdi",
+"bdo",
+"bgsound",
+"big",
+"blink",
+"blockquote",
+"body",
+"br",
+"button",
+"canvas",
+"caption",
+"center",
+"cite",
+"code",
+"col",
+"colgroup",
+"content",
+"data",
+"datalist",
+"dd",
+"del",
+"details",
+"dfn",
+"dialog",
+"dir",
+"div",
+"dl",
+"dt",
+"em",
+"embed",
+"fieldset",
+"figcaption",
+"figure",
+"font",
+"footer",
+"form",
+"frame",
+"frameset",
+"h1",
+"head",
+"header",
+"hgroup",
+"hr",
+"html",
+"i",
+"iframe",
+"image",
+"img",
+"input",
+"ins",
+"kbd",
+"keygen",
+"label",
+"legend",
+"li",
+"link",
+"main",
+"map",
+"mark",
+"marquee",
+"menu",
+"menuitem",
+"meta",
+"meter",
+"nav",
+"nobr",
+"noembed",
+"noframes",
+"noscript",
+"object",
+"ol",
+"optgroup",
+"option",
+"output",
+"p",
+"param",
+"picture",
+"plaintext",
+"portal",
+"pre",
+"progress",
+"q",
+"rb",
+"rp",
+"rt",
+"rtc",
+"ruby",
+"s",
+"samp",
+"script",
+"section",
+"select",
+"shadow",
+"slot",
+"small",
+"source",
+"spacer",
+"span",
+"strike",
+"strong",
+"style",
+"sub",
+"summary",
+"sup",
+"table",
+"tbody",
+"td",
+"template",
+"textarea",
+"tfoot",
+"th",
+"thead",
+"time",
+"title",
+"tr",
+"track",
+"tt",
+"u",
+"ul",
+"var",
+"video",
+"wbr",
+"xmp",
+];
+const svgNs = "http://www.w3.org/2000/svg";
+const mathMlNs = "http://www.w3.org/1998/Math/MathML";
+const svgEls = [
+"a",
+"animate",
+"animateMotion",
+"animateTransform",
+"circle",
+"clipPath",
+"defs",
+"desc",
+"ellipse",
+"feBlend",
+"feColorMatrix",
+"feComponentTransfer",
+"feComposite",
+"feConvolveMatrix",
+"feDiffuseLighting",
+"feDisplacementMap",
+"feDistantLight",
+"feDropShadow",
+"feFlood",
+"feFuncA",
+"feFuncB",
+"feFuncG",
+"feFuncR",
+"feGaussianBlur",
+"feImage",
+"feMerge",
+"feMergeNode",
+"feMorphology",
+"feOffset",
+"fePointLight",
+"feSpecularLighting",
+"feSpotLight",
+"feTile",
+"feTurbulence",
+"filter",
+"foreignObject",
+"g",
+"image",
+"line",
+"linearGradient",
+"marker",
+"mask",
+"metadata",
+"mpath",
+"path",
+"pattern",
+"polygon",
+"polyline",
+"radialGradient",
+"rect",
+"script",
+"set",
+"stop",
+"style",
+"svg",
+"switch",
+"symbol",
+"text",
+"textPath",
+"title",
+"tspan",
+"use",
+"view",
+];
+const mathMlEls = [
+"annotation",
+"annotation-xml",
+"math",
+"merror",
+"mfrac",
+"mi",
+"mmultiscripts",
+"mn",
+"mo",
+"mover",
+"mpadded",
+"mphantom",
+"mprescripts",
+"mroot",
+"mrow",
+"ms",
+"mspace",
+"msqrt",
+"mstyle",
+"msub",
+"msubsup",
+"msup",
+"mtable",
+"mtd",
+"mtext",
+"mtr",
+"munder",
+"munderover",
+"semantics",
+];
+const attrs = [
+"accept-charset",
+"accept",
+"accesskey",
+"action",
+"align",
+"allow",
+"alt",
+"aria-atomic",
+"aria-busy",
+"aria-controls",
+"aria-current",
+"aria-describedby",
+"aria-description",
+"aria-details",
+"aria-disabled",
+"aria-dropeffect",
+"aria-errormessage",
+"aria-flowto",
+"aria-grabbed",
+"aria-haspopup",
+"aria-hidden",
+"aria-invalid",
+"aria-keyshortcuts",
+"aria-label",
+"aria-labelledby",
+"aria-live",
+"aria-owns",
+"aria-relevant",
+"aria-roledescription",
+"async",
+"autocapitalize",
+"autocomplete",
+"autofocus",
+"autoplay",
+"background",
+"bgcolor",
+"border",
+"buffered",
+"capture",
+"challenge",
+"charset",
+"checked",
+"cite",
+"class",
+"code",
+"codebase",
+"color",
+"cols",
+"colspan",
+"content",
+"contenteditable",
+"contextmenu",
+"controls",
+"coords",
+"crossorigin",
+"csp",
+"data",
+"datetime",
+"decoding",
+"default",
+"defer",
+"dir",
+"dirname",
+"disabled",
+"download",
+"draggable",
+"enctype",
+"enterkeyhint",
+"for",
+"form",
+"formaction",
+"formenctype",
+"formmethod",
+"formnovalidate",
+"formtarget",
+"headers",
+"height",
+"hidden",
+"high",
+"href",
+"hreflang",
+"http-equiv",
+"icon",
+"id",
+"importance",
+"inputmode",
+"integrity",
+"intrinsicsize",
+"ismap",
+"itemprop",
+"keytype",
+"kind",
+"label",
+"lang",
+"language",
+"list",
+"loading",
+"loop",
+"low",
+"manifest",
+"max",
+"maxlength",
+"media",
+"method",
+"min",
+"minlength",
+"multiple",
+"muted",
+"name",
+"novalidate",
+"open",
+"optimum",
+"pattern",
+"ping",
+"placeholder",
+"poster",
+"preload",
+"radiogroup",
+"readonly",
+"referrerpolicy",
+"rel",
+"required",
+"reversed",
+"role",
+"rows",
+"rowspan",
+"sandbox",
+"scope",
+"scoped",
+"selected",
+"shape",
+"size",
+"sizes",
+"slot",
+"span",
+"spellcheck",
+"src",
+"srcdoc",
+"srclang",
+"srcset",
+"start",
+"step",
+"style",
+"summary",
+"tabindex",
+"target",
+"title",
+"translate",
+"type",
+"usemap",
+"value",
+"width",
+"wrap",
+"attributeName",
+"begin",
+"clip-path",
+"clipPathUnits",
+"clip-rule",
+"cx",
+"cy",
+"d",
+"dominant-baseline",
+"dur",
+"dx",
+"dy",
+"fill",
+"fill-opacity",
+"fill-rule",
+"filter",
+"filterUnits",
+"fr",
+"from",
+"fx",
+"fy",
+"gradientTransform",
+"gradientUnits",
+"in",
+"in2",
+"lengthAdjust",
+"marker-end",
+"markerHeight",
+"marker-mid",
+"marker-start",
+"markerUnits",
+"markerWidth",
+"mask",
+"maskContentUnits",
+"maskUnits",
+"opacity",
+"orient",
+"pathLength",
+"patternContentUnits",
+"patternTransform",
+"patternUnits",
+"points",
+"preserveAspectRatio",
+"r",
+"refX",
+"refY",
+"repeatCount",
+"result",
+"rx",
+"ry",
+"spreadMethod",
+"startOffset",
+"stdDeviation",
+"stop-color",
+"stop-opacity",
+"stroke",
+"stroke-dasharray",
+"stroke-dashoffset",
+"stroke-linecap",
+"stroke-linejoin",
+"stroke-miterlimit",
+"stroke-opacity",
+"stroke-width",
+"text-anchor",
+"textLength",
+"to",
+"transform",
+"values",
+"vector-effect",
+"viewBox",
+"x",
+"x1",
+"x2",
+"y",
+"y1",
+"y2",
+"accent",
+"accentunder",
+"columnspan",
+"display",
+"displaystyle",
+"fence",
+"largeop",
+"linethickness",
+"lspace",
+"mathbackground",
+"mathcolor",
+"mathsize",
+"mathvariant",
+"maxsize",
+"minsize",
+"movablelimits",
+"rspace",
+"scriptlevel",
+"separator",
+"stretchy",
+"symmetric",
+"voffset",
+];
+function exOp() {
+switch (op & 0x1F) {
+case 0:
+inptr.l = inptr.l.firstChild;
+break;
+case 1:
+inptr.l = inptr.l.nextSibling;
+break;
+case 2:
+inptr.l = inptr.l.parentNode;
+break;
+case 3: {
+const id = inptr.decodeU32();
+inptr.n[id] = inptr.l;
+break;
+}
+case 4: {
+const id = inptr.decodeU32();
+inptr.l = inptr.n[id];
+break;
+}
+case 5:
+return true;
+case 6: {
+const el = inptr.createFullElement();
+inptr.l = el;
+break;
+}
+case 7: {
+const root = op & 0x20 ? inptr.node() : inptr.l;
+const child = op & 0x40 ? inptr.node() : inptr.l;
+root.appendChild(child);
+break;
+}
+case 8: {
+const root = op & 0x20 ? inptr.node() : inptr.l;
+const nodes = op & 0x40 ? inptr.decodeNodes() : [op & 0x80 ? inptr.node() : inptr.l];
+root.replaceWith(...nodes);
+break;
+}
+case 9: {
+const root = op & 0x20 ? inptr.node() : inptr.l;
+const nodes = op & 0x40 ? inptr.decodeNodes() : [op & 0x80 ? inptr.node() : inptr.l];
+root.after(...nodes);
+break;
+}
+case 10: {
+const root = op & 0x20 ? inptr.node() : inptr.l;
+const nodes = op & 0x40 ? inptr.decodeNodes() : [op & 0x80 ? inptr.node() : inptr.l];
+root.before(...nodes);
+break;
+}
+case 11: {
+const node = op & 0x20 ? inptr.node() : inptr.l;
+const free = op & 0x40;
+node.remove();
+if (free) inptr.freeContained(node);
+break;
+}
+case 12: {
+const text = inptr.str(inptr.decodeU16());
+const id = op & 0x20 ? inptr.decodeU32() : -1;
+inptr.l = doc.createTextNode(text);
+if (id >= 0) inptr.n[id] = inptr.l;
+break;
+}
+case 13: {
+const tag = inptr.createElement();
+const id = op & 0x20 ? inptr.decodeU32() : -1;
+inptr.l = tag;
+if (id >= 0) inptr.n[id] = tag;
+break;
+}
+case 14: {
+const text = inptr.str(inptr.decodeU16());
+const root = op & 0x20 ? inptr.node() : inptr.l;
+root.textContent = text;
+break;
+}
+case 15: {
+const attr = op & 0x20 ? inptr.str(inptr.decodeU16()) : attrs[inptr.v.getUint8(inptr.u++)];
+const attrNs = op & 0x40 ? inptr.str(inptr.decodeU16()) : null;
+const value = inptr.str(inptr.decodeU16());
+const root = op & 0x80 ? inptr.node() : inptr.l;
+if (attrNs === null) root.setAttribute(attr, value);
+else root.setAttributeNS(attrNs, attr, value);
+break;
+}
+case 16: {
+const attr = op & 0x20 ? inptr.str(inptr.decodeU16()) : attrs[inptr.v.getUint8(inptr.u++)];
+const attrNs = op & 0x40 ? inptr.str(inptr.decodeU16()) : null;
+const root = op & 0x80 ? inptr.node() : inptr.l;
+if (attrNs === null) root.removeAttribute(attr);
+else root.removeAttributeNS(attrNs, attr);
+break;
+}
+case 17: {
+const style = inptr.styleName();
+const value = inptr.str(inptr.decodeU16());
+const important = op & 0x20;
+const root = op & 0x40 ? inptr.node() : inptr.l;
+root.style.setProperty(style, value, important ? "important" : "");
+break;
+}
+case 18: {
+const style = inptr.styleName();
+const root = op & 0x20 ? inptr.node() : inptr.l;
+root.style.removeProperty(style);
+break;
+}
+case 19: {
+const node = op & 0x20 ? inptr.node() : inptr.l;
+const id = op & 0x40 ? inptr.decodeU32() : -1;
+inptr.l = node.cloneNode(true);
+if (id >= 0) inptr.n[id] = inptr.l;
+break;
+}
+case 20:
+break;
+case 21: {
+const html = inptr.str(inptr.decodeU32());
+const root = op & 0x20 ? inptr.node() : inptr.l;
+const id = op & 0x40 ? inptr.decodeU32() : -1;
+root.innerHTML = html;
+if (id >= 0) inptr.n[id] = root.firstChild;
+break;
+}
+case 22: {
+const position = inptr.v.getUint8(inptr.u++);
+const html = inptr.str(inptr.decodeU32());
+const root = op & 0x20 ? inptr.node() : inptr.l;
+const id = op & 0x40 ? inptr.decodeU32() : -1;
+node = inptr.insertAdjacentHtml(root, position, html);
+if (id >= 0) inptr.n[id] = node;
+break;
+}
+case 23: {
+const text = inptr.str(inptr.decodeU16());
+const id = op & 0x20 ? inptr.decodeU32() : -1;
+inptr.l = doc.createComment(text);
+if (id >= 0) inptr.n[id] = inptr.l;
+break;
+}
+case 24: {
+const id = op & 0x20 ? inptr.decodeU32() : -1;
+inptr.l = doc.createDocumentFragment();
+if (id >= 0) inptr.n[id] = inptr.l;
+break;
+}
+case 25: {
+const id = inptr.decodeU32();
+const roots = inptr.decodeRoots();
+inptr.t[id] = doc.createElement("template");
+inptr.t[id].content.append(...roots);
+break;
+}
+case 26: {
+const template = inptr.decodeU32();
+const id = op & 0x20 ? inptr.decodeU32() : -1;
+inptr.l = doc.importNode(inptr.t[template].content, true);
+if (id >= 0) inptr.n[id] = inptr.l;
+break;
+}
+case 27: {
+const root = op & 0x20 ? inptr.node() : inptr.l;
+const mode = op & 0x40 ? "closed" : "open";
+const id = inptr.decodeU32();
+inptr.l = inptr.n[id] = root.attachShadow({ mode });
+inptr.sr.set(root, inptr.l);
+break;
+}
+case 28: {
+const css = inptr.str(inptr.decodeU32());
+const id = inptr.decodeU32();
+inptr.ss[id] = new (doc.defaultView || window).CSSStyleSheet();
+inptr.ss[id].replaceSync(css);
+break;
+}
+case 29: {
+const root = op & 0x20 ? inptr.node() : inptr.l;
+const sheet = inptr.decodeU32();
+root.adoptedStyleSheets = [...root.adoptedStyleSheets, inptr.ss[sheet]];
+break;
+}
+case 30: {
+const ids = inptr.decodeU32();
+const idsEnd = op & 0x20 ? inptr.decodeU32() : ids + 1;
+inptr.n.fill(undefined, ids, idsEnd);
+break;
+}
+case 31:
+return exExtOp();
+}
+}
+function exExtOp() {
+switch (inptr.v.getUint8(inptr.u++)) {
+case 32: {
+const root = op & 0x20 ? inptr.node() : inptr.l;
+inptr.p.push(root);
+break;
+}
+case 33: {
+const root = op & 0x20 ? inptr.node() : inptr.l;
+const n = inptr.decodeU16();
+root.append(...inptr.popStack(n));
+break;
+}
+case 34: {
+const root = op & 0x20 ? inptr.node() : inptr.l;
+const n = inptr.decodeU16();
+root.replaceWith(...inptr.popStack(n));
+break;
+}
+case 35: {
+const root = op & 0x20 ? inptr.node() : inptr.l;
+const n = inptr.decodeU16();
+root.after(...inptr.popStack(n));
+break;
+}
+case 36: {
+const root = op & 0x20 ? inptr.node() : inptr.l;
+const n = inptr.decodeU16();
+root.before(...inptr.popStack(n));
+break;
+}
+case 37: {
+const text = inptr.str(inptr.decodeU16());
+const root = op & 0x20 ? inptr.node() : inptr.l;
+root.appendData(text);
+break;
+}
+case 38: {
+const offset = inptr.decodeU32();
+const text = inptr.str(inptr.decodeU16());
+const root = op & 0x20 ? inptr.node() : inptr.l;
+root.insertData(offset, text);
+break;
+}
+case 39: {
+const offset = inptr.decodeU32();
+const count = inptr.decodeU32();
+const root = op & 0x20 ? inptr.node() : inptr.l;
+root.deleteData(offset, count);
+break;
+}
+case 40: {
+const offset = inptr.decodeU32();
+const count = inptr.decodeU32();
+const text = inptr.str(inptr.decodeU16());
+const root = op & 0x20 ? inptr.node() : inptr.l;
+root.replaceData(offset, count, text);
+break;
+}
+case 41: {
+const styles = inptr.decodeStyles();
+const root = op & 0x20 ? inptr.node() : inptr.l;
+for (i = 0; i < styles.length; i += 2) root.style.setProperty(styles[i], styles[i + 1]);
+break;
+}
+case 42: {
+const template = inptr.decodeU32();
+inptr.t[template] = undefined;
+break;
+}
+case 43: {
+const sheet = inptr.decodeU32();
+inptr.ss[sheet] = undefined;
+break;
+}
+}
+}
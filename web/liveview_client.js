@@ -0,0 +1,92 @@
+// Connects a page to a sledgehammer-liveview server. The server renders into the root element its LiveView was created with and runs the handlers of the events sent back.
+// See the protocol module of sledgehammer-liveview for the messages. When the connection drops, the client reconnects with the token of its session and the server rebuilds the dom.
+//
+// Example:
+// import { LiveViewClient } from "./liveview_client.js";
+// new LiveViewClient("ws://localhost:3000/ws").Connect();
+
+import { JsInterpreter } from "./interpreter_opt.js";
+import { StreamReceiver } from "./stream_receiver.js";
+
+const HELLO = 0;
+const EVENT = 1;
+
+export class LiveViewClient {
+    // reconnectDelay is in milliseconds, null disables reconnecting
+    constructor(url, doc, reconnectDelay) {
+        this.url = url;
+        this.doc = doc || document;
+        this.reconnectDelay = reconnectDelay === undefined ? 1000 : reconnectDelay;
+        // the token of the session, kept across connections so the session can be resumed
+        this.token = "";
+        this.encoder = new TextEncoder();
+        this.decoder = new TextDecoder();
+    }
+
+    Connect() {
+        const socket = new WebSocket(this.url);
+        socket.binaryType = "arraybuffer";
+        // the server rebuilds the dom with the same ids on every connection, so every connection starts with an empty interpreter
+        const interpreter = new JsInterpreter({ buffer: new ArrayBuffer(0) }, 0, 0, 0, 0, this.doc);
+        const receiver = new StreamReceiver(interpreter, this.doc, (id, name, e) => this.sendEvent(socket, id, name, e));
+        let welcomed = false;
+        socket.onopen = () => socket.send(this.message(HELLO, [], [this.token]));
+        socket.onmessage = (e) => {
+            // the first message is the token of the session, the rest is a stream
+            if (!welcomed) {
+                this.token = this.decoder.decode(e.data);
+                welcomed = true;
+                return;
+            }
+            receiver.Push(new Uint8Array(e.data)).forEach(console.error);
+        };
+        socket.onclose = () => {
+            if (this.reconnectDelay !== null) {
+                setTimeout(() => this.Connect(), this.reconnectDelay);
+            }
+        };
+        this.socket = socket;
+    }
+
+    // stops reconnecting and closes the connection
+    Close() {
+        this.reconnectDelay = null;
+        this.socket.close();
+    }
+
+    sendEvent(socket, id, name, e) {
+        // listeners on nodes that survived a reconnect belong to the old connection
+        if (socket.readyState !== WebSocket.OPEN) {
+            return;
+        }
+        const target = e.target;
+        const value = target && typeof target.value === "string" ? target.value : "";
+        socket.send(this.message(EVENT, [id], [name, value]));
+    }
+
+    // encodes a tag, u32 numbers and strings with u16 lengths
+    message(tag, numbers, strings) {
+        const encoded = strings.map((s) => this.encoder.encode(s));
+        let len = 1 + numbers.length * 4;
+        for (const s of encoded) {
+            if (s.length > 0xffff) {
+                throw new Error("the string is longer than 65535 bytes");
+            }
+            len += 2 + s.length;
+        }
+        const bytes = new Uint8Array(len);
+        const view = new DataView(bytes.buffer);
+        bytes[0] = tag;
+        let pos = 1;
+        for (const n of numbers) {
+            view.setUint32(pos, n, true);
+            pos += 4;
+        }
+        for (const s of encoded) {
+            view.setUint16(pos, s.length, true);
+            bytes.set(s, pos + 2);
+            pos += 2 + s.length;
+        }
+        return bytes;
+    }
+}
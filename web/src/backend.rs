@@ -0,0 +1,510 @@
+//! This module contains the [`DomBackend`] trait which is what a [`MsgChannel`](crate::MsgChannel) sends its batches to.
+//!
+//! - [`JsBackend`] runs batches with the javascript interpreter in the browser. This is the default backend of a [`MsgChannel`](crate::MsgChannel) on wasm32.
+//! - [`MemoryBackend`] runs batches with a [`NativeInterpreter`] on an in-memory dom, which is useful as a test double or to render without a browser. This is the default backend on every other target, so code that uses a [`MsgChannel`](crate::MsgChannel) can be tested with `cargo test`.
+//!
+//! - [`StreamBackend`] sends batches over a byte stream to a dom in another process, like a desktop webview or a browser connected to a server.
+//!
+//! Other backends can be added by implementing [`DomBackend`].
+
+use std::io::{self, Write};
+
+use js_sys::{Array, ArrayBuffer};
+use sledgehammer_encoder::{
+    batch::Batch,
+    dom::{DomId, NodeKind},
+    FrameWriter, InterpretError, NativeInterpreter, NodeId, Op, FORMAT_VERSION,
+};
+use wasm_bindgen::JsValue;
+use web_sys::{Document, Node};
+
+use crate::{FlushError, JsInterpreter, Metadata};
+
+/// The backend [`MsgChannel::default`](crate::MsgChannel::default) uses on this target
+#[cfg(target_arch = "wasm32")]
+pub type DefaultBackend = JsBackend;
+
+/// The backend [`MsgChannel::default`](crate::MsgChannel::default) uses on this target
+#[cfg(not(target_arch = "wasm32"))]
+pub type DefaultBackend = MemoryBackend;
+
+/// Something that can apply finalized batches to a dom and store nodes with ids
+pub trait DomBackend {
+    /// The handle to a node in the dom
+    type Node;
+
+    /// Run a finalized batch. The message ends with [`Op::Stop`].
+    fn run_batch(&mut self, msg: &[u8], str: &[u8]) -> Result<(), FlushError>;
+
+    /// Store a node with an id
+    fn set_node(&mut self, id: NodeId, node: Self::Node);
+
+    /// Get the node stored with an id
+    fn get_node(&mut self, id: NodeId) -> Self::Node;
+
+    /// The `nodeName` of a node, like `BODY` or `#document-fragment`. This is used to record the nodes that are set with ids, see [`MsgChannel::start_recording`](crate::MsgChannel::start_recording).
+    ///
+    /// Backends whose nodes are not dom nodes return whatever the node can be recreated from when the recording is replayed, like the selector of a [`StreamBackend`] node. Nodes that cannot be recreated return `None` and are not recorded.
+    fn node_name(&self, node: &Self::Node) -> Option<String>;
+}
+
+/// Runs batches with the javascript interpreter in the browser. This backend can only be created on wasm32.
+///
+/// Creating the backend panics if the javascript interpreter reads another version of the format than [`FORMAT_VERSION`].
+pub struct JsBackend {
+    pub(crate) js_interpreter: JsInterpreter,
+    /// Boxed so the address the interpreter reads from stays the same when the backend is moved
+    metadata: Box<Metadata>,
+    #[cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
+    last_mem_size: usize,
+}
+
+impl Default for JsBackend {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+impl JsBackend {
+    /// Create a backend that creates nodes in the given document instead of the global document
+    pub fn with_document(document: Document) -> Self {
+        Self::new(Some(document))
+    }
+
+    fn new(document: Option<Document>) -> Self {
+        let metadata = Box::<Metadata>::default();
+        let js_interpreter = JsInterpreter::new(
+            wasm_bindgen::memory(),
+            &metadata.flags as *const u8 as usize,
+            &metadata.msg_ptr as *const usize as usize,
+            &metadata.str_ptr as *const usize as usize,
+            &metadata.str_len as *const usize as usize,
+            document,
+        );
+        // a different interpreter.js would silently run the wrong operations, so check it once per channel
+        let js_version = js_interpreter.FormatVersion();
+        assert!(
+            js_version == FORMAT_VERSION,
+            "the javascript interpreter reads version {js_version} of the sledgehammer format, but sledgehammer-encoder writes version {FORMAT_VERSION}. Use interpreter.js from the same release",
+        );
+
+        Self {
+            js_interpreter,
+            metadata,
+            last_mem_size: 0,
+        }
+    }
+
+    /// Run a batch that was transferred from another thread
+    pub(crate) fn run_transferred(
+        &mut self,
+        msg: ArrayBuffer,
+        str: ArrayBuffer,
+    ) -> Result<(), FlushError> {
+        flush_error_from_js(self.js_interpreter.WorkTransferred(msg, str))
+    }
+}
+
+impl DomBackend for JsBackend {
+    type Node = Node;
+
+    fn run_batch(&mut self, msg: &[u8], str_buf: &[u8]) -> Result<(), FlushError> {
+        let metadata = &mut self.metadata;
+        let msg_ptr = msg.as_ptr() as usize;
+        let str_ptr = str_buf.as_ptr() as usize;
+        // the pointer will only be updated when the message vec is resized, so we have a flag to check if the pointer has changed to avoid unnecessary decoding
+        if metadata.flags == 255 {
+            // this is the first message, so we need to encode all the metadata
+            metadata.msg_ptr = msg_ptr;
+            metadata.str_ptr = str_ptr;
+            // the first bit encodes if the msg pointer has changed
+            // the second bit encodes if the str pointer has changed
+            metadata.flags = 1 | 2;
+        } else {
+            metadata.flags = 0;
+            if metadata.msg_ptr != msg_ptr {
+                metadata.msg_ptr = msg_ptr;
+                // the first bit encodes if the msg pointer has changed
+                metadata.flags |= 1;
+            }
+            if metadata.str_ptr != str_ptr {
+                metadata.str_ptr = str_ptr;
+                // the second bit encodes if the str pointer has changed
+                metadata.flags |= 1 << 1;
+            }
+        }
+        if !str_buf.is_empty() {
+            // the third bit encodes if there is any strings
+            metadata.flags |= 1 << 2;
+            metadata.str_len = str_buf.len();
+            if metadata.str_len < 100 {
+                // the fourth bit encodes if the strings are entirely ascii and small
+                metadata.flags |= (str_buf.is_ascii() as u8) << 3;
+            }
+        }
+        // the debug interpreter checks that every read stays inside of the message
+        #[cfg(feature = "debug-interpreter")]
+        self.js_interpreter.SetMsgLen(msg.len());
+        // we need to update the memory if the memory has grown
+        #[cfg(target_arch = "wasm32")]
+        {
+            let new_mem_size = core::arch::wasm32::memory_size(0);
+            if new_mem_size != self.last_mem_size {
+                self.last_mem_size = new_mem_size;
+                self.js_interpreter.UpdateMemory(wasm_bindgen::memory());
+            }
+        }
+
+        flush_error_from_js(self.js_interpreter.Work())
+    }
+
+    fn set_node(&mut self, id: NodeId, node: Node) {
+        self.js_interpreter.SetNode(id.0, node);
+    }
+
+    fn get_node(&mut self, id: NodeId) -> Node {
+        self.js_interpreter.GetNode(id.0)
+    }
+
+    fn node_name(&self, node: &Node) -> Option<String> {
+        Some(node.node_name())
+    }
+}
+
+/// Convert the value returned by the javascript interpreter to the error it describes
+fn flush_error_from_js(value: JsValue) -> Result<(), FlushError> {
+    if value.is_undefined() {
+        return Ok(());
+    }
+    let value = Array::from(&value);
+    Err(FlushError {
+        op_index: value.get(0).as_f64().unwrap_or_default() as u32,
+        op: value.get(1).as_f64().unwrap_or_default() as u8,
+        node: value.get(2).as_f64().map(|id| NodeId(id as u32)),
+        message: value.get(3).as_string().unwrap_or_default(),
+    })
+}
+
+/// Runs batches with a [`NativeInterpreter`] on an in-memory dom.
+///
+/// Nodes are [`DomId`]s in the dom of the interpreter. Setting `None` removes the node stored with an id, and getting an id without a node returns `None`.
+///
+/// Example:
+/// ```rust
+/// # use sledgehammer::*;
+/// let mut channel = MsgChannel::with_backend(MemoryBackend::default());
+/// let body = channel.backend_mut().interpreter_mut().dom_mut().create_element("body", None);
+/// channel.set_node(NodeId(0), Some(body));
+/// channel.create_element("div", None);
+/// channel.append_child(MaybeId::Node(NodeId(0)), MaybeId::LastNode);
+/// channel.flush().unwrap();
+/// assert_eq!(channel.backend().interpreter().dom().to_html(body), "<body><div></div></body>");
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct MemoryBackend {
+    interpreter: NativeInterpreter,
+}
+
+impl MemoryBackend {
+    /// Create a backend that runs batches with an existing interpreter
+    pub fn new(interpreter: NativeInterpreter) -> Self {
+        Self { interpreter }
+    }
+
+    /// The interpreter batches are run with
+    pub fn interpreter(&self) -> &NativeInterpreter {
+        &self.interpreter
+    }
+
+    /// The interpreter batches are run with
+    pub fn interpreter_mut(&mut self) -> &mut NativeInterpreter {
+        &mut self.interpreter
+    }
+
+    /// Take the interpreter out of the backend
+    pub fn into_interpreter(self) -> NativeInterpreter {
+        self.interpreter
+    }
+}
+
+impl DomBackend for MemoryBackend {
+    type Node = Option<DomId>;
+
+    fn run_batch(&mut self, msg: &[u8], str: &[u8]) -> Result<(), FlushError> {
+        self.interpreter.run(msg, str).map_err(FlushError::from)
+    }
+
+    fn set_node(&mut self, id: NodeId, node: Option<DomId>) {
+        self.interpreter.set_node(id, node);
+    }
+
+    fn get_node(&mut self, id: NodeId) -> Option<DomId> {
+        self.interpreter.get_node(id)
+    }
+
+    fn node_name(&self, node: &Option<DomId>) -> Option<String> {
+        let node = (*node)?;
+        Some(match &self.interpreter.dom().node(node).kind {
+            NodeKind::Document { .. } => "#document".to_string(),
+            NodeKind::Element(element) => element.name.clone(),
+            NodeKind::Text(_) => "#text".to_string(),
+            NodeKind::Comment(_) => "#comment".to_string(),
+            NodeKind::Fragment | NodeKind::ShadowRoot { .. } => "#document-fragment".to_string(),
+        })
+    }
+}
+
+impl From<InterpretError> for FlushError {
+    fn from(error: InterpretError) -> Self {
+        Self {
+            op_index: error.op_index,
+            op: error.op,
+            node: error.node,
+            message: error.message,
+        }
+    }
+}
+
+/// Sends batches over a byte stream to a dom in another process with a [`FrameWriter`]. `stream_receiver.js` applies the stream to the javascript interpreter on the other side. See the [`stream`](sledgehammer_encoder::stream) module for the format.
+///
+/// Nodes are the css selectors the receiver finds them with, and an empty selector is the document. Nodes created by batches only exist in the receiver, so [`DomBackend::get_node`] only returns the selectors that were set. Setting `None` frees the id in the receiver.
+///
+/// The stream is flushed after every batch. Errors writing to the stream are returned by the next flush as a [`FlushError`] for the first operation with the io error as the message.
+///
+/// Example:
+/// ```rust
+/// # use sledgehammer::*;
+/// let mut channel = MsgChannel::with_backend(StreamBackend::new(Vec::new()).unwrap());
+/// channel.set_node(NodeId(0), Some("#app".to_string()));
+/// channel.create_element("div", None);
+/// channel.append_child(MaybeId::Node(NodeId(0)), MaybeId::LastNode);
+/// channel.flush().unwrap();
+/// let stream: Vec<u8> = channel.into_backend().into_writer().into_inner();
+/// ```
+#[derive(Debug)]
+pub struct StreamBackend<W: Write> {
+    writer: FrameWriter<W>,
+    selectors: Vec<Option<String>>,
+    /// The first error writing a node, reported by the next batch
+    error: Option<io::Error>,
+}
+
+impl<W: Write> StreamBackend<W> {
+    /// Start a stream by writing the header
+    pub fn new(inner: W) -> io::Result<Self> {
+        Ok(Self {
+            writer: FrameWriter::new(inner)?,
+            selectors: Vec::new(),
+            error: None,
+        })
+    }
+
+    /// The writer frames are written with
+    pub fn writer(&self) -> &FrameWriter<W> {
+        &self.writer
+    }
+
+    /// Take the writer out of the backend
+    pub fn into_writer(self) -> FrameWriter<W> {
+        self.writer
+    }
+
+    fn write_set_node(&mut self, id: NodeId, selector: Option<&str>) -> io::Result<()> {
+        match selector {
+            Some(selector) => self.writer.write_set_node(id, selector),
+            None => {
+                let mut batch = Batch::default();
+                batch.free_id(id);
+                self.writer.write_batch(batch.finalize())
+            }
+        }
+    }
+}
+
+impl<W: Write> DomBackend for StreamBackend<W> {
+    type Node = Option<String>;
+
+    fn run_batch(&mut self, msg: &[u8], str: &[u8]) -> Result<(), FlushError> {
+        let result = match self.error.take() {
+            Some(err) => Err(err),
+            None => self
+                .writer
+                .write_batch_parts(msg, str)
+                .and_then(|_| self.writer.flush()),
+        };
+        result.map_err(|err| FlushError {
+            op_index: 0,
+            op: Op::first_code(msg),
+            node: None,
+            message: format!("failed to write to the stream: {err}"),
+        })
+    }
+
+    fn set_node(&mut self, id: NodeId, node: Option<String>) {
+        if let Err(err) = self.write_set_node(id, node.as_deref()) {
+            self.error.get_or_insert(err);
+        }
+        let index = id.0 as usize;
+        if self.selectors.len() <= index {
+            self.selectors.resize(index + 1, None);
+        }
+        self.selectors[index] = node;
+    }
+
+    fn get_node(&mut self, id: NodeId) -> Option<String> {
+        self.selectors.get(id.0 as usize).cloned().flatten()
+    }
+
+    /// The selector of the node, so a replay can select the same node again
+    fn node_name(&self, node: &Option<String>) -> Option<String> {
+        node.clone()
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use sledgehammer_encoder::{
+        batch::Batch, Attribute, FrameReader, MaybeId, NativeInterpreter, NodeId, Op, StaticBatch,
+        FORMAT_VERSION,
+    };
+    use sledgehammer_prebuild::html;
+
+    use super::{MemoryBackend, StreamBackend};
+    use crate::{FlushThreshold, MsgChannel};
+
+    #[test]
+    fn default_channel_uses_memory_backend() {
+        let mut channel = MsgChannel::default();
+        let body = channel
+            .backend_mut()
+            .interpreter_mut()
+            .dom_mut()
+            .create_element("body", None);
+        channel.set_node(NodeId(0), Some(body));
+        channel.create_element("p", Some(NodeId(1)));
+        channel.set_attribute(Attribute::class, "greeting", MaybeId::LastNode);
+        channel.set_text("hello", MaybeId::Node(NodeId(1)));
+        channel.append_child(MaybeId::Node(NodeId(0)), MaybeId::Node(NodeId(1)));
+        channel.flush().unwrap();
+        assert_eq!(
+            channel.backend().interpreter().dom().to_html(body),
+            r#"<body><p class="greeting">hello</p></body>"#
+        );
+
+        channel.remove(MaybeId::Node(NodeId(2)));
+        let error = channel.flush().unwrap_err();
+        assert_eq!(error.node, Some(NodeId(2)));
+    }
+
+    #[test]
+    fn flush_error_describes_the_failed_operation() {
+        let (interpreter, body) = NativeInterpreter::with_body();
+        let mut channel = MsgChannel::with_backend(MemoryBackend::new(interpreter));
+        channel.create_element("p", Some(NodeId(1)));
+        // appending a batch pads the operations before it with no-ops, which are not counted
+        let mut batch = Batch::default();
+        batch.append_child(MaybeId::Node(NodeId(0)), MaybeId::Node(NodeId(1)));
+        channel.append(batch);
+        channel.set_text("text", MaybeId::Node(NodeId(1)));
+        channel.append_data("!", MaybeId::Node(NodeId(2)));
+        channel.set_text("skipped", MaybeId::Node(NodeId(1)));
+        let error = channel.flush().unwrap_err();
+        assert_eq!(error.op_index, 3);
+        // extended operations report their own opcode
+        assert_eq!(error.op, Op::AppendData as u8);
+        assert_eq!(error.node, Some(NodeId(2)));
+        assert_eq!(
+            channel.backend().interpreter().dom().to_html(body),
+            "<body><p>text</p></body>"
+        );
+
+        // the operations after the failed one are skipped, but the next flush runs normally
+        channel.set_attribute(Attribute::class, "x", MaybeId::Node(NodeId(1)));
+        channel.flush().unwrap();
+        assert_eq!(
+            channel.backend().interpreter().dom().to_html(body),
+            r#"<body><p class="x">text</p></body>"#
+        );
+    }
+
+    #[test]
+    fn failed_auto_flush_drops_operations_until_flush() {
+        let (interpreter, body) = NativeInterpreter::with_body();
+        let mut channel = MsgChannel::with_backend(MemoryBackend::new(interpreter));
+        channel.set_flush_threshold(Some(FlushThreshold::Ops(2)));
+        channel.create_element("p", None);
+        channel.append_child(MaybeId::Node(NodeId(0)), MaybeId::LastNode);
+        // the remove fails when the next operations flush it automatically, and the <b> is skipped with the rest of its batch
+        channel.remove(MaybeId::Node(NodeId(5)));
+        channel.create_element("b", None);
+        channel.append_child(MaybeId::Node(NodeId(0)), MaybeId::LastNode);
+        // these would run against a dom without the <b>, so they are dropped
+        channel.create_element("i", None);
+        channel.append_child(MaybeId::Node(NodeId(0)), MaybeId::LastNode);
+        let error = channel.flush().unwrap_err();
+        assert_eq!(error.node, Some(NodeId(5)));
+        assert_eq!(
+            channel.backend().interpreter().dom().to_html(body),
+            "<body><p></p></body>"
+        );
+
+        channel.create_element("em", None);
+        channel.append_child(MaybeId::Node(NodeId(0)), MaybeId::LastNode);
+        channel.flush().unwrap();
+        assert_eq!(
+            channel.backend().interpreter().dom().to_html(body),
+            "<body><p></p><em></em></body>"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "a string of 70000 bytes is longer than the 65535 bytes")]
+    fn flush_threshold_does_not_split_long_strings() {
+        let mut channel = MsgChannel::default();
+        channel.set_flush_threshold(Some(FlushThreshold::Bytes(1024)));
+        channel.set_text("x".repeat(70_000).as_str(), MaybeId::LastNode);
+    }
+
+    #[test]
+    fn rejects_static_batch_from_other_version() {
+        const DIV: StaticBatch = html! { <div></div> };
+        let mut channel = MsgChannel::default();
+        channel.run_batch(&DIV).unwrap();
+        assert!(channel.backend().interpreter().last_node().is_some());
+
+        let old = StaticBatch {
+            version: FORMAT_VERSION + 1,
+            ..DIV
+        };
+        let error = channel.run_batch(old).unwrap_err();
+        assert!(error.message.contains(&format!(
+            "encoded in version {} of the sledgehammer format",
+            FORMAT_VERSION + 1
+        )));
+    }
+
+    #[test]
+    fn stream_backend_round_trip() {
+        let mut channel = MsgChannel::with_backend(StreamBackend::new(Vec::new()).unwrap());
+        channel.set_node(NodeId(0), Some("main".to_string()));
+        assert_eq!(channel.get_node(NodeId(0)).as_deref(), Some("main"));
+        channel.create_element("h1", None);
+        channel.set_text("title", MaybeId::LastNode);
+        channel.append_child(MaybeId::Node(NodeId(0)), MaybeId::LastNode);
+        channel.flush().unwrap();
+        channel.set_node(NodeId(0), None);
+        assert_eq!(channel.get_node(NodeId(0)), None);
+        let stream = channel.into_backend().into_writer().into_inner();
+
+        let mut interpreter = NativeInterpreter::default();
+        let main = interpreter.dom_mut().create_element("main", None);
+        let mut reader = FrameReader::new(&stream[..]).unwrap();
+        let select = |_: &_, selector: &str| (selector == "main").then_some(main);
+        while reader.receive(&mut interpreter, select).unwrap() {}
+        assert_eq!(
+            interpreter.dom().to_html(main),
+            "<main><h1>title</h1></main>"
+        );
+        assert_eq!(interpreter.get_node(NodeId(0)), None);
+    }
+}
@@ -3,85 +3,165 @@
 //!
 //!
 
+use std::ops::Range;
+
+use js_sys::Uint8Array;
 use sledgehammer_encoder::{
     batch::{Batch, PreparedBatch},
-    MaybeId, NodeId, Op, TextBuilder, WritableText,
+    CommentBuilder, InsertPosition, IntoStyle, MaybeId, NodeBuilder, NodeId, Op, Recording, Replay,
+    ShadowRootMode, StyleSheetId, TemplateId, TextBuilder, WritableText,
 };
-use web_sys::Node;
+use web_sys::Document;
 
 use crate::{
-    update_last_memory, work_last_created, ElementBuilder, IntoAttribue, IntoElement,
-    JsInterpreter, MSG_METADATA_PTR, MSG_PTR_PTR, STR_LEN_PTR, STR_PTR_PTR,
+    DefaultBackend, DomBackend, ElementBuilder, IntoAttribue, IntoElement, JsBackend,
+    TransferableBatch,
 };
 
-/// Tracks if a interpreter has been created. Used to prevent multiple interpreters from being created.
-static mut INTERPRETER_EXISTS: bool = false;
-
 /// The [`MsgChannel`] handles communication with the dom. It allows you to send batched operations to the dom.
 /// All of the functions that are not marked otherwise are qued and not exicuted imidately. When you want to exicute the que you have to call [`MsgChannel::flush`].
-/// There should only be one [`MsgChannel`] per application.
-pub struct MsgChannel {
-    pub(crate) js_interpreter: JsInterpreter,
-    last_mem_size: usize,
+/// Each [`MsgChannel`] has its own interpreter and node ids, so several channels can be used side by side, for example one per embedded widget.
+///
+/// The batches are applied by a [`DomBackend`]. By default this is the javascript interpreter in the browser on wasm32 and an in-memory dom on other targets, see the [`backend`](crate::backend) module.
+pub struct MsgChannel<B: DomBackend = DefaultBackend> {
+    backend: B,
     batch: Batch,
+    flush_threshold: Option<FlushThreshold>,
+    /// The number of operations queued since the last flush
+    queued_ops: usize,
+    /// The error from an automatic flush, reported by the next call to [`MsgChannel::flush`]. The operations queued until then are dropped
+    auto_flush_error: Option<FlushError>,
+    /// The batches and nodes recorded since [`MsgChannel::start_recording`]
+    recording: Option<Recording>,
+}
+
+/// When a [`MsgChannel`] flushes the queued operations automatically. See [`MsgChannel::set_flush_threshold`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FlushThreshold {
+    /// Flush when the encoded operations and strings reach this many bytes
+    Bytes(usize),
+    /// Flush when this many operations are queued
+    Ops(usize),
+}
+
+/// An operation that threw an error while a batch was applied.
+///
+/// The operations before the failed operation have been applied, and the operations after it in the same batch are skipped.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FlushError {
+    /// The index of the operation in the batch. The padding operations [`MsgChannel::append`] may add are not counted
+    pub op_index: u32,
+    /// The opcode of the operation. See [`Op`]
+    pub op: u8,
+    /// The node id the operation looked up, if no node is stored with that id
+    pub node: Option<NodeId>,
+    /// The message of the error thrown by the backend
+    pub message: String,
 }
 
+impl std::fmt::Display for FlushError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "operation {} (opcode {}) failed", self.op_index, self.op)?;
+        if let Some(node) = self.node {
+            write!(f, ", no node is stored with id {}", node.0)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+impl std::error::Error for FlushError {}
+
 impl Default for MsgChannel {
     fn default() -> Self {
-        unsafe {
-            debug_assert!(
-                !INTERPRETER_EXISTS,
-                "Found another MsgChannel. Only one MsgChannel can be created"
-            );
-            INTERPRETER_EXISTS = true;
-        }
-        debug_assert!(0x1F > Op::NoOp as u8);
-        // format!(
-        //     "init: {:?}, {:?}, {:?}",
-        //     unsafe { MSG_PTR_PTR as usize },
-        //     unsafe { STR_PTR_PTR as usize },
-        //     unsafe { STR_LEN_PTR as usize }
-        // );
-        let js_interpreter = unsafe {
-            JsInterpreter::new(
-                wasm_bindgen::memory(),
-                MSG_METADATA_PTR as usize,
-                MSG_PTR_PTR as usize,
-                STR_PTR_PTR as usize,
-                STR_LEN_PTR as usize,
-            )
-        };
+        Self::with_backend(DefaultBackend::default())
+    }
+}
 
+impl MsgChannel<JsBackend> {
+    /// Create a channel that creates nodes in the given document instead of the global document. This can be used to render into an iframe or popup window.
+    ///
+    /// Example:
+    /// ```no_run
+    /// # use sledgehammer::*;
+    /// # use wasm_bindgen::JsCast;
+    /// let window = web_sys::window().unwrap();
+    /// let popup = window.open().unwrap().unwrap();
+    /// let mut channel = MsgChannel::with_document(popup.document().unwrap());
+    /// channel.set_node(NodeId(0), JsCast::dyn_into(popup.document().unwrap().body().unwrap()).unwrap());
+    /// channel.create_element("div", None);
+    /// channel.append_child(MaybeId::Node(NodeId(0)), MaybeId::LastNode);
+    /// channel.flush();
+    /// ```
+    pub fn with_document(document: Document) -> Self {
+        Self::with_backend(JsBackend::with_document(document))
+    }
+}
+
+impl<B: DomBackend> MsgChannel<B> {
+    /// Create a channel that applies its batches with the given backend
+    ///
+    /// Example:
+    /// ```rust
+    /// # use sledgehammer::*;
+    /// let mut channel = MsgChannel::with_backend(MemoryBackend::default());
+    /// channel.create_element("div", Some(NodeId(0)));
+    /// channel.flush().unwrap();
+    /// assert!(channel.get_node(NodeId(0)).is_some());
+    /// ```
+    pub fn with_backend(backend: B) -> Self {
         Self {
-            js_interpreter,
-            last_mem_size: 0,
+            backend,
             batch: Batch::default(),
+            flush_threshold: None,
+            queued_ops: 0,
+            auto_flush_error: None,
+            recording: None,
         }
     }
-}
 
-impl MsgChannel {
+    /// The backend the channel applies its batches with
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    /// The backend the channel applies its batches with
+    pub fn backend_mut(&mut self) -> &mut B {
+        &mut self.backend
+    }
+
+    /// Take the backend out of the channel. Queued operations are dropped.
+    pub fn into_backend(self) -> B {
+        self.backend
+    }
     /// IMPORTANT: This method is exicuted immediatly and does not wait for the next flush
     ///
     /// Example:
     /// ```no_run
+    /// # use sledgehammer::*;
+    /// # use wasm_bindgen::JsCast;
     /// let window = web_sys::window().unwrap();
     /// let document = window.document().unwrap();
     /// let body = document.body().unwrap();
-    /// let mut channel = MsgChannel::default();
+    /// let mut channel = MsgChannel::with_backend(JsBackend::default());
     /// // assign the NodeId(0) to the body element from web-sys
     /// channel.set_node(NodeId(0), JsCast::dyn_into(body).unwrap());
     /// // no need to call flush here because set_node is exicuted immediatly
     /// ```
-    pub fn set_node(&mut self, id: NodeId, node: Node) {
-        self.js_interpreter.SetNode(id.0, node);
+    pub fn set_node(&mut self, id: NodeId, node: B::Node) {
+        if let Some(recording) = &mut self.recording {
+            if let Some(name) = self.backend.node_name(&node) {
+                recording.record_set_node(id, &name);
+            }
+        }
+        self.backend.set_node(id, node);
     }
 
     /// IMPORTANT: This method is exicuted immediatly and does not wait for the next flush
     ///
     /// Example:
     /// ```no_run
-    /// let mut channel = MsgChannel::default();
+    /// # use sledgehammer::*;
+    /// let mut channel = MsgChannel::with_backend(JsBackend::default());
     /// channel.create_element("div", Some(NodeId(0)));
     /// channel.flush();
     /// let element = channel.get_node(NodeId(0));
@@ -89,33 +169,98 @@ impl MsgChannel {
     /// element.set_text_content(text.as_deref());
     /// // no need to call flush here because get_node is exicuted immediatly
     /// ```
-    pub fn get_node(&mut self, id: NodeId) -> Node {
-        self.js_interpreter.GetNode(id.0)
+    pub fn get_node(&mut self, id: NodeId) -> B::Node {
+        self.backend.get_node(id)
     }
 
     /// Exicutes any queued operations in the order they were added
     ///
+    /// If an operation fails, the rest of the batch is skipped and the error is returned. Errors from automatic flushes (see [`MsgChannel::set_flush_threshold`]) are returned by the next call to flush, and the operations queued after the automatic flush failed are skipped like the rest of its batch.
+    ///
     /// Example:
     ///
     /// ```no_run
+    /// # use sledgehammer::*;
     /// let mut channel = MsgChannel::default();
     /// // this does not immediatly create a <div> or <p>
     /// channel.create_element("div", None);
     /// channel.create_element("p", None);
     /// // this creates the <div> and <p> elements
-    /// channel.flush();
+    /// channel.flush().unwrap();
     /// ```
-    pub fn flush(&mut self) {
+    pub fn flush(&mut self) -> Result<(), FlushError> {
+        match self.auto_flush_error.take() {
+            Some(err) => {
+                self.clear_queued();
+                Err(err)
+            }
+            None => self.flush_queued(),
+        }
+    }
+
+    fn flush_queued(&mut self) -> Result<(), FlushError> {
         self.batch.encode_op(Op::Stop);
-        run_batch(
-            &self.batch.msg,
-            &self.batch.str_buf,
-            &mut self.last_mem_size,
-        );
+        if let Some(recording) = &mut self.recording {
+            recording.record_batch(&self.batch.msg, &self.batch.str_buf);
+        }
+        let result = self.backend.run_batch(&self.batch.msg, &self.batch.str_buf);
+        self.clear_queued();
+        result
+    }
+
+    /// Drop the queued operations without running them
+    fn clear_queued(&mut self) {
         self.batch.msg.clear();
         self.batch.current_op_batch_idx = 0;
         self.batch.current_op_byte_idx = 3;
         self.batch.str_buf.clear();
+        self.queued_ops = 0;
+    }
+
+    /// Flush the queued operations automatically when the batch gets too large. `None` disables automatic flushing, which is the default.
+    ///
+    /// The batch is only split between operations, and the last node and node stack are kept between flushes, so operations using [`MaybeId::LastNode`] still refer to the right node after a split. If an automatic flush fails, the operations queued after it are dropped until the next call to [`MsgChannel::flush`] returns the error, because they would run against a dom where the rest of the failed batch was skipped.
+    ///
+    /// A single operation is never split, so the threshold does not limit the size of one string. Strings longer than 65535 bytes panic instead of being truncated, except html and css, which have a u32 length.
+    ///
+    /// Example:
+    /// ```no_run
+    /// # use sledgehammer::*;
+    /// let mut channel = MsgChannel::default();
+    /// // run the operations every 1MB while building a large list
+    /// channel.set_flush_threshold(Some(FlushThreshold::Bytes(1 << 20)));
+    /// for i in 0..100_000 {
+    ///     channel.create_element("li", None);
+    ///     channel.set_text(format_args!("{i}"), MaybeId::LastNode);
+    ///     channel.append_child(MaybeId::Node(NodeId(0)), MaybeId::LastNode);
+    /// }
+    /// channel.flush();
+    /// ```
+    pub fn set_flush_threshold(&mut self, threshold: Option<FlushThreshold>) {
+        self.flush_threshold = threshold;
+    }
+
+    /// The batch to queue an operation in. Every operation is queued through this, so the queued operations are flushed first if the flush threshold has been reached and batches are only split between operations.
+    #[inline(always)]
+    fn queue(&mut self) -> &mut Batch {
+        if self.auto_flush_error.is_some() {
+            // the rest of the failed batch was skipped, so the operations after it are dropped as well
+            self.clear_queued();
+        } else if let Some(threshold) = self.flush_threshold {
+            let reached = match threshold {
+                FlushThreshold::Bytes(bytes) => {
+                    self.batch.msg.len() + self.batch.str_buf.len() >= bytes
+                }
+                FlushThreshold::Ops(ops) => self.queued_ops >= ops,
+            };
+            if reached {
+                if let Err(err) = self.flush_queued() {
+                    self.auto_flush_error = Some(err);
+                }
+            }
+            self.queued_ops += 1;
+        }
+        &mut self.batch
     }
 
     /// Appends a number of nodes as children of the given node.
@@ -123,6 +268,7 @@ impl MsgChannel {
     /// Example:
     ///
     /// ```no_run
+    /// # use sledgehammer::*;
     /// let mut channel = MsgChannel::default();
     /// channel.create_element("div", Some(NodeId(0)));
     /// channel.create_element("p", None);
@@ -131,13 +277,14 @@ impl MsgChannel {
     /// channel.flush();
     /// ```
     pub fn append_child(&mut self, root: MaybeId, child: MaybeId) {
-        self.batch.append_child(root, child)
+        self.queue().append_child(root, child)
     }
 
     /// Replace a node with another node
     ///
     /// Example:
     /// ```no_run
+    /// # use sledgehammer::*;
     /// let mut channel = MsgChannel::default();
     /// channel.create_element("div", Some(NodeId(0)));
     /// channel.create_element("p", None);
@@ -146,28 +293,30 @@ impl MsgChannel {
     /// channel.flush();
     /// ```
     pub fn replace_with(&mut self, root: MaybeId, node: MaybeId) {
-        self.batch.replace_with(root, node)
+        self.queue().replace_with(root, node)
     }
 
     /// Replace a node with many nodes
     ///
     /// Example:
     /// ```no_run
+    /// # use sledgehammer::*;
     /// let mut channel = MsgChannel::default();
     /// channel.create_element("div", Some(NodeId(0)));
     /// channel.create_element("p", None);
     /// // replace the <p> element with the <div> element
-    /// channel.replace_with_nodes(MaybeId::Node(NodeId(0)), MaybeId::LastNode);
+    /// channel.replace_with_nodes(MaybeId::LastNode, &[MaybeId::Node(NodeId(0))]);
     /// channel.flush();
     /// ```
     pub fn replace_with_nodes(&mut self, root: MaybeId, nodes: &[MaybeId]) {
-        self.batch.replace_with_nodes(root, nodes)
+        self.queue().replace_with_nodes(root, nodes)
     }
 
     /// Insert a single node after a given node.
     ///
     /// Example:
     /// ```no_run
+    /// # use sledgehammer::*;
     /// let mut channel = MsgChannel::default();
     /// channel.create_element("div", Some(NodeId(0)));
     /// channel.create_element("p", None);
@@ -176,13 +325,14 @@ impl MsgChannel {
     /// channel.flush();
     /// ```
     pub fn insert_after(&mut self, root: MaybeId, node: MaybeId) {
-        self.batch.insert_after(root, node)
+        self.queue().insert_after(root, node)
     }
 
     /// Insert a many nodes after a given node.
     ///
     /// Example:
     /// ```no_run
+    /// # use sledgehammer::*;
     /// let mut channel = MsgChannel::default();
     /// channel.create_element("div", Some(NodeId(0)));
     /// channel.create_element("p", None);
@@ -191,13 +341,14 @@ impl MsgChannel {
     /// channel.flush();
     /// ```
     pub fn insert_nodes_after(&mut self, root: MaybeId, nodes: &[MaybeId]) {
-        self.batch.insert_nodes_after(root, nodes)
+        self.queue().insert_nodes_after(root, nodes)
     }
 
     /// Insert a single node before a given node.
     ///
     /// Example:
     /// ```no_run
+    /// # use sledgehammer::*;
     /// let mut channel = MsgChannel::default();
     /// channel.create_element("div", Some(NodeId(0)));
     /// channel.create_element("p", None);
@@ -206,13 +357,14 @@ impl MsgChannel {
     /// channel.flush();
     /// ```
     pub fn insert_before(&mut self, root: MaybeId, node: MaybeId) {
-        self.batch.insert_before(root, node)
+        self.queue().insert_before(root, node)
     }
 
     /// Insert many nodes before a given node.
     ///
     /// Example:
     /// ```no_run
+    /// # use sledgehammer::*;
     /// let mut channel = MsgChannel::default();
     /// channel.create_element("div", Some(NodeId(0)));
     /// channel.create_element("p", None);
@@ -221,13 +373,99 @@ impl MsgChannel {
     /// channel.flush();
     /// ```
     pub fn insert_nodes_before(&mut self, root: MaybeId, nodes: &[MaybeId]) {
-        self.batch.insert_nodes_before(root, nodes)
+        self.queue().insert_nodes_before(root, nodes)
+    }
+
+    /// Push a node onto the stack. Nodes on the stack can be consumed by [`MsgChannel::append_children`], [`MsgChannel::replace_with_stack`], [`MsgChannel::insert_stack_after`] and [`MsgChannel::insert_stack_before`].
+    ///
+    /// Example:
+    /// ```no_run
+    /// # use sledgehammer::*;
+    /// let mut channel = MsgChannel::default();
+    /// channel.create_element("h1", None);
+    /// channel.push_root(MaybeId::LastNode);
+    /// channel.create_element("p", None);
+    /// channel.push_root(MaybeId::LastNode);
+    /// // append the <h1> and <p> elements to the node with the id 0
+    /// channel.append_children(MaybeId::Node(NodeId(0)), 2);
+    /// channel.flush();
+    /// ```
+    pub fn push_root(&mut self, id: MaybeId) {
+        self.queue().push_root(id)
+    }
+
+    /// Pop the topmost n nodes from the stack and append them as children of the given node in the order they were pushed.
+    ///
+    /// Example:
+    /// ```no_run
+    /// # use sledgehammer::*;
+    /// let mut channel = MsgChannel::default();
+    /// channel.create_element("div", Some(NodeId(0)));
+    /// channel.create_element("p", None);
+    /// channel.push_root(MaybeId::LastNode);
+    /// // append the <p> element to the <div> element
+    /// channel.append_children(MaybeId::Node(NodeId(0)), 1);
+    /// channel.flush();
+    /// ```
+    pub fn append_children(&mut self, root: MaybeId, n: u16) {
+        self.queue().append_children(root, n)
+    }
+
+    /// Pop the topmost n nodes from the stack and replace the given node with them in the order they were pushed.
+    ///
+    /// Example:
+    /// ```no_run
+    /// # use sledgehammer::*;
+    /// let mut channel = MsgChannel::default();
+    /// channel.create_element("h1", None);
+    /// channel.push_root(MaybeId::LastNode);
+    /// channel.create_element("p", None);
+    /// channel.push_root(MaybeId::LastNode);
+    /// // replace the node with the id 0 with the <h1> and <p> elements
+    /// channel.replace_with_stack(MaybeId::Node(NodeId(0)), 2);
+    /// channel.flush();
+    /// ```
+    pub fn replace_with_stack(&mut self, root: MaybeId, n: u16) {
+        self.queue().replace_with_stack(root, n)
+    }
+
+    /// Pop the topmost n nodes from the stack and insert them after the given node in the order they were pushed.
+    ///
+    /// Example:
+    /// ```no_run
+    /// # use sledgehammer::*;
+    /// let mut channel = MsgChannel::default();
+    /// channel.create_element("p", None);
+    /// channel.push_root(MaybeId::LastNode);
+    /// // insert the <p> element after the node with the id 0
+    /// channel.insert_stack_after(MaybeId::Node(NodeId(0)), 1);
+    /// channel.flush();
+    /// ```
+    pub fn insert_stack_after(&mut self, root: MaybeId, n: u16) {
+        self.queue().insert_stack_after(root, n)
+    }
+
+    /// Pop the topmost n nodes from the stack and insert them before the given node in the order they were pushed.
+    ///
+    /// Example:
+    /// ```no_run
+    /// # use sledgehammer::*;
+    /// let mut channel = MsgChannel::default();
+    /// channel.create_element("p", None);
+    /// channel.push_root(MaybeId::LastNode);
+    /// // insert the <p> element before the node with the id 0
+    /// channel.insert_stack_before(MaybeId::Node(NodeId(0)), 1);
+    /// channel.flush();
+    /// ```
+    pub fn insert_stack_before(&mut self, root: MaybeId, n: u16) {
+        self.queue().insert_stack_before(root, n)
     }
 
     /// Remove a node from the DOM.
     ///
     /// Example:
     /// ```no_run
+    /// # use sledgehammer::*;
     /// let mut channel = MsgChannel::default();
     /// channel.create_element("p", None);
     /// // remove the <p> element
@@ -235,53 +473,348 @@ impl MsgChannel {
     /// channel.flush();
     /// ```
     pub fn remove(&mut self, id: MaybeId) {
-        self.batch.remove(id)
+        self.queue().remove(id)
+    }
+
+    /// Remove a node from the DOM and release the ids of the node and of any stored descendants of the node, including the nodes in shadow roots.
+    ///
+    /// This checks every stored node, so if the ids in the subtree are known it is cheaper to call [`MsgChannel::remove`] followed by [`MsgChannel::free_id`] or [`MsgChannel::free_ids`].
+    ///
+    /// Example:
+    /// ```no_run
+    /// # use sledgehammer::*;
+    /// let mut channel = MsgChannel::default();
+    /// channel.build_full_element(
+    ///     ElementBuilder::new("div".into())
+    ///         .id(NodeId(0))
+    ///         .children(&[
+    ///             ElementBuilder::new(Element::p.into())
+    ///                 .id(NodeId(1))
+    ///                 .into(),
+    ///         ]),
+    /// );
+    /// // remove the <div> element and release the ids 0 and 1
+    /// channel.remove_and_free(MaybeId::Node(NodeId(0)));
+    /// channel.flush();
+    /// ```
+    pub fn remove_and_free(&mut self, id: MaybeId) {
+        self.queue().remove_and_free(id)
+    }
+
+    /// Release the node stored with an id. The id can be reused for another node afterwards.
+    ///
+    /// Example:
+    /// ```no_run
+    /// # use sledgehammer::*;
+    /// let mut channel = MsgChannel::default();
+    /// channel.create_element("p", Some(NodeId(0)));
+    /// channel.remove(MaybeId::Node(NodeId(0)));
+    /// // the <p> element can be garbage collected after the id is released
+    /// channel.free_id(NodeId(0));
+    /// channel.flush();
+    /// ```
+    pub fn free_id(&mut self, id: NodeId) {
+        self.queue().free_id(id)
+    }
+
+    /// Release the nodes stored with any id in a range. The ids can be reused for other nodes afterwards.
+    ///
+    /// Example:
+    /// ```no_run
+    /// # use sledgehammer::*;
+    /// let mut channel = MsgChannel::default();
+    /// // release the ids 0 through 99
+    /// channel.free_ids(NodeId(0)..NodeId(100));
+    /// channel.flush();
+    /// ```
+    pub fn free_ids(&mut self, ids: Range<NodeId>) {
+        self.queue().free_ids(ids)
     }
 
     /// Create a new text node
     ///
     /// Example:
     /// ```no_run
+    /// # use sledgehammer::*;
     /// let mut channel = MsgChannel::default();
     /// // create a text node with the text "Hello World"
     /// channel.create_text_node("Hello World", None);
     /// channel.flush();
     pub fn create_text_node(&mut self, text: impl WritableText, id: Option<NodeId>) {
-        self.batch.create_text_node(text, id)
+        self.queue().create_text_node(text, id)
+    }
+
+    /// Create a new comment node. Comments are useful as placeholders for conditionally rendered nodes and fragments.
+    ///
+    /// Example:
+    /// ```no_run
+    /// # use sledgehammer::*;
+    /// let mut channel = MsgChannel::default();
+    /// // create a comment node to mark where a conditional node will be inserted
+    /// channel.create_comment("placeholder", Some(NodeId(0)));
+    /// channel.flush();
+    /// ```
+    pub fn create_comment(&mut self, text: impl WritableText, id: Option<NodeId>) {
+        self.queue().create_comment(text, id)
     }
 
     /// Create a new element node
     ///
     /// Example:
     /// ```no_run
+    /// # use sledgehammer::*;
     /// let mut channel = MsgChannel::default();
     /// // create a <div> element
     /// channel.create_element("div", None);
     /// channel.flush();
     /// ```
     pub fn create_element<'a, 'b>(&mut self, tag: impl IntoElement<'a, 'b>, id: Option<NodeId>) {
-        self.batch.create_element(tag, id)
+        self.queue().create_element(tag, id)
+    }
+
+    /// Create a new document fragment. Nodes can be appended to the fragment with [`MsgChannel::append_child`], and all of the nodes in the fragment are moved at once when the fragment is appended or inserted.
+    ///
+    /// Example:
+    /// ```no_run
+    /// # use sledgehammer::*;
+    /// let mut channel = MsgChannel::default();
+    /// channel.create_fragment(Some(NodeId(0)));
+    /// // append a <h1> and <p> element to the fragment
+    /// channel.create_element("h1", None);
+    /// channel.append_child(MaybeId::Node(NodeId(0)), MaybeId::LastNode);
+    /// channel.create_element("p", None);
+    /// channel.append_child(MaybeId::Node(NodeId(0)), MaybeId::LastNode);
+    /// // insert both elements into the node with the id 1 at once
+    /// channel.append_child(MaybeId::Node(NodeId(1)), MaybeId::Node(NodeId(0)));
+    /// channel.flush();
+    /// ```
+    pub fn create_fragment(&mut self, id: Option<NodeId>) {
+        self.queue().create_fragment(id)
+    }
+
+    /// Build a number of nodes into a template element that is held by the interpreter. The template can be instantiated any number of times with [`MsgChannel::clone_template`].
+    ///
+    /// Ids assigned to nodes in the template refer to the nodes inside of the template, not to the clones.
+    ///
+    /// Example:
+    /// ```no_run
+    /// # use sledgehammer::*;
+    /// let mut channel = MsgChannel::default();
+    /// // create a template with two root nodes
+    /// channel.create_template(
+    ///     TemplateId(0),
+    ///     &[
+    ///         ElementBuilder::new(Element::h1.into()).into(),
+    ///         ElementBuilder::new(Element::p.into()).into(),
+    ///     ],
+    /// );
+    /// channel.flush();
+    /// ```
+    pub fn create_template(&mut self, id: TemplateId, roots: &[NodeBuilder]) {
+        self.queue().create_template(id, roots)
+    }
+
+    /// Clone the content of a template into a new document fragment. The fragment becomes the last node and contains a copy of every root of the template.
+    ///
+    /// Example:
+    /// ```no_run
+    /// # use sledgehammer::*;
+    /// let mut channel = MsgChannel::default();
+    /// channel.create_template(
+    ///     TemplateId(0),
+    ///     &[
+    ///         ElementBuilder::new(Element::h1.into()).into(),
+    ///         ElementBuilder::new(Element::p.into()).into(),
+    ///     ],
+    /// );
+    /// // create a copy of the template
+    /// channel.clone_template(TemplateId(0), None);
+    /// // move to the <h1> element and store it with the id 1
+    /// channel.first_child();
+    /// channel.store_with_id(NodeId(1));
+    /// // move back to the fragment and append both roots to the node with the id 0
+    /// channel.parent_node();
+    /// channel.append_child(MaybeId::Node(NodeId(0)), MaybeId::LastNode);
+    /// channel.flush();
+    /// ```
+    pub fn clone_template(&mut self, id: TemplateId, new_id: Option<NodeId>) {
+        self.queue().clone_template(id, new_id)
+    }
+
+    /// Release the template stored with an id. The id can be reused for another template afterwards.
+    ///
+    /// Example:
+    /// ```no_run
+    /// # use sledgehammer::*;
+    /// let mut channel = MsgChannel::default();
+    /// channel.create_template(TemplateId(0), &[ElementBuilder::new(Element::p.into()).into()]);
+    /// channel.clone_template(TemplateId(0), None);
+    /// // the template can be garbage collected after the id is released
+    /// channel.free_template(TemplateId(0));
+    /// channel.flush();
+    /// ```
+    pub fn free_template(&mut self, id: TemplateId) {
+        self.queue().free_template(id)
+    }
+
+    /// Attach a shadow root to a node and store the shadow root with the given id. The shadow root becomes the last node.
+    ///
+    /// Nodes can be added to the shadow root like any other node. Light dom children of the host can be projected into the shadow root with `<slot>` elements.
+    ///
+    /// Example:
+    /// ```no_run
+    /// # use sledgehammer::*;
+    /// let mut channel = MsgChannel::default();
+    /// channel.create_element("my-element", Some(NodeId(0)));
+    /// // attach a shadow root to the <my-element> element and store it with the id 1
+    /// channel.attach_shadow(MaybeId::Node(NodeId(0)), ShadowRootMode::Open, NodeId(1));
+    /// // render a slot into the shadow root
+    /// channel.build_full_element(
+    ///     ElementBuilder::new(Element::slot.into())
+    ///         .attrs(&[(Attribute::name.into(), "title")]),
+    /// );
+    /// channel.append_child(MaybeId::Node(NodeId(1)), MaybeId::LastNode);
+    /// channel.flush();
+    /// ```
+    pub fn attach_shadow(&mut self, root: MaybeId, mode: ShadowRootMode, id: NodeId) {
+        self.queue().attach_shadow(root, mode, id)
+    }
+
+    /// Create a new constructable style sheet from css and store it with the given id. The style sheet can be shared between many shadow roots with [`MsgChannel::adopt_style_sheet`].
+    ///
+    /// Example:
+    /// ```no_run
+    /// # use sledgehammer::*;
+    /// let mut channel = MsgChannel::default();
+    /// channel.create_style_sheet("p { color: blue; }", StyleSheetId(0));
+    /// channel.flush();
+    /// ```
+    pub fn create_style_sheet(&mut self, css: impl WritableText, id: StyleSheetId) {
+        self.queue().create_style_sheet(css, id)
+    }
+
+    /// Add a style sheet to the adopted style sheets of a shadow root or document.
+    ///
+    /// Example:
+    /// ```no_run
+    /// # use sledgehammer::*;
+    /// let mut channel = MsgChannel::default();
+    /// channel.create_style_sheet("p { color: blue; }", StyleSheetId(0));
+    /// channel.create_element("my-element", Some(NodeId(0)));
+    /// channel.attach_shadow(MaybeId::Node(NodeId(0)), ShadowRootMode::Open, NodeId(1));
+    /// // style the shadow root with the style sheet
+    /// channel.adopt_style_sheet(MaybeId::Node(NodeId(1)), StyleSheetId(0));
+    /// channel.flush();
+    /// ```
+    pub fn adopt_style_sheet(&mut self, root: MaybeId, style_sheet: StyleSheetId) {
+        self.queue().adopt_style_sheet(root, style_sheet)
+    }
+
+    /// Release the style sheet stored with an id. Documents and shadow roots that adopted the style sheet keep it, and the id can be reused for another style sheet afterwards.
+    ///
+    /// Example:
+    /// ```no_run
+    /// # use sledgehammer::*;
+    /// let mut channel = MsgChannel::default();
+    /// channel.create_style_sheet("p { color: blue; }", StyleSheetId(0));
+    /// channel.create_element("my-element", Some(NodeId(0)));
+    /// channel.attach_shadow(MaybeId::Node(NodeId(0)), ShadowRootMode::Open, NodeId(1));
+    /// channel.adopt_style_sheet(MaybeId::Node(NodeId(1)), StyleSheetId(0));
+    /// // the shadow root keeps the style sheet after the id is released
+    /// channel.free_style_sheet(StyleSheetId(0));
+    /// channel.flush();
+    /// ```
+    pub fn free_style_sheet(&mut self, id: StyleSheetId) {
+        self.queue().free_style_sheet(id)
     }
 
     /// Set the textcontent of a node.
     ///
     /// Example:
     /// ```no_run
+    /// # use sledgehammer::*;
     /// let mut channel = MsgChannel::default();
     /// // create a text node with the text "Hello World"
     /// channel.create_text_node("Hello ", None);
     /// // set the text content of the text node to "Hello World!!!"
-    /// channel.set_text_content("World!!!", MaybeId::LastNode);
+    /// channel.set_text("World!!!", MaybeId::LastNode);
     /// channel.flush();
     /// ```
     pub fn set_text(&mut self, text: impl WritableText, root: MaybeId) {
-        self.batch.set_text(text, root)
+        self.queue().set_text(text, root)
+    }
+
+    /// Append text to the data of a text or comment node.
+    ///
+    /// Example:
+    /// ```no_run
+    /// # use sledgehammer::*;
+    /// let mut channel = MsgChannel::default();
+    /// channel.create_text_node("Hello", None);
+    /// // the text node now contains "Hello World"
+    /// channel.append_data(" World", MaybeId::LastNode);
+    /// channel.flush();
+    /// ```
+    pub fn append_data(&mut self, text: impl WritableText, root: MaybeId) {
+        self.queue().append_data(text, root)
+    }
+
+    /// Insert text into the data of a text or comment node. The offset is in UTF-16 code units, see [`utf16_offset`](crate::utf16_offset).
+    ///
+    /// Example:
+    /// ```no_run
+    /// # use sledgehammer::*;
+    /// let mut channel = MsgChannel::default();
+    /// channel.create_text_node("Hello World", None);
+    /// // the text node now contains "Hello big World"
+    /// channel.insert_data(utf16_offset("Hello World", 6), "big ", MaybeId::LastNode);
+    /// channel.flush();
+    /// ```
+    pub fn insert_data(&mut self, offset: u32, text: impl WritableText, root: MaybeId) {
+        self.queue().insert_data(offset, text, root)
+    }
+
+    /// Delete a range of the data of a text or comment node. The offset and count are in UTF-16 code units, see [`utf16_offset`](crate::utf16_offset).
+    ///
+    /// Example:
+    /// ```no_run
+    /// # use sledgehammer::*;
+    /// let mut channel = MsgChannel::default();
+    /// channel.create_text_node("Hello World", None);
+    /// // the text node now contains "Hello"
+    /// channel.delete_data(5, 6, MaybeId::LastNode);
+    /// channel.flush();
+    /// ```
+    pub fn delete_data(&mut self, offset: u32, count: u32, root: MaybeId) {
+        self.queue().delete_data(offset, count, root)
+    }
+
+    /// Replace a range of the data of a text or comment node with new text. The offset and count are in UTF-16 code units, see [`utf16_offset`](crate::utf16_offset).
+    ///
+    /// Example:
+    /// ```no_run
+    /// # use sledgehammer::*;
+    /// let mut channel = MsgChannel::default();
+    /// channel.create_text_node("Hello World", None);
+    /// // the text node now contains "Hello Rust"
+    /// channel.replace_data(6, 5, "Rust", MaybeId::LastNode);
+    /// channel.flush();
+    /// ```
+    pub fn replace_data(
+        &mut self,
+        offset: u32,
+        count: u32,
+        text: impl WritableText,
+        root: MaybeId,
+    ) {
+        self.queue().replace_data(offset, count, text, root)
     }
 
     /// Set the value of a node's attribute.
     ///
     /// Example:
     /// ```no_run
+    /// # use sledgehammer::*;
     /// let mut channel = MsgChannel::default();
     /// // create a <div> element
     /// channel.create_element("div", None);
@@ -295,13 +828,14 @@ impl MsgChannel {
         value: impl WritableText,
         root: MaybeId,
     ) {
-        self.batch.set_attribute(attr, value, root)
+        self.queue().set_attribute(attr, value, root)
     }
 
     /// Remove an attribute from a node.
     ///
     /// Example:
     /// ```no_run
+    /// # use sledgehammer::*;
     /// let mut channel = MsgChannel::default();
     /// // create a <div> element
     /// channel.create_element("div", None);
@@ -311,28 +845,70 @@ impl MsgChannel {
     /// channel.flush();
     /// ```
     pub fn remove_attribute<'a, 'b>(&mut self, attr: impl IntoAttribue<'a, 'b>, root: MaybeId) {
-        self.batch.remove_attribute(attr, root)
+        self.queue().remove_attribute(attr, root)
+    }
+
+    /// Set the inner html of a node. If an id is given, the first child of the node after parsing the html is stored with that id.
+    ///
+    /// The html is inserted without sanitization, so it should only be used with trusted markup.
+    ///
+    /// Example:
+    /// ```no_run
+    /// # use sledgehammer::*;
+    /// let mut channel = MsgChannel::default();
+    /// channel.create_element("div", None);
+    /// // replace the children of the <div> element with the parsed html and store the <h1> with the id 0
+    /// channel.set_inner_html("<h1>Title</h1><p>Body</p>", MaybeId::LastNode, Some(NodeId(0)));
+    /// channel.flush();
+    /// ```
+    pub fn set_inner_html(&mut self, html: impl WritableText, root: MaybeId, id: Option<NodeId>) {
+        self.queue().set_inner_html(html, root, id)
+    }
+
+    /// Parse html and insert it at the given position relative to a node. If an id is given, the first node that was inserted is stored with that id.
+    ///
+    /// The html is inserted without sanitization, so it should only be used with trusted markup.
+    ///
+    /// Example:
+    /// ```no_run
+    /// # use sledgehammer::*;
+    /// let mut channel = MsgChannel::default();
+    /// channel.create_element("div", None);
+    /// // insert the html after the last child of the <div> element and store the <p> with the id 0
+    /// channel.insert_adjacent_html(InsertPosition::BeforeEnd, "<p>Body</p>", MaybeId::LastNode, Some(NodeId(0)));
+    /// channel.flush();
+    /// ```
+    pub fn insert_adjacent_html(
+        &mut self,
+        position: InsertPosition,
+        html: impl WritableText,
+        root: MaybeId,
+        id: Option<NodeId>,
+    ) {
+        self.queue().insert_adjacent_html(position, html, root, id)
     }
 
     /// Clone a node and store it with a new id.
     ///
     /// Example:
     /// ```no_run
+    /// # use sledgehammer::*;
     /// let mut channel = MsgChannel::default();
     /// // create a <div> element
     /// channel.create_element("div", None);
     /// // clone the <div> element and store it with the id 1
-    /// channel.clone_node(MaybeId::LastNode, Some(NodeId(1)));
+    /// channel.clone_node(MaybeId::LastNode, MaybeId::Node(NodeId(1)));
     /// channel.flush();
     /// ```
     pub fn clone_node(&mut self, id: MaybeId, new_id: MaybeId) {
-        self.batch.clone_node(id, new_id)
+        self.queue().clone_node(id, new_id)
     }
 
     /// Move the last node to the first child
     ///
     /// Example:
     /// ```no_run
+    /// # use sledgehammer::*;
     /// let mut channel = MsgChannel::default();
     /// // create a element: <div><p></p></div>
     /// channel.build_full_element(
@@ -348,13 +924,14 @@ impl MsgChannel {
     /// channel.flush();
     /// ```
     pub fn first_child(&mut self) {
-        self.batch.first_child()
+        self.queue().first_child()
     }
 
     /// Move the last node to the next sibling
     ///
     /// Example:
     /// ```no_run
+    /// # use sledgehammer::*;
     /// let mut channel = MsgChannel::default();
     /// // create a element: <div><h1></h1><p></p></div>
     /// channel.build_full_element(
@@ -363,6 +940,7 @@ impl MsgChannel {
     ///             ElementBuilder::new(Element::h1.into())
     ///                 .into(),
     ///             ElementBuilder::new(Element::p.into())
+    ///                 .into(),
     ///         ]),
     /// );
     /// // move from the <div> to the <h1>
@@ -373,13 +951,14 @@ impl MsgChannel {
     /// channel.flush();
     /// ```
     pub fn next_sibling(&mut self) {
-        self.batch.next_sibling()
+        self.queue().next_sibling()
     }
 
     /// Move the last node to the parent node
     ///
     /// Example:
     /// ```no_run
+    /// # use sledgehammer::*;
     /// let mut channel = MsgChannel::default();
     /// // create a element: <div><p></p></div>
     /// channel.build_full_element(
@@ -398,13 +977,14 @@ impl MsgChannel {
     /// channel.flush();
     /// ```
     pub fn parent_node(&mut self) {
-        self.batch.parent_node()
+        self.queue().parent_node()
     }
 
     /// Store the last node with the given id. This is useful when traversing the document tree.
     ///
     /// Example:
     /// ```no_run
+    /// # use sledgehammer::*;
     /// let mut channel = MsgChannel::default();
     /// // create a element without an id
     /// channel.create_element("div", None);
@@ -413,13 +993,14 @@ impl MsgChannel {
     /// channel.flush();
     /// ```
     pub fn store_with_id(&mut self, id: NodeId) {
-        self.batch.store_with_id(id)
+        self.queue().store_with_id(id)
     }
 
     /// Set the last node to the given id. The last node can be used to traverse the document tree without passing objects between wasm and js every time.
     ///
     /// Example:
     /// ```no_run
+    /// # use sledgehammer::*;
     /// let mut channel = MsgChannel::default();
     /// // create a element: <div><h1><h2></h2></h1><p></p></div>
     /// channel.build_full_element(
@@ -427,7 +1008,7 @@ impl MsgChannel {
     ///         .children(&[
     ///             ElementBuilder::new(Element::h1.into())
     ///                 .children(&[
-    ///                     ElementBuilder::new(Element::h2.into())
+    ///                     ElementBuilder::new("h2".into())
     ///                         .into(),
     ///                 ]).into(),
     ///             ElementBuilder::new(Element::p.into())
@@ -449,13 +1030,14 @@ impl MsgChannel {
     /// channel.flush();
     /// ```
     pub fn set_last_node(&mut self, id: NodeId) {
-        self.batch.set_last_node(id)
+        self.queue().set_last_node(id)
     }
 
     /// Build a full element, slightly more efficent than creating the element creating the element with `create_element` and then setting the attributes.
     ///
     /// Example:
     /// ```rust
+    /// # use sledgehammer::*;
     /// let mut channel = MsgChannel::default();
     /// // create an element using sledgehammer
     /// channel.build_full_element(
@@ -471,13 +1053,14 @@ impl MsgChannel {
     /// channel.flush();
     /// ```
     pub fn build_full_element(&mut self, el: ElementBuilder) {
-        self.batch.build_full_element(el)
+        self.queue().build_full_element(el)
     }
 
     /// Build a text node
     ///
     /// Example:
     /// ```rust
+    /// # use sledgehammer::*;
     /// let mut channel = MsgChannel::default();
     /// // create an element using sledgehammer
     /// channel.build_text_node(
@@ -486,27 +1069,86 @@ impl MsgChannel {
     /// channel.flush();
     /// ```
     pub fn build_text_node(&mut self, text: TextBuilder) {
-        self.batch.build_text_node(text)
+        self.queue().build_text_node(text)
     }
 
-    /// Set a style property on a node.
+    /// Build a comment node
     ///
     /// Example:
     /// ```rust
+    /// # use sledgehammer::*;
+    /// let mut channel = MsgChannel::default();
+    /// channel.build_comment(
+    ///     CommentBuilder::new("placeholder").id(NodeId(0))
+    /// );
+    /// channel.flush();
+    /// ```
+    pub fn build_comment(&mut self, comment: CommentBuilder) {
+        self.queue().build_comment(comment)
+    }
+
+    /// Set a style property on a node. Custom properties can be set with [`CustomProperty`](crate::CustomProperty).
+    ///
+    /// Example:
+    /// ```rust
+    /// # use sledgehammer::*;
     /// let mut channel = MsgChannel::default();
     /// channel.create_element("div", None);
     /// // set the style property "color" to "blue"
-    /// channel.set_style("color", "blue", MaybeId::LastNode);
+    /// channel.set_style(Style::color, "blue", MaybeId::LastNode);
+    /// // set the style property "--main-color" to "red"
+    /// channel.set_style(CustomProperty("main-color"), "red", MaybeId::LastNode);
+    /// channel.flush();
+    /// ```
+    pub fn set_style<'a>(
+        &mut self,
+        style: impl IntoStyle<'a>,
+        value: impl WritableText,
+        id: MaybeId,
+    ) {
+        self.queue().set_style(style, value, id)
+    }
+
+    /// Set a style property on a node with the `important` priority.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use sledgehammer::*;
+    /// let mut channel = MsgChannel::default();
+    /// channel.create_element("div", None);
+    /// // set the style property "color" to "blue !important"
+    /// channel.set_important_style(Style::color, "blue", MaybeId::LastNode);
     /// channel.flush();
     /// ```
-    pub fn set_style(&mut self, style: &str, value: &str, id: MaybeId) {
-        self.batch.set_style(style, value, id)
+    pub fn set_important_style<'a>(
+        &mut self,
+        style: impl IntoStyle<'a>,
+        value: impl WritableText,
+        id: MaybeId,
+    ) {
+        self.queue().set_important_style(style, value, id)
+    }
+
+    /// Set a number of style properties on a node. This only looks up the node once.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use sledgehammer::*;
+    /// let mut channel = MsgChannel::default();
+    /// channel.create_element("div", None);
+    /// // set the color and width of the div
+    /// channel.set_styles(&[(Style::color, "blue"), (Style::width, "100px")], MaybeId::LastNode);
+    /// channel.flush();
+    /// ```
+    pub fn set_styles<'a, S: IntoStyle<'a> + Copy>(&mut self, styles: &[(S, &str)], id: MaybeId) {
+        self.queue().set_styles(styles, id)
     }
 
     /// Remove a style property from a node.
     ///
     /// Example:
     /// ```rust
+    /// # use sledgehammer::*;
     /// let mut channel = MsgChannel::default();
     /// channel.create_element("div", None);
     /// channel.set_style("color", "blue", MaybeId::LastNode);
@@ -514,14 +1156,16 @@ impl MsgChannel {
     /// channel.remove_style("color", MaybeId::LastNode);
     /// channel.flush();
     /// ```
-    pub fn remove_style(&mut self, style: &str, id: MaybeId) {
-        self.batch.remove_style(style, id)
+    pub fn remove_style<'a>(&mut self, style: impl IntoStyle<'a>, id: MaybeId) {
+        self.queue().remove_style(style, id)
     }
 
     /// Adds a batch of operations to the current batch.
     ///
     /// Example:
     /// ```rust
+    /// # use sledgehammer::*;
+    /// # use sledgehammer::sledgehammer_encoder::batch::Batch;
     /// let mut channel = MsgChannel::default();
     /// let mut batch = Batch::default();
     /// batch.create_element("div", None);
@@ -530,89 +1174,161 @@ impl MsgChannel {
     /// channel.flush();
     /// ```
     pub fn append(&mut self, batch: Batch) {
-        self.batch.append(batch);
+        self.queue().append(batch);
     }
 
     /// IMPORTANT: This method is exicuted immediatly and does not wait for the next flush
     ///
     /// Run a batch of operations on the DOM immediately. This only runs the operations that are in the batch, not the operations that are queued in the [`MsgChannel`].
+    /// A [`StaticBatch`](sledgehammer_encoder::StaticBatch) built by another release of sledgehammer-prebuild with a different format version is not run, and the error describes the mismatch.
     ///
     /// Example:
     /// ```rust
+    /// # use sledgehammer::*;
+    /// # use sledgehammer::sledgehammer_encoder::batch::Batch;
     /// let mut channel = MsgChannel::default();
     /// let mut batch = Batch::default();
     /// batch.create_element("div", None);
     /// // add the batch to the channel
-    /// channel.run_batch(&batch.finalize());
+    /// channel.run_batch(&batch.finalize()).unwrap();
+    /// ```
+    pub fn run_batch(&mut self, batch: impl PreparedBatch) -> Result<(), FlushError> {
+        batch.check_version().map_err(|err| FlushError {
+            op_index: 0,
+            op: Op::first_code(batch.msg()),
+            node: None,
+            message: err.to_string(),
+        })?;
+        if let Some(recording) = &mut self.recording {
+            recording.record_batch(batch.msg(), batch.str());
+        }
+        self.backend.run_batch(batch.msg(), batch.str())
+    }
+
+    /// Start recording every batch the channel runs and every node set with [`MsgChannel::set_node`], discarding any recording in progress.
+    ///
+    /// The recording can be saved with [`Recording::write_to`] and replayed with [`MsgChannel::replay`] or, without a browser, with a [`NativeInterpreter`](sledgehammer_encoder::NativeInterpreter).
+    ///
+    /// Example:
+    /// ```no_run
+    /// # use sledgehammer::*;
+    /// let mut channel = MsgChannel::default();
+    /// channel.start_recording();
+    /// channel.create_element("div", None);
+    /// channel.flush().unwrap();
+    /// let mut log = Vec::new();
+    /// channel.stop_recording().unwrap().write_to(&mut log).unwrap();
     /// ```
-    pub fn run_batch(&mut self, batch: impl PreparedBatch) {
-        run_batch(batch.msg(), batch.str(), &mut self.last_mem_size);
+    pub fn start_recording(&mut self) {
+        self.recording = Some(Recording::default());
+    }
+
+    /// Stop recording and return what was recorded since [`MsgChannel::start_recording`]
+    pub fn stop_recording(&mut self) -> Option<Recording> {
+        self.recording.take()
+    }
+
+    /// IMPORTANT: This method is exicuted immediatly and does not wait for the next flush
+    ///
+    /// Replay a recording into this channel. Queued operations are not flushed first.
+    /// Every node that was set in the recording is replaced by the node `node` returns for its id and node name.
+    ///
+    /// The recording only has the names of the nodes that were set, not their content, so the replayed DOM only matches the original if those nodes were empty when the recording started. See the [`record`](sledgehammer_encoder::record) module.
+    ///
+    /// Example:
+    /// ```no_run
+    /// # use sledgehammer::*;
+    /// # use wasm_bindgen::JsCast;
+    /// let document = web_sys::window().unwrap().document().unwrap();
+    /// // a log saved with Recording::write_to
+    /// let log: &[u8] = &[];
+    /// let recording = Recording::read_from(log).unwrap();
+    /// let mut channel = MsgChannel::with_backend(JsBackend::default());
+    /// channel
+    ///     .replay(&recording, |_, name| match name {
+    ///         // nodes that are not elements have names like #text that create_element rejects
+    ///         "#text" => document.create_text_node("").into(),
+    ///         "#comment" => document.create_comment("").into(),
+    ///         "#document-fragment" => document.create_document_fragment().into(),
+    ///         "#document" => document.clone().into(),
+    ///         name => document.create_element(name).unwrap().into(),
+    ///     })
+    ///     .unwrap();
+    /// ```
+    pub fn replay(
+        &mut self,
+        recording: &Recording,
+        node: impl FnMut(NodeId, &str) -> B::Node,
+    ) -> Result<(), FlushError> {
+        recording.replay(&mut ChannelReplay {
+            channel: self,
+            node,
+        })
     }
 }
 
-fn run_batch(msg: &[u8], str_buf: &[u8], last_mem_size: &mut usize) {
-    debug_assert_eq!(0usize.to_le_bytes().len(), 32 / 8);
-    let msg_ptr = msg.as_ptr() as usize;
-    let str_ptr = str_buf.as_ptr() as usize;
-    // the pointer will only be updated when the message vec is resized, so we have a flag to check if the pointer has changed to avoid unnecessary decoding
-    if unsafe { *MSG_METADATA_PTR } == 255 {
-        // this is the first message, so we need to encode all the metadata
-        unsafe {
-            let mut_ptr_ptr: *mut usize = std::mem::transmute(MSG_PTR_PTR);
-            *mut_ptr_ptr = msg_ptr;
-            let mut_metadata_ptr: *mut u8 = std::mem::transmute(MSG_METADATA_PTR);
-            // the first bit encodes if the msg pointer has changed
-            *mut_metadata_ptr = 1;
-            let mut_str_ptr_ptr: *mut usize = std::mem::transmute(STR_PTR_PTR);
-            *mut_str_ptr_ptr = str_ptr as usize;
-            // the second bit encodes if the str pointer has changed
-            *mut_metadata_ptr |= 2;
-        }
-    } else {
-        if unsafe { *MSG_PTR_PTR } != msg_ptr {
-            unsafe {
-                let mut_ptr_ptr: *mut usize = std::mem::transmute(MSG_PTR_PTR);
-                *mut_ptr_ptr = msg_ptr;
-                let mut_ptr_ptr: *mut u8 = std::mem::transmute(MSG_METADATA_PTR);
-                // the first bit encodes if the msg pointer has changed
-                *mut_ptr_ptr = 1;
-            }
-        } else {
-            unsafe {
-                let mut_ptr_ptr: *mut u8 = std::mem::transmute(MSG_METADATA_PTR);
-                // the first bit encodes if the msg pointer has changed
-                *mut_ptr_ptr = 0;
-            }
-        }
-        if unsafe { *STR_PTR_PTR } != str_ptr {
-            unsafe {
-                let mut_str_ptr_ptr: *mut usize = std::mem::transmute(STR_PTR_PTR);
-                *mut_str_ptr_ptr = str_ptr as usize;
-                let mut_metadata_ptr: *mut u8 = std::mem::transmute(MSG_METADATA_PTR);
-                // the second bit encodes if the str pointer has changed
-                *mut_metadata_ptr |= 1 << 1;
-            }
-        }
+impl MsgChannel<JsBackend> {
+    /// Log every operation the interpreter runs to the console as the operation name, its flags, the bytes of its operands, and the strings it read.
+    ///
+    /// Only available with the `debug-interpreter` feature.
+    #[cfg(feature = "debug-interpreter")]
+    pub fn set_trace(&mut self, trace: bool) {
+        self.backend.js_interpreter.SetTrace(trace);
     }
-    unsafe {
-        let mut_metadata_ptr: *mut u8 = std::mem::transmute(MSG_METADATA_PTR);
-        if !str_buf.is_empty() {
-            // the third bit encodes if there is any strings
-            *mut_metadata_ptr |= 1 << 2;
-            let mut_str_len_ptr: *mut usize = std::mem::transmute(STR_LEN_PTR);
-            *mut_str_len_ptr = str_buf.len() as usize;
-            if *mut_str_len_ptr < 100 {
-                // the fourth bit encodes if the strings are entirely ascii and small
-                *mut_metadata_ptr |= (str_buf.is_ascii() as u8) << 3;
-            }
+
+    /// IMPORTANT: This method is exicuted immediatly and does not wait for the next flush
+    ///
+    /// Run a batch that was encoded somewhere else, like a Web Worker, and transferred to this thread. See the [`transfer`](crate::transfer) module for the format of the buffers.
+    ///
+    /// Example:
+    /// ```no_run
+    /// # use sledgehammer::*;
+    /// # use sledgehammer::sledgehammer_encoder::batch::Batch;
+    /// let mut channel = MsgChannel::with_backend(JsBackend::default());
+    /// // the buffers are usually received from a worker with postMessage
+    /// let mut batch = Batch::default();
+    /// batch.create_element("div", None);
+    /// let transferable = TransferableBatch::new(batch.finalize());
+    /// channel.run_transferred(transferable).unwrap();
+    /// ```
+    pub fn run_transferred(&mut self, batch: TransferableBatch) -> Result<(), FlushError> {
+        let TransferableBatch { msg, str, .. } = &batch;
+        batch.check_version().map_err(|err| FlushError {
+            op_index: 0,
+            // the code of the first operation is in the first five bytes
+            op: Op::first_code(&Uint8Array::new(msg).subarray(0, 5).to_vec()),
+            node: None,
+            message: err.to_string(),
+        })?;
+        if let Some(recording) = &mut self.recording {
+            recording.record_batch(
+                &Uint8Array::new(msg).to_vec(),
+                &Uint8Array::new(str).to_vec(),
+            );
         }
+        self.backend.run_transferred(batch.msg, batch.str)
     }
-    let new_mem_size = core::arch::wasm32::memory_size(0);
-    // we need to update the memory if the memory has grown
-    if new_mem_size != *last_mem_size {
-        *last_mem_size = new_mem_size;
-        update_last_memory(wasm_bindgen::memory());
+}
+
+/// Replays a recording into a [`MsgChannel`], creating nodes for the recorded nodes with a closure
+struct ChannelReplay<'a, B: DomBackend, F> {
+    channel: &'a mut MsgChannel<B>,
+    node: F,
+}
+
+impl<B: DomBackend, F: FnMut(NodeId, &str) -> B::Node> Replay for ChannelReplay<'_, B, F> {
+    type Error = FlushError;
+
+    fn replay_set_node(&mut self, id: NodeId, name: &str) -> Result<(), FlushError> {
+        let node = (self.node)(id, name);
+        self.channel.set_node(id, node);
+        Ok(())
     }
 
-    work_last_created();
+    fn replay_batch(&mut self, msg: &[u8], str: &[u8]) -> Result<(), FlushError> {
+        if let Some(recording) = &mut self.channel.recording {
+            recording.record_batch(msg, str);
+        }
+        self.channel.backend.run_batch(msg, str)
+    }
 }
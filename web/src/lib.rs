@@ -24,6 +24,9 @@
 //!
 //! # Getting started
 //! - All operations go through a [`MsgChannel`] which handles the communication with js.
+//! - Batches are applied by a [`DomBackend`]: the javascript interpreter in the browser, or an in-memory dom with [`MemoryBackend`]. On targets other than wasm32 channels use the in-memory dom by default, so code that uses a [`MsgChannel`] can be unit tested with `cargo test`.
+//! - To render into a dom in another process, like a desktop webview, send batches with a [`StreamBackend`] and apply them with the `StreamReceiver` in `stream_receiver.js`.
+//! - Enable the `debug-interpreter` feature while developing to run batches with an interpreter that checks every read from the batch and can log every operation with `MsgChannel::set_trace`.
 //!
 //!# Benchmarks
 //!
@@ -70,46 +73,70 @@
 //!## Minimize passing ids
 //!
 //!- A common set of operations for webframeworks to perform is traversing dom nodes after cloning them. Instead of assigning an id to every node, sledgehammer allows you to perform operations on the last node that was created or navigated to. This means traversing id takes only one byte per operation instead of 5.
+//!
+//!# Contributing
+//!
+//!The javascript interpreters in `web` are generated from the operations in sledgehammer-encoder and checked in. After you change an operation, regenerate them with `SLEDGEHAMMER_BLESS=1 cargo test -p sledgehammer --test interpreters` and commit the result. CI fails until you do. See `CONTRIBUTING.md` in the repository.
 
 #![allow(non_camel_case_types)]
 
+pub mod backend;
 pub mod channel;
+pub mod schedule;
+pub mod transfer;
 
-pub use channel::MsgChannel;
+pub use backend::{DefaultBackend, DomBackend, JsBackend, MemoryBackend, StreamBackend};
+pub use channel::{FlushError, FlushThreshold, MsgChannel};
+pub use schedule::{FrameFlushed, ScheduledChannel};
 pub use sledgehammer_encoder::{
-    Attribute, Element, ElementBuilder, IntoAttribue, IntoElement, MaybeId, NodeBuilder, NodeId,
-    StaticBatch, TextBuilder, WritableText,
+    utf16_offset, Attribute, CommentBuilder, CustomProperty, Element, ElementBuilder,
+    InsertPosition, IntoAttribue, IntoElement, IntoStyle, MathMlAttribute, MathMlElement, MaybeId,
+    NativeInterpreter, NodeBuilder, NodeId, Recording, Replay, ShadowRootMode, StaticBatch, Style,
+    StyleSheetId, SvgAttribute, SvgElement, TemplateId, TextBuilder, WritableText,
 };
+pub use transfer::TransferableBatch;
 
 pub use sledgehammer_encoder;
 
+use js_sys::ArrayBuffer;
 use wasm_bindgen::prelude::*;
-use web_sys::Node;
-
-#[used]
-static mut MSG_PTR: usize = 0;
-#[used]
-static mut MSG_PTR_PTR: *const usize = unsafe { &MSG_PTR } as *const usize;
-#[used]
-static mut MSG_POS_UPDATED: u8 = 255;
-#[used]
-static mut MSG_METADATA_PTR: *const u8 = unsafe { &MSG_POS_UPDATED } as *const u8;
-#[used]
-static mut STR_PTR: usize = 0;
-#[used]
-static mut STR_PTR_PTR: *const usize = unsafe { &STR_PTR } as *const usize;
-#[used]
-static mut STR_LEN: usize = 0;
-#[used]
-static mut STR_LEN_PTR: *const usize = unsafe { &STR_LEN } as *const usize;
-
-#[wasm_bindgen(module = "/interpreter_opt.js")]
-// #[wasm_bindgen(module = "/interpreter.js")]
-extern "C" {
-    fn work_last_created();
+use web_sys::{Document, Node};
+
+/// The pointers and flags the interpreter reads to find the next batch. Every [`JsBackend`] owns one, so channels do not share any state with each other.
+#[repr(C)]
+pub(crate) struct Metadata {
+    pub(crate) msg_ptr: usize,
+    pub(crate) str_ptr: usize,
+    pub(crate) str_len: usize,
+    /// 255 until the first batch is sent
+    pub(crate) flags: u8,
+}
 
-    fn update_last_memory(mem: JsValue);
+// the interpreter reads the pointers in the metadata as u32s
+#[cfg(target_arch = "wasm32")]
+const _: () = assert!(std::mem::size_of::<usize>() == 4);
 
+impl Default for Metadata {
+    fn default() -> Self {
+        Self {
+            msg_ptr: 0,
+            str_ptr: 0,
+            str_len: 0,
+            flags: 255,
+        }
+    }
+}
+
+#[cfg_attr(
+    not(feature = "debug-interpreter"),
+    wasm_bindgen(module = "/interpreter_opt.js")
+)]
+#[cfg_attr(
+    feature = "debug-interpreter",
+    wasm_bindgen(module = "/interpreter_debug.js")
+)]
+// #[wasm_bindgen(module = "/interpreter.js")]
+extern "C" {
     pub(crate) type JsInterpreter;
 
     #[wasm_bindgen(constructor)]
@@ -119,8 +146,19 @@ extern "C" {
         msg_ptr: usize,
         str_ptr: usize,
         str_len_ptr: usize,
+        document: Option<Document>,
     ) -> JsInterpreter;
 
+    #[wasm_bindgen(method)]
+    pub(crate) fn Work(this: &JsInterpreter) -> JsValue;
+
+    #[wasm_bindgen(method)]
+    pub(crate) fn WorkTransferred(
+        this: &JsInterpreter,
+        msg: ArrayBuffer,
+        str: ArrayBuffer,
+    ) -> JsValue;
+
     #[wasm_bindgen(method)]
     pub(crate) fn UpdateMemory(this: &JsInterpreter, mem: JsValue);
 
@@ -130,4 +168,17 @@ extern "C" {
     #[allow(unused)]
     #[wasm_bindgen(method)]
     pub(crate) fn GetNode(this: &JsInterpreter, id: u32) -> Node;
+
+    #[wasm_bindgen(method)]
+    pub(crate) fn FormatVersion(this: &JsInterpreter) -> u8;
+}
+
+#[cfg(feature = "debug-interpreter")]
+#[wasm_bindgen(module = "/interpreter_debug.js")]
+extern "C" {
+    #[wasm_bindgen(method)]
+    pub(crate) fn SetMsgLen(this: &JsInterpreter, len: usize);
+
+    #[wasm_bindgen(method)]
+    pub(crate) fn SetTrace(this: &JsInterpreter, trace: bool);
 }
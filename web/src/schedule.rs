@@ -0,0 +1,110 @@
+//! This module contains the [`ScheduledChannel`] type which flushes a [`MsgChannel`] once per animation frame.
+//!
+//! Instead of deciding when to flush, parts of an application can share a [`ScheduledChannel`], queue operations on it and call [`ScheduledChannel::schedule_flush`]. All operations queued before the next animation frame are flushed together.
+
+use std::{
+    cell::{Cell, RefCell, RefMut},
+    future::Future,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll, Waker},
+};
+
+use wasm_bindgen::{prelude::Closure, JsCast};
+
+use crate::{FlushError, JsBackend, MsgChannel};
+
+struct Shared {
+    channel: RefCell<MsgChannel<JsBackend>>,
+    /// If a flush is already scheduled for the next animation frame
+    scheduled: Cell<bool>,
+    /// The futures waiting for the next animation frame
+    waiting: RefCell<Vec<Rc<FlushSlot>>>,
+}
+
+/// Where the result of a flush is stored for a [`FrameFlushed`] future
+#[derive(Default)]
+struct FlushSlot {
+    result: RefCell<Option<Result<(), FlushError>>>,
+    waker: RefCell<Option<Waker>>,
+}
+
+/// A [`MsgChannel`] that is flushed in a requestAnimationFrame callback. Cloning a [`ScheduledChannel`] creates another handle to the same channel.
+///
+/// Example:
+/// ```no_run
+/// # use sledgehammer::*;
+/// # async fn run() {
+/// let channel = ScheduledChannel::new(MsgChannel::with_backend(JsBackend::default()));
+/// channel.channel().create_element("div", None);
+/// channel.schedule_flush();
+/// // this is flushed in the same frame as the div
+/// channel.channel().create_element("p", None);
+/// let flushed = channel.schedule_flush();
+/// // wait for the div and p to be created
+/// flushed.await.unwrap();
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct ScheduledChannel(Rc<Shared>);
+
+impl ScheduledChannel {
+    /// Wrap a channel so it can be flushed on the next animation frame
+    pub fn new(channel: MsgChannel<JsBackend>) -> Self {
+        Self(Rc::new(Shared {
+            channel: RefCell::new(channel),
+            scheduled: Cell::new(false),
+            waiting: RefCell::new(Vec::new()),
+        }))
+    }
+
+    /// Get the channel to queue operations on. The channel should not be borrowed when the animation frame starts.
+    pub fn channel(&self) -> RefMut<'_, MsgChannel<JsBackend>> {
+        self.0.channel.borrow_mut()
+    }
+
+    /// Flush the channel on the next animation frame. Calling this multiple times before the frame only flushes once.
+    ///
+    /// The returned future resolves with the result of the flush after the operations have been applied. It does not need to be awaited for the flush to happen.
+    pub fn schedule_flush(&self) -> FrameFlushed {
+        if !self.0.scheduled.replace(true) {
+            let shared = self.0.clone();
+            let callback = Closure::once_into_js(move || {
+                shared.scheduled.set(false);
+                let result = shared.channel.borrow_mut().flush();
+                for slot in shared.waiting.take() {
+                    *slot.result.borrow_mut() = Some(result.clone());
+                    if let Some(waker) = slot.waker.take() {
+                        waker.wake();
+                    }
+                }
+            });
+            web_sys::window()
+                .unwrap()
+                .request_animation_frame(callback.unchecked_ref())
+                .unwrap();
+        }
+        let slot = Rc::<FlushSlot>::default();
+        self.0.waiting.borrow_mut().push(slot.clone());
+        FrameFlushed { slot }
+    }
+}
+
+/// A future that resolves after the animation frame a flush was scheduled for has been applied. See [`ScheduledChannel::schedule_flush`].
+pub struct FrameFlushed {
+    slot: Rc<FlushSlot>,
+}
+
+impl Future for FrameFlushed {
+    type Output = Result<(), FlushError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match self.slot.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                *self.slot.waker.borrow_mut() = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
@@ -0,0 +1,87 @@
+//! This module contains the [`TransferableBatch`] type which allows batches to be encoded in a Web Worker and applied on the main thread with [`MsgChannel::run_transferred`](crate::MsgChannel::run_transferred).
+//!
+//! # Wire format
+//!
+//! A transferred batch is two buffers that do not refer to any wasm memory, and the version of the format they were encoded in:
+//! - The message buffer contains the encoded operations starting at byte 0, exactly as they are stored in [`FinalizedBatch::msg`](sledgehammer_encoder::batch::FinalizedBatch). Operations are packed four to a little endian u32, followed by their data, and the batch ends with [`Op::Stop`](sledgehammer_encoder::Op::Stop).
+//! - The string buffer contains the utf-8 encoded strings the operations refer to, in the order they are read.
+//! - The version is the [`FORMAT_VERSION`] of the sledgehammer-encoder that encoded the batch. The worker and the main thread may load different builds, so [`MsgChannel::run_transferred`](crate::MsgChannel::run_transferred) checks it before running the batch.
+//!
+//! [`TransferableBatch::to_message`] puts the three parts in an array that can be posted to another thread, and [`TransferableBatch::from_message`] reads them back.
+//!
+//! Example (the worker scope and message event come from web-sys features this crate does not enable):
+//! ```ignore
+//! // in the worker
+//! let mut batch = Batch::default();
+//! batch.create_element("div", None);
+//! let transferable = TransferableBatch::new(batch.finalize());
+//! worker_scope.post_message_with_transfer(&transferable.to_message(), &transferable.transfer_list()).unwrap();
+//!
+//! // on the main thread
+//! let transferable = TransferableBatch::from_message(&event.data().into());
+//! channel.run_transferred(transferable).unwrap();
+//! ```
+
+use js_sys::{Array, ArrayBuffer, Uint8Array};
+use sledgehammer_encoder::{
+    batch::{PreparedBatch, VersionMismatch},
+    FORMAT_VERSION,
+};
+
+/// A batch copied out of wasm memory into buffers that can be transferred to another thread with `postMessage`.
+pub struct TransferableBatch {
+    /// The encoded operations
+    pub msg: ArrayBuffer,
+    /// The utf-8 encoded strings
+    pub str: ArrayBuffer,
+    /// The version of the format the batch was encoded in
+    pub version: u8,
+}
+
+impl TransferableBatch {
+    /// Copy a batch into new buffers
+    ///
+    /// # Panics
+    ///
+    /// If the batch was encoded in another version of the format, see [`PreparedBatch::check_version`].
+    pub fn new(batch: impl PreparedBatch) -> Self {
+        if let Err(err) = batch.check_version() {
+            panic!("{err}");
+        }
+        Self {
+            msg: Uint8Array::from(batch.msg()).buffer(),
+            str: Uint8Array::from(batch.str()).buffer(),
+            version: batch.version(),
+        }
+    }
+
+    /// The message to pass to `postMessage`: an array of the message buffer, the string buffer and the version
+    pub fn to_message(&self) -> Array {
+        Array::of3(&self.msg, &self.str, &self.version.into())
+    }
+
+    /// Read a message created with [`TransferableBatch::to_message`]. A message without a version, like the two buffers older releases posted, has version 0 so it is rejected when it is run.
+    pub fn from_message(message: &Array) -> Self {
+        Self {
+            msg: message.get(0).into(),
+            str: message.get(1).into(),
+            version: message.get(2).as_f64().map_or(0, |version| version as u8),
+        }
+    }
+
+    /// The list of buffers to pass to `postMessage` so they are moved instead of copied
+    pub fn transfer_list(&self) -> Array {
+        Array::of2(&self.msg, &self.str)
+    }
+
+    /// Check that the batch was encoded in the version of the format this version of sledgehammer reads
+    pub fn check_version(&self) -> Result<(), VersionMismatch> {
+        match self.version {
+            FORMAT_VERSION => Ok(()),
+            found => Err(VersionMismatch {
+                found,
+                expected: FORMAT_VERSION,
+            }),
+        }
+    }
+}
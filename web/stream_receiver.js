@@ -0,0 +1,133 @@
+// Applies a sledgehammer byte stream to a JsInterpreter in a process that did not encode the batches, like a desktop webview or a browser connected to a server.
+// See the stream module of sledgehammer-encoder for the format. The stream can arrive in chunks of any size, and frames are applied as soon as they are complete.
+//
+// Example:
+// import { JsInterpreter } from "./interpreter.js";
+// import { StreamReceiver } from "./stream_receiver.js";
+// const receiver = new StreamReceiver(new JsInterpreter({ buffer: new ArrayBuffer(0) }, 0, 0, 0, 0));
+// socket.binaryType = "arraybuffer";
+// socket.onmessage = (e) => receiver.Push(new Uint8Array(e.data)).forEach(console.error);
+//
+// Events the stream listens for are passed to onEvent(id, name, event). Without onEvent they are ignored.
+
+const MAGIC = [0x53, 0x48, 0x53, 0x54];
+const VERSION = 1;
+
+const BATCH = 0;
+const SET_NODE = 1;
+const LISTEN = 2;
+
+export class StreamReceiver {
+    constructor(interpreter, doc, onEvent) {
+        this.interpreter = interpreter;
+        // the document selectors are looked up in
+        this.doc = doc || interpreter.doc || document;
+        this.onEvent = onEvent;
+        // bytes of a frame that has not been completely received
+        this.pending = new Uint8Array(0);
+        this.headerRead = false;
+        this.decoder = new TextDecoder();
+    }
+
+    // applies every complete frame in the stream so far
+    // returns the errors of the batches that failed as [operation index, opcode, missing node id or undefined, message]
+    Push(chunk) {
+        let bytes = chunk;
+        if (this.pending.length) {
+            bytes = new Uint8Array(this.pending.length + chunk.length);
+            bytes.set(this.pending);
+            bytes.set(chunk, this.pending.length);
+        }
+        const view = new DataView(bytes.buffer, bytes.byteOffset, bytes.byteLength);
+        const errors = [];
+        let pos = 0;
+        if (!this.headerRead) {
+            // the magic bytes, the version of the stream and the version of the batches
+            const headerLen = 6;
+            if (bytes.length < headerLen) {
+                this.pending = bytes.slice();
+                return errors;
+            }
+            for (let i = 0; i < 4; i++) {
+                if (bytes[i] !== MAGIC[i]) {
+                    throw new Error("the stream is not a sledgehammer stream");
+                }
+            }
+            if (bytes[4] !== VERSION) {
+                throw new Error("the stream has version " + bytes[4] + ", but only version " + VERSION + " is supported");
+            }
+            const formatVersion = bytes[5];
+            if (formatVersion !== this.interpreter.FormatVersion()) {
+                throw new Error("the batches in the stream were encoded in version " + formatVersion + " of the sledgehammer format, but the interpreter reads version " + this.interpreter.FormatVersion());
+            }
+            this.headerRead = true;
+            pos = headerLen;
+        }
+        for (; ;) {
+            const end = this.frameEnd(view, pos);
+            if (end === undefined) {
+                break;
+            }
+            switch (bytes[pos]) {
+                case BATCH: {
+                    const msgLen = view.getUint32(pos + 1, true);
+                    const msg = bytes.slice(pos + 5, pos + 5 + msgLen).buffer;
+                    const str = bytes.subarray(pos + 9 + msgLen, end);
+                    const error = this.interpreter.WorkTransferred(msg, str);
+                    if (error !== undefined) {
+                        errors.push(error);
+                    }
+                    break;
+                }
+                case SET_NODE: {
+                    const selector = this.decoder.decode(bytes.subarray(pos + 7, end));
+                    this.interpreter.SetNode(view.getUint32(pos + 1, true), selector ? this.doc.querySelector(selector) : this.doc);
+                    break;
+                }
+                case LISTEN: {
+                    const id = view.getUint32(pos + 1, true);
+                    const name = this.decoder.decode(bytes.subarray(pos + 7, end));
+                    const onEvent = this.onEvent;
+                    if (onEvent) {
+                        this.interpreter.GetNode(id).addEventListener(name, (e) => onEvent(id, name, e));
+                    }
+                    break;
+                }
+            }
+            pos = end;
+        }
+        this.pending = bytes.slice(pos);
+        return errors;
+    }
+
+    // the end of the frame starting at pos, or undefined if it has not been completely received
+    frameEnd(view, pos) {
+        const len = view.byteLength;
+        if (pos >= len) {
+            return undefined;
+        }
+        switch (view.getUint8(pos)) {
+            case BATCH: {
+                if (pos + 5 > len) {
+                    return undefined;
+                }
+                const strLenPos = pos + 5 + view.getUint32(pos + 1, true);
+                if (strLenPos + 4 > len) {
+                    return undefined;
+                }
+                const end = strLenPos + 4 + view.getUint32(strLenPos, true);
+                return end > len ? undefined : end;
+            }
+            case SET_NODE:
+            case LISTEN: {
+                if (pos + 7 > len) {
+                    return undefined;
+                }
+                const end = pos + 7 + view.getUint16(pos + 5, true);
+                return end > len ? undefined : end;
+            }
+            default:
+                throw new Error(view.getUint8(pos) + " is not a frame");
+        }
+    }
+}
@@ -0,0 +1,164 @@
+//! The javascript interpreters are generated from the specification in sledgehammer-encoder and checked in. Run this test with `SLEDGEHAMMER_BLESS=1` to regenerate them after changing the operations or `interpreter.js`, see `CONTRIBUTING.md`.
+//!
+//! The other tests run batches with the interpreters in node with the small dom in `tests/js`, and compare the result with the native interpreter.
+
+use std::{
+    fs,
+    io::{self, Write},
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use sledgehammer_encoder::{
+    batch::{Batch, FinalizedBatch},
+    spec::{debug_js, js_definitions, optimize_js, replace_generated},
+    Attribute, Element, InsertPosition, MaybeId, NativeInterpreter, NodeId, ShadowRootMode,
+};
+
+const INTERPRETERS: [&str; 3] = [
+    "interpreter.js",
+    "interpreter_opt.js",
+    "interpreter_debug.js",
+];
+
+/// Run batches with every interpreter in node and return the output of `tests/js/run.mjs` for each of them: the body after every batch and the ids with a stored node. The strings are in the wasm memory, or transferred like the batches of a worker.
+///
+/// Returns `None` if node is not installed, unless the `CI` environment variable is set.
+fn run_js(batches: &[FinalizedBatch], transferred: bool) -> Option<Vec<String>> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let json = batches
+        .iter()
+        .map(|batch| {
+            format!(
+                r#"{{"msg":{:?},"str":{:?},"transferred":{transferred}}}"#,
+                batch.msg, batch.str
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+    let mut outputs = Vec::new();
+    for interpreter in INTERPRETERS {
+        let child = Command::new("node")
+            .arg(dir.join("tests/js/run.mjs"))
+            .arg(dir.join(interpreter))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn();
+        let mut child = match child {
+            Err(err)
+                if err.kind() == io::ErrorKind::NotFound && std::env::var_os("CI").is_none() =>
+            {
+                eprintln!("node is not installed, skipping the javascript interpreters");
+                return None;
+            }
+            child => child.expect("failed to run node"),
+        };
+        write!(child.stdin.take().unwrap(), "[{json}]").unwrap();
+        let output = child.wait_with_output().unwrap();
+        assert!(output.status.success(), "{interpreter} failed in node");
+        outputs.push(String::from_utf8(output.stdout).unwrap());
+    }
+    Some(outputs)
+}
+
+/// The output `tests/js/run.mjs` should print for batches run with the native interpreter
+fn run_native(batches: &[FinalizedBatch]) -> String {
+    let (mut interpreter, body) = NativeInterpreter::with_body();
+    let mut out = String::new();
+    for batch in batches {
+        interpreter.run(&batch.msg, &batch.str).unwrap();
+        out += &interpreter.dom().to_html(body);
+        out.push('\n');
+    }
+    let ids = (0..64)
+        .filter(|&id| interpreter.get_node(NodeId(id)).is_some())
+        .map(|id| id.to_string());
+    out + &format!("ids: {}\n", ids.collect::<Vec<_>>().join(" "))
+}
+
+#[test]
+fn generated_interpreters_are_current() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let read = |name: &str| fs::read_to_string(dir.join(name)).unwrap();
+    let interpreter = replace_generated(&read("interpreter.js"), &js_definitions(false))
+        .expect("interpreter.js has a generated region");
+    let optimized = optimize_js(&interpreter).expect("interpreter.js has a generated region");
+    let debug = debug_js(&interpreter, &read("debug_checks.js"))
+        .expect("interpreter.js has an exported JsInterpreter class");
+
+    let bless = std::env::var_os("SLEDGEHAMMER_BLESS").is_some();
+    for (name, generated) in [
+        ("interpreter.js", interpreter),
+        ("interpreter_opt.js", optimized),
+        ("interpreter_debug.js", debug),
+    ] {
+        if bless {
+            fs::write(dir.join(name), generated).unwrap();
+        } else {
+            assert!(
+                read(name) == generated,
+                "{name} is out of date, run `SLEDGEHAMMER_BLESS=1 cargo test -p sledgehammer --test interpreters` to regenerate it and commit the result, see CONTRIBUTING.md"
+            );
+        }
+    }
+}
+
+#[test]
+fn non_ascii_strings_are_read_like_the_native_interpreter() {
+    let mut batch = Batch::default();
+    batch.create_element(Element::p, Some(NodeId(1)));
+    batch.set_attribute(Attribute::title, "ünïcode 🦀", MaybeId::LastNode);
+    batch.create_text_node("日本語 ", None);
+    batch.append_child(MaybeId::Node(NodeId(1)), MaybeId::LastNode);
+    batch.insert_adjacent_html(
+        InsertPosition::BeforeEnd,
+        "<b>🦀</b>é",
+        MaybeId::Node(NodeId(1)),
+        None,
+    );
+    batch.create_comment("ça", None);
+    batch.append_child(MaybeId::Node(NodeId(1)), MaybeId::LastNode);
+    // the strings after the non-ascii strings are read from the right offset
+    batch.set_attribute(Attribute::class, "after", MaybeId::Node(NodeId(1)));
+    batch.append_child(MaybeId::Node(NodeId(0)), MaybeId::Node(NodeId(1)));
+    let batches = [batch.finalize()];
+
+    let native = run_native(&batches);
+    assert_eq!(
+        native,
+        "<body><p title=\"ünïcode 🦀\" class=\"after\">日本語 <b>🦀</b>é<!--ça--></p></body>\nids: 0 1\n"
+    );
+    for transferred in [false, true] {
+        for output in run_js(&batches, transferred).into_iter().flatten() {
+            assert_eq!(output, native);
+        }
+    }
+}
+
+#[test]
+fn removed_shadow_roots_free_their_nodes() {
+    let mut batch = Batch::default();
+    batch.create_element(Element::div, Some(NodeId(1)));
+    batch.append_child(MaybeId::Node(NodeId(0)), MaybeId::LastNode);
+    batch.attach_shadow(MaybeId::Node(NodeId(1)), ShadowRootMode::Closed, NodeId(2));
+    batch.create_element(Element::p, Some(NodeId(3)));
+    batch.append_child(MaybeId::Node(NodeId(2)), MaybeId::LastNode);
+    batch.create_element(Element::span, Some(NodeId(4)));
+    batch.append_child(MaybeId::Node(NodeId(0)), MaybeId::LastNode);
+    let first = batch.finalize();
+    let mut batch = Batch::default();
+    batch.remove_and_free(MaybeId::Node(NodeId(1)));
+    let batches = [first, batch.finalize()];
+
+    let native = run_native(&batches);
+    assert_eq!(
+        native,
+        "<body><div><template shadowrootmode=\"closed\"><p></p></template></div><span></span></body>\n<body><span></span></body>\nids: 0 4\n"
+    );
+    for transferred in [false, true] {
+        for output in run_js(&batches, transferred).into_iter().flatten() {
+            assert_eq!(output, native);
+        }
+    }
+}
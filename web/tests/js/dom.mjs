@@ -0,0 +1,270 @@
+// A small dom with the parts of the browser api the interpreters use, so they can run in node.
+// Nodes serialize like Dom::to_html in sledgehammer-encoder, so the output can be compared with the native interpreter.
+
+const ELEMENT = 1;
+const TEXT = 3;
+const COMMENT = 8;
+const FRAGMENT = 11;
+
+class Node {
+    constructor(nodeType, nodeName) {
+        this.nodeType = nodeType;
+        this.nodeName = nodeName;
+        this.childNodes = [];
+        this.parentNode = null;
+        this.attributes = new Map();
+        this.data = "";
+        this.style = new Style();
+        this.shadowRoot = null;
+    }
+
+    get firstChild() {
+        return this.childNodes[0] ?? null;
+    }
+
+    get lastChild() {
+        return this.childNodes[this.childNodes.length - 1] ?? null;
+    }
+
+    get nextSibling() {
+        const siblings = this.parentNode?.childNodes ?? [];
+        return siblings[siblings.indexOf(this) + 1] ?? null;
+    }
+
+    get previousSibling() {
+        const siblings = this.parentNode?.childNodes ?? [];
+        return siblings[siblings.indexOf(this) - 1] ?? null;
+    }
+
+    // inserts nodes before the child at index, moving the children of fragments
+    insertAt(index, nodes) {
+        const inserted = [];
+        for (const node of nodes) {
+            inserted.push(...(node.nodeType === FRAGMENT ? [...node.childNodes] : [node]));
+        }
+        for (const node of inserted) {
+            if (node.parentNode === this && this.childNodes.indexOf(node) < index) index--;
+            node.remove();
+            node.parentNode = this;
+        }
+        this.childNodes.splice(index, 0, ...inserted);
+    }
+
+    appendChild(node) {
+        this.insertAt(this.childNodes.length, [node]);
+        return node;
+    }
+
+    append(...nodes) {
+        this.insertAt(this.childNodes.length, nodes);
+    }
+
+    before(...nodes) {
+        this.parentNode.insertAt(this.parentNode.childNodes.indexOf(this), nodes);
+    }
+
+    after(...nodes) {
+        this.parentNode.insertAt(this.parentNode.childNodes.indexOf(this) + 1, nodes);
+    }
+
+    replaceWith(...nodes) {
+        const parent = this.parentNode;
+        const index = parent.childNodes.indexOf(this);
+        this.remove();
+        parent.insertAt(index, nodes);
+    }
+
+    remove() {
+        if (this.parentNode) {
+            this.parentNode.childNodes.splice(this.parentNode.childNodes.indexOf(this), 1);
+            this.parentNode = null;
+        }
+    }
+
+    // like the browser, this does not look into shadow roots
+    contains(node) {
+        for (; node; node = node.parentNode) {
+            if (node === this) return true;
+        }
+        return false;
+    }
+
+    get textContent() {
+        if (this.nodeType === TEXT || this.nodeType === COMMENT) return this.data;
+        return this.childNodes.filter((child) => child.nodeType !== COMMENT).map((child) => child.textContent).join("");
+    }
+
+    set textContent(text) {
+        if (this.nodeType === TEXT || this.nodeType === COMMENT) {
+            this.data = text;
+        } else {
+            [...this.childNodes].forEach((child) => child.remove());
+            if (text) this.appendChild(document.createTextNode(text));
+        }
+    }
+
+    set innerHTML(html) {
+        [...this.childNodes].forEach((child) => child.remove());
+        this.append(...parse(html));
+    }
+
+    insertAdjacentHTML(position, html) {
+        const nodes = parse(html);
+        switch (position) {
+            case "beforebegin": return this.before(...nodes);
+            case "afterbegin": return this.insertAt(0, nodes);
+            case "beforeend": return this.append(...nodes);
+            case "afterend": return this.after(...nodes);
+        }
+    }
+
+    setAttribute(name, value) {
+        this.attributes.set(name, value);
+    }
+
+    setAttributeNS(_namespace, name, value) {
+        this.attributes.set(name, value);
+    }
+
+    removeAttribute(name) {
+        this.attributes.delete(name);
+    }
+
+    removeAttributeNS(_namespace, name) {
+        this.attributes.delete(name);
+    }
+
+    attachShadow({ mode }) {
+        this.shadowRoot = new Node(FRAGMENT, "#shadow-root");
+        this.shadowRoot.mode = mode;
+        this.shadowRoot.host = this;
+        this.shadowRoot.adoptedStyleSheets = [];
+        return this.shadowRoot;
+    }
+
+    cloneNode(deep) {
+        const clone = new Node(this.nodeType, this.nodeName);
+        clone.data = this.data;
+        clone.attributes = new Map(this.attributes);
+        clone.namespaceURI = this.namespaceURI;
+        if (this.content) clone.content = this.content.cloneNode(true);
+        if (deep) this.childNodes.forEach((child) => clone.appendChild(child.cloneNode(true)));
+        return clone;
+    }
+
+    appendData(data) {
+        this.data += data;
+    }
+
+    insertData(offset, data) {
+        this.data = this.data.slice(0, offset) + data + this.data.slice(offset);
+    }
+
+    deleteData(offset, count) {
+        this.data = this.data.slice(0, offset) + this.data.slice(offset + count);
+    }
+
+    replaceData(offset, count, data) {
+        this.data = this.data.slice(0, offset) + data + this.data.slice(offset + count);
+    }
+
+    toString() {
+        switch (this.nodeType) {
+            case TEXT:
+                return this.data;
+            case COMMENT:
+                return `<!--${this.data}-->`;
+            case FRAGMENT:
+                return this.childNodes.join("");
+        }
+        let html = `<${this.nodeName}`;
+        for (const [name, value] of this.attributes) {
+            html += ` ${name}="${value}"`;
+        }
+        if (this.style.properties.size) {
+            html += ` style="${this.style}"`;
+        }
+        html += ">";
+        if (this.shadowRoot) {
+            html += `<template shadowrootmode="${this.shadowRoot.mode}">${this.shadowRoot}</template>`;
+        }
+        return `${html}${this.content ?? ""}${this.childNodes.join("")}</${this.nodeName}>`;
+    }
+}
+
+class Style {
+    constructor() {
+        this.properties = new Map();
+    }
+
+    setProperty(name, value, priority) {
+        this.properties.set(name, priority ? `${value} !${priority}` : value);
+    }
+
+    removeProperty(name) {
+        this.properties.delete(name);
+    }
+
+    toString() {
+        return [...this.properties].map(([name, value]) => `${name}: ${value};`).join(" ");
+    }
+}
+
+class CSSStyleSheet {
+    replaceSync(css) {
+        this.css = css;
+    }
+}
+
+// parses the html the tests use: elements with quoted attributes, text and comments
+function parse(html) {
+    const root = new Node(FRAGMENT, "#document-fragment");
+    const tokens = /<!--(.*?)-->|<\/([\w-]+)>|<([\w-]+)((?:\s+[\w-]+="[^"]*")*)\s*>|([^<]+)/gs;
+    let parent = root;
+    for (const [, comment, close, open, attributes, text] of html.matchAll(tokens)) {
+        if (comment !== undefined) {
+            parent.appendChild(document.createComment(comment));
+        } else if (close) {
+            parent = parent.parentNode;
+        } else if (open) {
+            const element = parent.appendChild(document.createElement(open));
+            for (const [, name, value] of attributes.matchAll(/([\w-]+)="([^"]*)"/g)) {
+                element.setAttribute(name, value);
+            }
+            parent = element;
+        } else {
+            parent.appendChild(document.createTextNode(text));
+        }
+    }
+    return [...root.childNodes];
+}
+
+export const document = {
+    defaultView: { CSSStyleSheet },
+    createElement(name) {
+        const element = new Node(ELEMENT, name);
+        if (name === "template") element.content = new Node(FRAGMENT, "#document-fragment");
+        return element;
+    },
+    createElementNS(namespace, name) {
+        const element = new Node(ELEMENT, name);
+        element.namespaceURI = namespace;
+        return element;
+    },
+    createTextNode(text) {
+        const node = new Node(TEXT, "#text");
+        node.data = text;
+        return node;
+    },
+    createComment(text) {
+        const node = new Node(COMMENT, "#comment");
+        node.data = text;
+        return node;
+    },
+    createDocumentFragment() {
+        return new Node(FRAGMENT, "#document-fragment");
+    },
+    importNode(node, deep) {
+        return node.cloneNode(deep);
+    },
+};
@@ -0,0 +1,47 @@
+// Runs batches with an interpreter in node: `node run.mjs <interpreter.js>` with the batches as json on stdin.
+// The batches are [{ msg: [...], str: [...], transferred: bool }, ...] and run with a <body> stored as the node with the id 0, like the backend of a channel would run them.
+// Prints the body after every batch, preceded by the error of the batch if it failed, and then the ids that still have a node stored.
+
+import { readFileSync } from "fs";
+import { pathToFileURL } from "url";
+import { document } from "./dom.mjs";
+
+const { JsInterpreter } = await import(pathToFileURL(process.argv[2]));
+const batches = JSON.parse(readFileSync(0, "utf8"));
+
+// the metadata and the pointers to the batch are at the start of the memory, followed by the batch
+const [metadataPtr, msgPtrPtr, strPtrPtr, strLenPtr, batchPtr] = [0, 4, 8, 12, 16];
+const size = batches.reduce((max, batch) => Math.max(max, batch.msg.length + batch.str.length), 0);
+const mem = { buffer: new ArrayBuffer(batchPtr + size + 8) };
+const view = new DataView(mem.buffer);
+const interpreter = new JsInterpreter(mem, metadataPtr, msgPtrPtr, strPtrPtr, strLenPtr, document);
+const body = document.createElement("body");
+interpreter.SetNode(0, body);
+
+for (const batch of batches) {
+    let error;
+    if (batch.transferred) {
+        error = interpreter.WorkTransferred(new Uint8Array(batch.msg).buffer, new Uint8Array(batch.str).buffer);
+    } else {
+        const strPtr = batchPtr + batch.msg.length;
+        new Uint8Array(mem.buffer, batchPtr, batch.msg.length).set(batch.msg);
+        new Uint8Array(mem.buffer, strPtr, batch.str.length).set(batch.str);
+        view.setUint32(msgPtrPtr, batchPtr, true);
+        view.setUint32(strPtrPtr, strPtr, true);
+        view.setUint32(strLenPtr, batch.str.length, true);
+        // the same flags as the backend of a channel: both pointers changed, if there are strings, and if they are small and ascii
+        let metadata = 1 | 2;
+        if (batch.str.length) {
+            metadata |= 4;
+            if (batch.str.length < 100 && batch.str.every((byte) => byte < 128)) metadata |= 8;
+        }
+        view.setUint8(metadataPtr, metadata);
+        interpreter.SetMsgLen?.(batch.msg.length);
+        error = interpreter.Work();
+    }
+    if (error !== undefined) console.log(`error: ${JSON.stringify(error)}`);
+    console.log(`${body}`);
+}
+// the optimized interpreter shortens the name of the nodes
+const ids = (interpreter.nodes ?? interpreter.n).flatMap((node, id) => (node === undefined ? [] : [id]));
+console.log(`ids: ${ids.join(" ")}`);